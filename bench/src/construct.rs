@@ -88,6 +88,10 @@ impl core::BenchmarkDescription for ConstructionBenchmarkDescription {
 			BlockType::RandomTransfersKeepAlive => path.push("transfer"),
 			BlockType::RandomTransfersReaping => path.push("transfer_reaping"),
 			BlockType::Noop => path.push("noop"),
+			BlockType::DexSwaps => path.push("dex_swap"),
+			BlockType::DexMultiHopSwaps => path.push("dex_multi_hop_swap"),
+			BlockType::DpoSharePurchases => path.push("dpo_share_purchase"),
+			BlockType::LargeDpoYieldReleases => path.push("large_dpo_yield_release"),
 		}
 
 		match self.database_type {
@@ -108,6 +112,9 @@ impl core::BenchmarkDescription for ConstructionBenchmarkDescription {
 			50_000,
 			self.key_types
 		);
+		if self.block_type.requires_seeded_state() {
+			bench_db.seed_state();
+		}
 
 		let client = bench_db.client();
 
@@ -159,10 +166,11 @@ impl core::Benchmark for ConstructionBenchmark {
 
 		let start = std::time::Instant::now();
 
+		let best_hash = context.client.chain_info().best_hash;
 		let proposer = futures::executor::block_on(proposer_factory.init(
-			&context.client.header(&BlockId::number(0))
-				.expect("Database error querying block #0")
-				.expect("Block #0 should exist"),
+			&context.client.header(&BlockId::Hash(best_hash))
+				.expect("Database error querying the best block")
+				.expect("The best block should exist"),
 		)).expect("Proposer initialization failed");
 
 		let _block = futures::executor::block_on(
@@ -77,6 +77,10 @@ impl core::BenchmarkDescription for ImportBenchmarkDescription {
 			BlockType::RandomTransfersKeepAlive => path.push("transfer_keep_alive"),
 			BlockType::RandomTransfersReaping => path.push("transfer_reaping"),
 			BlockType::Noop => path.push("noop"),
+			BlockType::DexSwaps => path.push("dex_swap"),
+			BlockType::DexMultiHopSwaps => path.push("dex_multi_hop_swap"),
+			BlockType::DpoSharePurchases => path.push("dpo_share_purchase"),
+			BlockType::LargeDpoYieldReleases => path.push("large_dpo_yield_release"),
 		}
 
 		match self.database_type {
@@ -96,6 +100,9 @@ impl core::BenchmarkDescription for ImportBenchmarkDescription {
 			50_000,
 			self.key_types
 		);
+		if self.block_type.requires_seeded_state() {
+			bench_db.seed_state();
+		}
 		let block = bench_db.generate_block(self.block_type.to_content(self.size.transactions()));
 		Box::new(ImportBenchmark {
 			database: bench_db,
@@ -134,8 +141,8 @@ impl core::Benchmark for ImportBenchmark {
 
 		// Sanity checks.
 		context.client
-			.state_at(&BlockId::number(1))
-			.expect("state_at failed for block#1")
+			.state_at(&BlockId::number(self.block.header.number))
+			.expect("state_at failed for the imported block")
 			.inspect_state(|| {
 				match self.block_type {
 					BlockType::RandomTransfersKeepAlive => {
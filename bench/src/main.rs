@@ -97,6 +97,10 @@ fn main() {
 				BlockType::RandomTransfersKeepAlive,
 				BlockType::RandomTransfersReaping,
 				BlockType::Noop,
+				BlockType::DexSwaps,
+				BlockType::DexMultiHopSwaps,
+				BlockType::DpoSharePurchases,
+				BlockType::LargeDpoYieldReleases,
 			].iter() {
 				for database_type in [BenchDataBaseType::RocksDb, BenchDataBaseType::ParityDb].iter() {
 					import_benchmarks.push((profile, size.clone(), block_type.clone(), database_type));
@@ -150,6 +154,34 @@ fn main() {
 			size: SizeType::Large,
 			database_type: BenchDataBaseType::RocksDb,
 		},
+		ConstructionBenchmarkDescription {
+			profile: Profile::Wasm,
+			key_types: KeyTypes::Sr25519,
+			block_type: BlockType::DexSwaps,
+			size: SizeType::Medium,
+			database_type: BenchDataBaseType::RocksDb,
+		},
+		ConstructionBenchmarkDescription {
+			profile: Profile::Wasm,
+			key_types: KeyTypes::Sr25519,
+			block_type: BlockType::DexMultiHopSwaps,
+			size: SizeType::Medium,
+			database_type: BenchDataBaseType::RocksDb,
+		},
+		ConstructionBenchmarkDescription {
+			profile: Profile::Wasm,
+			key_types: KeyTypes::Sr25519,
+			block_type: BlockType::DpoSharePurchases,
+			size: SizeType::Medium,
+			database_type: BenchDataBaseType::RocksDb,
+		},
+		ConstructionBenchmarkDescription {
+			profile: Profile::Wasm,
+			key_types: KeyTypes::Sr25519,
+			block_type: BlockType::LargeDpoYieldReleases,
+			size: SizeType::Medium,
+			database_type: BenchDataBaseType::RocksDb,
+		},
 		PoolBenchmarkDescription { database_type: BenchDataBaseType::RocksDb },
 	);
 
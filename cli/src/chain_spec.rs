@@ -22,6 +22,7 @@ use grandpa_primitives::AuthorityId as GrandpaId;
 use hex_literal::hex;
 use spanner_runtime as spanner;
 use hammer_runtime as hammer;
+use pallet_bullet_train::YieldSchedule;
 use pallet_dex::TradingPair;
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_chain_spec::ChainSpecExtension;
@@ -37,7 +38,7 @@ use sp_runtime::{
     Perbill,
 };
 
-pub use node_primitives::{AccountId, Balance, Signature, Block, TokenSymbol, CurrencyId};
+pub use node_primitives::{AccountId, Balance, BlockNumber, Signature, Block, TokenSymbol, CurrencyId};
 
 type AccountPublic = <Signature as Verify>::Signer;
 
@@ -97,6 +98,7 @@ fn spanner_development_config_genesis() -> spanner::GenesisConfig {
         get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
         true,
+        true,
     )
 }
 
@@ -113,6 +115,7 @@ pub fn spanner_testnet_genesis(
     root_key: AccountId,
     endowed_accounts: Option<Vec<AccountId>>,
     _enable_println: bool,
+    seed_bullet_train: bool,
 ) -> spanner::GenesisConfig {
     let mut endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(testnet_accounts);
     initial_authorities.iter().for_each(|x| {
@@ -127,6 +130,16 @@ pub fn spanner_testnet_genesis(
     const STASH: Balance = 0;
     const INITIAL_BALANCE: u128 = 1_000_000 * spanner::constants::currency::BOLTS;
 
+    let bullet_train = if seed_bullet_train {
+        testnet_bullet_train_genesis(
+            root_key.clone(),
+            spanner::constants::currency::BOLTS,
+            spanner::constants::time::DAYS,
+        )
+    } else {
+        Default::default()
+    };
+
     spanner::GenesisConfig {
         frame_system: Some(spanner::SystemConfig {
             code: spanner::wasm_binary_unwrap().to_vec(),
@@ -229,6 +242,7 @@ pub fn spanner_testnet_genesis(
             initial_enabled_trading_pairs: testnet_trading_pairs(),
             initial_added_liquidity_pools: vec![],
         }),
+        pallet_bullet_train: Some(bullet_train),
     }
 }
 
@@ -256,6 +270,7 @@ fn hammer_development_config_genesis() -> hammer::GenesisConfig {
         get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
         true,
+        true,
     )
 }
 
@@ -272,6 +287,7 @@ pub fn hammer_testnet_genesis(
     root_key: AccountId,
     endowed_accounts: Option<Vec<AccountId>>,
     _enable_println: bool,
+    seed_bullet_train: bool,
 ) -> hammer::GenesisConfig {
     let mut endowed_accounts: Vec<AccountId> = endowed_accounts.unwrap_or_else(testnet_accounts);
     initial_authorities.iter().for_each(|x| {
@@ -286,6 +302,16 @@ pub fn hammer_testnet_genesis(
     const STASH: Balance = 100;
     const INITIAL_BALANCE: u128 = 1_000_000 * hammer::constants::currency::BOLTS;
 
+    let bullet_train = if seed_bullet_train {
+        testnet_bullet_train_genesis(
+            root_key.clone(),
+            hammer::constants::currency::BOLTS,
+            hammer::constants::time::DAYS,
+        )
+    } else {
+        Default::default()
+    };
+
     hammer::GenesisConfig {
         frame_system: Some(hammer::SystemConfig {
             code: hammer::wasm_binary_unwrap().to_vec(),
@@ -388,6 +414,7 @@ pub fn hammer_testnet_genesis(
             initial_enabled_trading_pairs: testnet_trading_pairs(),
             initial_added_liquidity_pools: vec![],
         }),
+        pallet_bullet_train: Some(bullet_train),
     }
 }
 
@@ -400,6 +427,7 @@ fn spanner_local_testnet_genesis() -> spanner::GenesisConfig {
         get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
         false,
+        true,
     )
 }
 
@@ -430,6 +458,7 @@ fn hammer_local_testnet_genesis() -> hammer::GenesisConfig {
         get_account_id_from_seed::<sr25519::Public>("Alice"),
         None,
         false,
+        true,
     )
 }
 
@@ -546,7 +575,7 @@ fn spanner_staging_testnet_config_genesis() -> spanner::GenesisConfig {
     ].into();
 
     let endowed_accounts: Vec<AccountId> = vec![root_key.clone()];
-    spanner_testnet_genesis(initial_authorities, root_key, Some(endowed_accounts), false)
+    spanner_testnet_genesis(initial_authorities, root_key, Some(endowed_accounts), false, false)
 }
 
 /// Spanner staging testnet config.
@@ -634,6 +663,41 @@ fn testnet_trading_pairs() -> Vec<TradingPair> {
     ]
 }
 
+/// travel cabins in BOLT, WUSD and NCAT funded by the engineer account, milestone rewards,
+/// and a dpo of the manager buying the BOLT cabin, so QA does not start from an empty train
+fn testnet_bullet_train_genesis<T>(
+    manager: AccountId,
+    bolts: Balance,
+    days: BlockNumber,
+) -> pallet_bullet_train::GenesisConfig<T>
+where
+    T: pallet_bullet_train::Config<AccountId = AccountId, BlockNumber = BlockNumber>,
+{
+    let bolt = CurrencyId::Token(TokenSymbol::BOLT);
+    let wusd = CurrencyId::Token(TokenSymbol::WUSD);
+    let ncat = CurrencyId::Token(TokenSymbol::NCAT);
+
+    pallet_bullet_train::GenesisConfig {
+        engineer_endowment: vec![
+            (bolt, 1_000_000 * bolts),
+            (wusd, 1_000_000 * bolts),
+            (ncat, 1_000_000 * bolts),
+        ],
+        initial_travel_cabins: vec![
+            (bolt, b"BOLT Express".to_vec(), 100_000 * bolts, 1_000 * bolts, 10_000 * bolts, 30 * days, 10, YieldSchedule::Linear),
+            (wusd, b"WUSD Weekly".to_vec(), 10_000 * bolts, 100 * bolts, 1_000 * bolts, 28 * days, 10, YieldSchedule::Periodic(7 * days)),
+            (ncat, b"NCAT Sleeper".to_vec(), 10_000 * bolts, 100 * bolts, 1_000 * bolts, 90 * days, 5, YieldSchedule::CliffLinear(30 * days)),
+        ],
+        initial_milestone_rewards: vec![
+            (bolt, 500_000 * bolts, 10_000 * bolts),
+            (wusd, 50_000 * bolts, 1_000 * bolts),
+        ],
+        initial_dpos: vec![
+            (manager, b"BOLT Express DPO".to_vec(), 0, 10_000 * bolts, 10, 7 * days),
+        ],
+    }
+}
+
 /// Helper function to generate a crypto pair from seed
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
     TPublic::Pair::from_string(&format!("//{}", seed), None)
@@ -685,6 +749,7 @@ pub(crate) mod tests {
             get_account_id_from_seed::<sr25519::Public>("Alice"),
             None,
             false,
+            false,
         )
     }
 
@@ -16,7 +16,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use sc_cli::{RunCmd, KeySubcommand, SignCmd, VanityCmd, VerifyCmd};
+use std::path::PathBuf;
+use sc_cli::{CliConfiguration, RunCmd, KeySubcommand, SharedParams, SignCmd, VanityCmd, VerifyCmd};
 use structopt::StructOpt;
 
 /// An overarching CLI command definition.
@@ -76,4 +77,42 @@ pub enum Subcommand {
 
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
+
+	/// The custom try-runtime subcommand replaying the runtime migrations offline.
+	#[structopt(
+		name = "try-runtime",
+		about = "Run the runtime migrations against a state snapshot written by export-state."
+	)]
+	TryRuntime(TryRuntimeCmd),
+
+	/// The custom export-types subcommand writing the type definitions of the runtime.
+	#[structopt(
+		name = "export-types",
+		about = "Write types.json and types_mapping.json from the types of the native runtime."
+	)]
+	ExportTypes(ExportTypesCmd),
+}
+
+/// The `try-runtime` command. The snapshot is passed with `--chain`, as the chain spec
+/// written by `export-state`; its storage is loaded in memory, without any network.
+#[derive(Debug, StructOpt)]
+pub struct TryRuntimeCmd {
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+}
+
+impl CliConfiguration for TryRuntimeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+}
+
+/// The `export-types` command. Writes the polkadot.js type definitions, `types.json`, and the
+/// py-substrate-interface type mappings, `types_mapping.json`, of the native runtime.
+#[derive(Debug, StructOpt)]
+pub struct ExportTypesCmd {
+	/// Directory the files are written to.
+	#[structopt(long, parse(from_os_str), default_value = ".")]
+	pub path: PathBuf,
 }
@@ -53,6 +53,8 @@ pub trait RuntimeApiCollection:
     + sp_authority_discovery::AuthorityDiscoveryApi<Block>
     + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
     + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+    + pallet_bullet_train_rpc_runtime_api::BulletTrainApi<Block, AccountId>
+    + pallet_rewards_rpc_runtime_api::RewardsApi<Block, AccountId, BlockNumber>
     + sp_session::SessionKeys<Block>
 where
     <Self as sp_api::ApiExt<Block>>::StateBackend: sp_api::StateBackend<BlakeTwo256>,
@@ -70,6 +72,8 @@ where
         + sp_authority_discovery::AuthorityDiscoveryApi<Block>
         + frame_system_rpc_runtime_api::AccountNonceApi<Block, AccountId, Index>
         + pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
+        + pallet_bullet_train_rpc_runtime_api::BulletTrainApi<Block, AccountId>
+        + pallet_rewards_rpc_runtime_api::RewardsApi<Block, AccountId, BlockNumber>
         + sp_session::SessionKeys<Block>,
     <Self as sp_api::ApiExt<Block>>::StateBackend: sp_api::StateBackend<BlakeTwo256>,
 {}
@@ -20,6 +20,7 @@ use crate::{chain_spec, service, Cli, Subcommand};
 use node_executor::{SpannerExecutor, HammerExecutor};
 use sc_cli::{Result, SubstrateCli, RuntimeVersion, Role, ChainSpec};
 use crate::client::IdentifyVariant;
+use std::path::Path;
 
 impl SubstrateCli for Cli {
 	fn impl_name() -> String {
@@ -159,6 +160,49 @@ pub fn run() -> Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		#[cfg(feature = "try-runtime")]
+		Some(Subcommand::TryRuntime(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.sync_run(|config| try_runtime(config.chain_spec))
+		},
+		#[cfg(not(feature = "try-runtime"))]
+		Some(Subcommand::TryRuntime(_)) => {
+			Err("TryRuntime wasn't enabled when building the node. \
+			You can enable it with `--features try-runtime`.".into())
+		},
+		Some(Subcommand::ExportTypes(cmd)) => export_types(&cmd.path),
+	}
+}
+
+/// Writes the type registry of the native runtime into `path`. The spanner and hammer
+/// runtimes share their custom types.
+fn export_types(path: &Path) -> Result<()> {
+	let registry = spanner_runtime::type_registry::type_registry();
+	std::fs::write(path.join("types.json"), registry.types_json())?;
+	std::fs::write(path.join("types_mapping.json"), registry.types_mapping_json())?;
+	Ok(())
+}
+
+/// Runs the migrations of the native runtime on the storage of the given snapshot,
+/// reporting the weight used. Failed invariants are logged by the runtime.
+#[cfg(feature = "try-runtime")]
+fn try_runtime(chain_spec: Box<dyn ChainSpec>) -> Result<()> {
+	use sp_runtime::BuildStorage;
+
+	let storage = chain_spec.build_storage()?;
+	let mut ext = sp_io::TestExternalities::new(storage);
+	let result = if chain_spec.is_hammer() {
+		ext.execute_with(hammer_runtime::try_runtime_upgrade)
+	} else {
+		// else we assume it is spanner itself.
+		ext.execute_with(spanner_runtime::try_runtime_upgrade)
+	};
+	match result {
+		Ok(weight) => {
+			println!("Runtime upgrade of {} succeeded, weight used: {}", chain_spec.id(), weight);
+			Ok(())
+		},
+		Err(e) => Err(format!("Runtime upgrade of {} failed: {}", chain_spec.id(), e).into()),
 	}
 }
 
@@ -395,6 +395,7 @@ where
 	})
 }
 
+/// Builds a new service for a full client of the spanner or hammer chain of `config`.
 pub fn build_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 	if config.chain_spec.is_spanner() {
 		new_full::<spanner_runtime::RuntimeApi, SpannerExecutor>(config)
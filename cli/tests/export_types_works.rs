@@ -0,0 +1,26 @@
+use assert_cmd::cargo::cargo_bin;
+use std::{fs, path::Path, process::Command};
+use tempfile::tempdir;
+
+#[test]
+fn export_types_works() {
+	let path = tempdir().expect("could not create a temp dir");
+
+	let status = Command::new(cargo_bin("substrate"))
+		.args(&["export-types", "--path"])
+		.arg(path.path())
+		.status()
+		.unwrap();
+	assert!(status.success());
+
+	// the checked-in registries have to follow the runtime types
+	let repository = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+	for file in &["types.json", "types_mapping.json"] {
+		assert_eq!(
+			fs::read_to_string(path.path().join(file)).unwrap(),
+			fs::read_to_string(repository.join(file)).unwrap(),
+			"{} is stale, regenerate it with `substrate export-types`",
+			file,
+		);
+	}
+}
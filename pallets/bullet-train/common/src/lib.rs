@@ -1,5 +1,67 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
+use primitives::CurrencyId;
+use sp_runtime::RuntimeDebug;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
 pub type TravelCabinIndex = u32;
 pub type TravelCabinInventoryIndex = u16;
 pub type DpoIndex = u32;
+pub type MilestoneRewardReleaseIndex = u32;
+
+/// The balance of the pallet account in a currency against the obligations it backs
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CurrencyAccounting<Balance> {
+    pub currency_id: CurrencyId,
+    /// free balance of the pallet account
+    pub balance: Balance,
+    /// unsold cabin rewards, unwithdrawn cabin yields and fares, dpo vaults,
+    /// dpo rewards booked for the members and milestone rewards not paid yet
+    pub obligations: Balance,
+    /// balance over the obligations
+    pub surplus: Balance,
+    /// obligations not covered by the balance
+    pub deficit: Balance,
+}
+
+/// A per-entity invariant broken by a travel cabin or a dpo
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum InvariantViolation {
+    /// more cabins sold than issued
+    TravelCabinOversold,
+    /// a sold cabin has no buyer record
+    TravelCabinBuyerMissing,
+    /// a buyer withdrew more yield than the cabin yields
+    TravelCabinYieldOverWithdrawn,
+    /// the shares issued by a dpo differ from the sum of the shares of its members
+    DpoSharesMismatch,
+    /// the member count of a dpo differs from its members
+    DpoMemberCountMismatch,
+    /// the members of a dpo claimed more rewards than booked for them
+    DpoRewardsOverWithdrawn,
+}
+
+/// The result of checking the accounting of bullet-train
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct InvariantReport<Balance> {
+    /// by currency used by a cabin, a dpo or a milestone reward
+    pub currencies: Vec<CurrencyAccounting<Balance>>,
+    pub travel_cabins: Vec<(TravelCabinIndex, InvariantViolation)>,
+    pub dpos: Vec<(DpoIndex, InvariantViolation)>,
+}
+
+impl<Balance: Default + PartialEq> InvariantReport<Balance> {
+    /// no currency in deficit and no entity breaking an invariant
+    pub fn is_ok(&self) -> bool {
+        self.currencies.iter().all(|c| c.deficit == Balance::default())
+            && self.travel_cabins.is_empty()
+            && self.dpos.is_empty()
+    }
+}
@@ -1,5 +1,6 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 use codec::{Codec};
+use primitives::{Balance, BlockNumber, CurrencyId};
 use sp_std::vec::Vec;
 use pallet_bullet_train_primitives::*;
 
@@ -14,5 +15,24 @@ sp_api::decl_runtime_apis! {
         fn get_dpos_of_account(
             account: AccountId
         ) -> Vec<DpoIndex>;
+
+        fn get_travel_cabin_withdrawable_yield(
+            travel_cabin_idx: TravelCabinIndex,
+            travel_cabin_number: TravelCabinInventoryIndex,
+        ) -> Balance;
+
+        fn get_milestone_reward_releases(
+            token_id: CurrencyId,
+        ) -> Vec<(MilestoneRewardReleaseIndex, BlockNumber, Balance, Balance, Balance)>;
+
+        fn get_milestone_reward_payouts_of_account(
+            account: AccountId
+        ) -> Vec<(CurrencyId, MilestoneRewardReleaseIndex, TravelCabinIndex, TravelCabinInventoryIndex, Balance)>;
+
+        fn get_referral_earnings_of_account(
+            account: AccountId
+        ) -> Vec<(CurrencyId, Balance)>;
+
+        fn check_invariants() -> InvariantReport<Balance>;
     }
 }
@@ -7,6 +7,7 @@ use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use std::sync::Arc;
 
 use pallet_bullet_train_primitives::*;
+use primitives::{Balance, BlockNumber, CurrencyId};
 pub use pallet_bullet_train_rpc_runtime_api::BulletTrainApi as BulletTrainRuntimeApi;
 use sp_std::vec::Vec;
 
@@ -36,6 +37,41 @@ pub trait BulletTrainApi<BlockHash, AccountId> {
         account: AccountId,
         at: Option<BlockHash>,
     ) -> Result<Vec<DpoIndex>>;
+
+    #[rpc(name = "bulletTrain_getTravelCabinWithdrawableYield")]
+    fn get_travel_cabin_withdrawable_yield(
+        &self,
+        travel_cabin_idx: TravelCabinIndex,
+        travel_cabin_number: TravelCabinInventoryIndex,
+        at: Option<BlockHash>,
+    ) -> Result<Balance>;
+
+    #[rpc(name = "bulletTrain_getMilestoneRewardReleases")]
+    fn get_milestone_reward_releases(
+        &self,
+        token_id: CurrencyId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(MilestoneRewardReleaseIndex, BlockNumber, Balance, Balance, Balance)>>;
+
+    #[rpc(name = "bulletTrain_getMilestoneRewardPayoutsOfAccount")]
+    fn get_milestone_reward_payouts_of_account(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(CurrencyId, MilestoneRewardReleaseIndex, TravelCabinIndex, TravelCabinInventoryIndex, Balance)>>;
+
+    #[rpc(name = "bulletTrain_getReferralEarningsOfAccount")]
+    fn get_referral_earnings_of_account(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(CurrencyId, Balance)>>;
+
+    #[rpc(name = "bulletTrain_checkInvariants")]
+    fn check_invariants(
+        &self,
+        at: Option<BlockHash>,
+    ) -> Result<InvariantReport<Balance>>;
 }
 
 /// An implementation of bullet-train specific RPC methods
@@ -98,4 +134,99 @@ where
                 data: Some(format!("{:?}", e).into()),
             })
     }
+
+    fn get_travel_cabin_withdrawable_yield(
+        &self,
+        travel_cabin_idx: TravelCabinIndex,
+        travel_cabin_number: TravelCabinInventoryIndex,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Balance> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_travel_cabin_withdrawable_yield(&at, travel_cabin_idx, travel_cabin_number)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get withdrawable yield of travel cabin.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_milestone_reward_releases(
+        &self,
+        token_id: CurrencyId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(MilestoneRewardReleaseIndex, BlockNumber, Balance, Balance, Balance)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_milestone_reward_releases(&at, token_id)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get milestone reward releases.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_milestone_reward_payouts_of_account(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(CurrencyId, MilestoneRewardReleaseIndex, TravelCabinIndex, TravelCabinInventoryIndex, Balance)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_milestone_reward_payouts_of_account(&at, account)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get milestone reward payouts from account.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_referral_earnings_of_account(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(CurrencyId, Balance)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_referral_earnings_of_account(&at, account)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get referral earnings from account.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn check_invariants(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<InvariantReport<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.check_invariants(&at)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to check the invariants of bullet-train.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
 }
@@ -2,6 +2,7 @@ use super::*;
 use frame_benchmarking::{account, benchmarks};
 use frame_support::dispatch::UnfilteredDispatchable;
 use frame_system::RawOrigin;
+use sp_core::crypto::KeyTypeId;
 use primitives::{Balance, BlockNumber, CurrencyId, TokenSymbol};
 
 use crate::Module as BulletTrain;
@@ -36,6 +37,7 @@ fn mint_travel_cabin<T: Config>(
         yield_total,
         maturity.into(),
         stockpile,
+        YieldSchedule::Linear,
     )?;
     Ok(())
 }
@@ -57,6 +59,27 @@ fn funded_create_dpo<T: Config>(
         800,
         end.into(),
         referrer,
+        DpoAccessPolicy::Public,
+    )?;
+    Ok(())
+}
+
+fn funded_create_portfolio_dpo<T: Config>(
+    manager: T::AccountId,
+    legs: Vec<Target<Balance>>,
+    manager_share: Balance,
+    end: BlockNumber,
+) -> Result<(), &'static str> {
+    BulletTrain::<T>::create_portfolio_dpo(
+        RawOrigin::Signed(manager).into(),
+        b"benchmarking".to_vec(),
+        legs,
+        manager_share,
+        50,
+        800,
+        end.into(),
+        None,
+        DpoAccessPolicy::Public,
     )?;
     Ok(())
 }
@@ -96,6 +119,24 @@ fn funded_fill_dpo_share<T: Config>(idx: DpoIndex) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// m passengers each take the minimum share, the rest is filled by passengers taking the cap
+fn funded_fill_dpo_share_with_members<T: Config>(idx: DpoIndex, members: u32) -> Result<(), &'static str> {
+    let target_dpo = BulletTrain::<T>::dpos(idx).unwrap();
+    let share_min_percent = T::PassengerSharePercentMinimum::get();
+    let share_min = Percentage::checked_from_rational(share_min_percent.0, share_min_percent.1)
+        .unwrap_or_default().saturating_mul_int(target_dpo.target_amount);
+    for acc_index in 0..members {
+        let buyer: T::AccountId = funded_account::<T>("dpo_member", acc_index);
+        BulletTrain::<T>::passenger_buy_dpo_share(
+            RawOrigin::Signed(buyer).into(),
+            idx,
+            share_min,
+            None,
+        )?;
+    }
+    funded_fill_dpo_share::<T>(idx)
+}
+
 benchmarks! {
     create_milestone_reward {
         T::Currency::update_balance(BOLT, &BulletTrain::<T>::eng_account_id(), Balance::MAX.unique_saturated_into())?;
@@ -109,16 +150,23 @@ benchmarks! {
     }
 
     release_milestone_reward {
+        let p in 0 .. T::MilestoneRewardPayoutsPerCall::get();
+        let deposit_amount: Balance = 100_000_000_000;
+        mint_travel_cabin::<T>(BOLT, deposit_amount, 10_000_000_000, 10_000_000_000, 1, p.max(1) as TravelCabinInventoryIndex)?;
+        for i in 0..p {
+            passenger_buy_traver_cabin::<T>(account("passenger", i, SEED), 0)?;
+        }
+        let deposited = deposit_amount.saturating_mul(p.into()).max(10_000_000_000);
         MilestoneReward::<T>::insert(BOLT, MilestoneRewardInfo{
             token_id: BOLT,
-            deposited: 10_000_000_000,
-            milestones: vec!((10_000_000_000, 10_000_000_000), (10_000_000_000, 10_000_000_000), (100_000_000_000, 10_000_000_000))
+            deposited,
+            milestones: vec!((deposited, 10_000_000_000), (deposited.saturating_add(1), 10_000_000_000))
         });
-        let call = Call::<T>::release_milestone_reward(BOLT);
-        let origin = T::EngineerOrigin::successful_origin();
-    }: { call.dispatch_bypass_filter(origin)? }
+        let caller = funded_account::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller), BOLT)
     verify{
         assert_eq!(BulletTrain::<T>::milestone_reward(BOLT).unwrap().milestones.len(), 1);
+        assert_eq!(BulletTrain::<T>::milestone_reward_release_count(BOLT), 1);
     }
 
     create_travel_cabin {
@@ -130,7 +178,7 @@ benchmarks! {
         let maturity: BlockNumber = 10;
         let stockpile: TravelCabinInventoryIndex = 1;
 
-        let call = Call::<T>::create_travel_cabin(BOLT, b"test".to_vec(), deposit_amount, bonus_reward, yield_reward, maturity.into(), stockpile);
+        let call = Call::<T>::create_travel_cabin(BOLT, b"test".to_vec(), deposit_amount, bonus_reward, yield_reward, maturity.into(), stockpile, YieldSchedule::Linear);
         let origin = T::EngineerOrigin::successful_origin();
 
     }: { call.dispatch_bypass_filter(origin)? }
@@ -151,6 +199,7 @@ benchmarks! {
             bonus_total: 10_000_000_000,
             yield_total: 10_000_000_000,
             maturity: maturity.into(),
+            yield_schedule: YieldSchedule::Linear,
         });
         TravelCabinInventory::<T>::insert(0, (0, 5));
 
@@ -198,11 +247,11 @@ benchmarks! {
         let stockpile: TravelCabinInventoryIndex = 1;
 
         T::Currency::update_balance(BOLT, &BulletTrain::<T>::eng_account_id(), Balance::MAX.unique_saturated_into())?;
-        let call = Call::<T>::create_travel_cabin(BOLT, b"test".to_vec(), deposit_amount, bonus_reward, yield_reward, maturity.into(), stockpile);
+        let call = Call::<T>::create_travel_cabin(BOLT, b"test".to_vec(), deposit_amount, bonus_reward, yield_reward, maturity.into(), stockpile, YieldSchedule::Linear);
         let origin = T::EngineerOrigin::successful_origin();
         call.dispatch_bypass_filter(origin)?;
 
-    }: _(RawOrigin::Signed(caller), dpo_name.as_bytes().to_vec(), Target::TravelCabin(0), 15, 50, 800, ending_block.into(), None)
+    }: _(RawOrigin::Signed(caller), dpo_name.as_bytes().to_vec(), Target::TravelCabin(0), 15, 50, 800, ending_block.into(), None, DpoAccessPolicy::Public)
     verify{
         assert_eq!(DpoCount::<T>::get(), 1);
     }
@@ -270,10 +319,11 @@ benchmarks! {
     }
 
     release_fare_from_dpo{
+        let m in 1 .. 50;
         mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
         let manager: T::AccountId = funded_account::<T>("manager", 0);
         funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
-        funded_fill_dpo_share::<T>(0)?;
+        funded_fill_dpo_share_with_members::<T>(0, m)?;
         BulletTrain::<T>::dpo_buy_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
         BulletTrain::<T>::withdraw_fare_from_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
     }: _(RawOrigin::Signed(manager), 0)
@@ -296,14 +346,181 @@ benchmarks! {
     }
 
     release_bonus_from_dpo{
+        let m in 1 .. 50;
         mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
         let manager: T::AccountId = funded_account::<T>("manager", 0);
+        //dpo 0, manager takes 15% share
         funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
+        //dpo 1 with m members, reading them all to book the bonus remainder of its manager
+        funded_create_dpo::<T>(manager.clone(), Target::Dpo(0, 15_000_000_000), 2_250_000_000, 9)?;
+        funded_fill_dpo_share_with_members::<T>(1, m)?;
+        BulletTrain::<T>::dpo_buy_dpo_share(RawOrigin::Signed(manager.clone()).into(), 1, 0, 15_000_000_000)?;
         funded_fill_dpo_share::<T>(0)?;
         BulletTrain::<T>::dpo_buy_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
+        BulletTrain::<T>::release_bonus_from_dpo(RawOrigin::Signed(manager.clone()).into(), 0)?;
+    }: _(RawOrigin::Signed(manager), 1)
+    verify{
+        assert_eq!(BulletTrain::<T>::dpos(1).unwrap().vault_bonus, 0);
+        assert!(BulletTrain::<T>::dpo_reward_pools(1).total_bonus > 0);
+    }
+
+    continue_dpo_payout{
+        let m in 1 .. 50;
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
+        funded_fill_dpo_share_with_members::<T>(0, m)?;
+        BulletTrain::<T>::dpo_buy_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
+        BulletTrain::<T>::withdraw_fare_from_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
+        // a fare release paused before paying any member
+        let total_fare = BulletTrain::<T>::dpos(0).unwrap().vault_withdraw;
+        DpoPayoutInProgress::<T>::insert(0, DpoPayoutProgress::new(PaymentType::WithdrawOnCompletion, total_fare));
     }: _(RawOrigin::Signed(manager), 0)
     verify{
-        assert_eq!(BulletTrain::<T>::dpos(0).unwrap().vault_bonus, 0);
+        assert_eq!(BulletTrain::<T>::dpos(0).unwrap().fare_withdrawn, true);
+        assert_eq!(BulletTrain::<T>::dpo_payout_in_progress(0), None);
+    }
+
+    claim_dpo_rewards{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
+        funded_fill_dpo_share::<T>(0)?;
+        BulletTrain::<T>::dpo_buy_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
+        BulletTrain::<T>::withdraw_yield_from_travel_cabin(RawOrigin::Signed(manager.clone()).into(), 0, 0)?;
+        BulletTrain::<T>::release_yield_from_dpo(RawOrigin::Signed(manager.clone()).into(), 0)?;
+        BulletTrain::<T>::release_bonus_from_dpo(RawOrigin::Signed(manager.clone()).into(), 0)?;
+        // the second member pays its bonus to the first member and the manager
+        let member: T::AccountId = account("dpo_buyer", 1, SEED);
+    }: _(RawOrigin::Signed(manager), 0, Buyer::Passenger(member.clone()))
+    verify{
+        let (yield_withdrawn, bonus_withdrawn) = BulletTrain::<T>::dpo_member_rewards_withdrawn(0, Buyer::Passenger(member));
+        assert!(yield_withdrawn > 0);
+        assert!(bonus_withdrawn > 0);
+    }
+
+    set_dpo_base_fee{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        funded_create_dpo::<T>(manager, Target::TravelCabin(0), 15_000_000_000, 10)?;
+        let call = Call::<T>::set_dpo_base_fee(0, 20);
+        let origin = T::EngineerOrigin::successful_origin();
+    }: { call.dispatch_bypass_filter(origin)? }
+    verify{
+        assert_eq!(BulletTrain::<T>::pending_dpo_base_fees(0).unwrap().0, 20);
+    }
+
+    set_dpo_referral_policy{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
+    }: _(RawOrigin::Signed(manager), 0, 200, 2)
+    verify{
+        assert!(BulletTrain::<T>::dpo_referral_policies(0).is_some());
+    }
+
+    update_dpo_allow_list{
+        let n in 1 .. T::MaxAllowListLength::get();
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        funded_create_dpo::<T>(manager.clone(), Target::TravelCabin(0), 15_000_000_000, 10)?;
+        let buyers: Vec<Buyer<T::AccountId>> = (0..n)
+            .map(|i| Buyer::Passenger(account("dpo_buyer", i, SEED)))
+            .collect();
+    }: _(RawOrigin::Signed(manager), 0, buyers, true)
+    verify{
+        assert!(BulletTrain::<T>::dpo_allow_list(0, Buyer::Passenger(account("dpo_buyer", n - 1, SEED))));
+    }
+
+    redeem_dpo_invite_code{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        let invite_key = sp_io::crypto::sr25519_generate(KeyTypeId(*b"dpoi"), None);
+        let end: BlockNumber = 10;
+        BulletTrain::<T>::create_dpo(
+            RawOrigin::Signed(manager).into(),
+            b"benchmarking".to_vec(),
+            Target::TravelCabin(0),
+            15_000_000_000,
+            50,
+            800,
+            end.into(),
+            None,
+            DpoAccessPolicy::InviteCode(H256(invite_key.0)),
+        )?;
+        let buyer: T::AccountId = account("dpo_buyer", 0, SEED);
+        let signature = sp_io::crypto::sr25519_sign(
+            KeyTypeId(*b"dpoi"),
+            &invite_key,
+            &BulletTrain::<T>::dpo_invitation(0, &buyer),
+        ).ok_or("the invite key is not in the keystore")?;
+    }: _(RawOrigin::Signed(buyer.clone()), 0, H512(signature.0))
+    verify{
+        assert!(BulletTrain::<T>::dpo_allow_list(0, Buyer::Passenger(buyer)));
+    }
+
+    fail_expired_dpos{
+        let n in 1 .. 20;
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        for i in 0..n {
+            let manager: T::AccountId = funded_account::<T>("manager", i);
+            funded_create_dpo::<T>(manager, Target::TravelCabin(0), 15_000_000_000, 10)?;
+        }
+        let expiry: BlockNumber = 11;
+    }: { BulletTrain::<T>::fail_expired_dpos(expiry.into()); }
+    verify{
+        assert_eq!(BulletTrain::<T>::dpos(n - 1).unwrap().state, DpoState::FAILED);
+    }
+
+    apply_dpo_base_fee_changes{
+        let n in 1 .. 20;
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 0, 1)?;
+        for i in 0..n {
+            let manager: T::AccountId = funded_account::<T>("manager", i);
+            funded_create_dpo::<T>(manager, Target::TravelCabin(0), 15_000_000_000, 10)?;
+            let call = Call::<T>::set_dpo_base_fee(i, 20);
+            call.dispatch_bypass_filter(T::EngineerOrigin::successful_origin())?;
+        }
+        let effective_blk = <frame_system::Module<T>>::block_number() + T::DpoBaseFeeChangeDelay::get();
+    }: { BulletTrain::<T>::apply_dpo_base_fee_changes(effective_blk); }
+    verify{
+        assert_eq!(BulletTrain::<T>::dpos(n - 1).unwrap().base_fee, 20);
+    }
+
+    create_portfolio_dpo{
+        let l in 2 .. T::MaxPortfolioLegs::get();
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 1, l as TravelCabinInventoryIndex)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        let legs: Vec<Target<Balance>> = (0..l).map(|_| Target::TravelCabin(0)).collect();
+        let end: BlockNumber = 10;
+    }: _(RawOrigin::Signed(manager), b"benchmarking".to_vec(), legs, 15_000_000_000, 50, 800, end.into(), None, DpoAccessPolicy::Public)
+    verify{
+        assert_eq!(BulletTrain::<T>::dpo_portfolio_legs(0).len(), l as usize);
+    }
+
+    dpo_buy_portfolio_leg{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 1, 2)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        let legs = vec![Target::TravelCabin(0), Target::TravelCabin(0)];
+        funded_create_portfolio_dpo::<T>(manager.clone(), legs, 15_000_000_000, 10)?;
+        funded_fill_dpo_share::<T>(0)?;
+    }: _(RawOrigin::Signed(manager), 0, 0)
+    verify{
+        assert_eq!(BulletTrain::<T>::travel_cabin_buyer(0, 0).unwrap().buyer, Buyer::Dpo(0));
+    }
+
+    dpo_abandon_portfolio_leg{
+        mint_travel_cabin::<T>(BOLT, 100_000_000_000, 10_000_000_000, 10_000_000_000, 1, 2)?;
+        let manager: T::AccountId = funded_account::<T>("manager", 0);
+        let legs = vec![Target::TravelCabin(0), Target::TravelCabin(0)];
+        funded_create_portfolio_dpo::<T>(manager.clone(), legs, 15_000_000_000, 10)?;
+        funded_fill_dpo_share::<T>(0)?;
+        // make cabin 0 unavailable
+        passenger_buy_traver_cabin::<T>(manager.clone(), 0)?;
+        passenger_buy_traver_cabin::<T>(manager.clone(), 0)?;
+    }: _(RawOrigin::Signed(manager), 0, 0)
+    verify{
+        assert_eq!(BulletTrain::<T>::dpo_portfolio_legs(0)[0].state, PortfolioLegState::Abandoned);
     }
 }
 
@@ -312,6 +529,8 @@ mod tests {
     use super::*;
     use crate::mock::{ExtBuilder, Test};
     use frame_support::assert_ok;
+    use sp_keystore::{testing::KeyStore, KeystoreExt};
+    use std::sync::Arc;
 
     #[test]
     fn create_milestone_reward() {
@@ -417,4 +636,83 @@ mod tests {
             assert_ok!(test_benchmark_release_bonus_from_dpo::<Test>());
         });
     }
+
+    #[test]
+    fn continue_dpo_payout() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_continue_dpo_payout::<Test>());
+        });
+    }
+
+    #[test]
+    fn claim_dpo_rewards() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_claim_dpo_rewards::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_dpo_base_fee() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_set_dpo_base_fee::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_dpo_referral_policy() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_set_dpo_referral_policy::<Test>());
+        });
+    }
+
+    #[test]
+    fn update_dpo_allow_list() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_update_dpo_allow_list::<Test>());
+        });
+    }
+
+    #[test]
+    fn redeem_dpo_invite_code() {
+        let mut ext = ExtBuilder::default().build();
+        ext.register_extension(KeystoreExt(Arc::new(KeyStore::new())));
+        ext.execute_with(|| {
+            assert_ok!(test_benchmark_redeem_dpo_invite_code::<Test>());
+        });
+    }
+
+    #[test]
+    fn fail_expired_dpos() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_fail_expired_dpos::<Test>());
+        });
+    }
+
+    #[test]
+    fn apply_dpo_base_fee_changes() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_apply_dpo_base_fee_changes::<Test>());
+        });
+    }
+
+    #[test]
+    fn create_portfolio_dpo() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_create_portfolio_dpo::<Test>());
+        });
+    }
+
+    #[test]
+    fn dpo_buy_portfolio_leg() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_dpo_buy_portfolio_leg::<Test>());
+        });
+    }
+
+    #[test]
+    fn dpo_abandon_portfolio_leg() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_dpo_abandon_portfolio_leg::<Test>());
+        });
+    }
 }
@@ -0,0 +1,244 @@
+//! Fuzzing harness for the bullet-train state machine.
+//!
+//! `run` decodes an arbitrary byte string into a sequence of calls against the mock runtime
+//! and checks after every call that no funds were created or destroyed, that the module
+//! account still covers its obligations and that no dpo made an illegal state transition.
+//! It is driven by seeded inputs in the unit tests and by `cargo fuzz` (see `fuzz/`).
+
+use crate::{mock::*, Buyer, DpoAccessPolicy, DpoState, Target, YieldSchedule};
+use pallet_bullet_train_primitives::{DpoIndex, TravelCabinIndex, TravelCabinInventoryIndex};
+use primitives::CurrencyId;
+use sp_std::collections::btree_map::BTreeMap;
+
+const ACCOUNTS: [AccountId; 11] = [ALICE, BOB, CAROL, DYLAN, ELSA, FRED, GREG, HUGH, IVAN, JILL, ADAM];
+const MAX_OPS: usize = 64;
+
+/// reads the fuzzer input byte by byte, yielding zeroes once it is exhausted
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((b, rest)) => {
+                self.data = rest;
+                *b
+            }
+            None => 0,
+        }
+    }
+
+    fn account(&mut self) -> AccountId {
+        ACCOUNTS[self.byte() as usize % ACCOUNTS.len()]
+    }
+
+    fn travel_cabin(&mut self) -> TravelCabinIndex {
+        self.byte() as TravelCabinIndex % BulletTrain::travel_cabin_count().max(1)
+    }
+
+    fn dpo(&mut self) -> DpoIndex {
+        self.byte() as DpoIndex % BulletTrain::dpo_count().max(1)
+    }
+
+    /// a percentage in 1..=100
+    fn percent(&mut self) -> Balance {
+        self.byte() as Balance % 100 + 1
+    }
+
+    /// a target for a dpo, with the dpo share amount taken as a percentage of the target dpo
+    fn target(&mut self) -> Target<Balance> {
+        if self.byte() % 2 == 0 {
+            Target::TravelCabin(self.travel_cabin())
+        } else {
+            let dpo_idx = self.dpo();
+            let amount = BulletTrain::dpos(dpo_idx).map_or(0, |dpo| dpo.target_amount) * self.percent() / 100;
+            Target::Dpo(dpo_idx, amount)
+        }
+    }
+}
+
+fn target_amount(target: &Target<Balance>) -> Balance {
+    match target {
+        Target::TravelCabin(idx) => BulletTrain::travel_cabins(*idx).map_or(0, |cabin| cabin.deposit_amount),
+        Target::Dpo(_, amount) => *amount,
+        Target::Portfolio(amount) => *amount,
+    }
+}
+
+fn is_legal_transition(from: DpoState, to: DpoState) -> bool {
+    use DpoState::*;
+    from == to
+        || match from {
+            CREATED => matches!(to, ACTIVE | RUNNING | FAILED | COMPLETED),
+            ACTIVE => matches!(to, RUNNING | COMPLETED),
+            RUNNING => to == COMPLETED,
+            FAILED | COMPLETED => false,
+        }
+}
+
+fn dpo_states() -> BTreeMap<DpoIndex, DpoState> {
+    crate::Dpos::<Test>::iter().map(|(idx, dpo)| (idx, dpo.state)).collect()
+}
+
+/// the total of every currency, per currency, summed over the accounts holding it
+fn account_totals() -> BTreeMap<CurrencyId, Balance> {
+    let mut totals = BTreeMap::new();
+    for (_, info) in frame_system::Account::<Test>::iter() {
+        *totals.entry(BOLT).or_insert(0) += info.data.free + info.data.reserved;
+    }
+    for (_, currency_id, data) in orml_tokens::Accounts::<Test>::iter() {
+        *totals.entry(currency_id).or_insert(0) += data.free + data.reserved;
+    }
+    totals
+}
+
+fn issuances() -> BTreeMap<CurrencyId, Balance> {
+    let mut issuances: BTreeMap<CurrencyId, Balance> = orml_tokens::TotalIssuance::<Test>::iter().collect();
+    issuances.insert(BOLT, Balances::total_issuance());
+    issuances
+}
+
+fn check_properties(issuance_at_genesis: &BTreeMap<CurrencyId, Balance>, states_before: &BTreeMap<DpoIndex, DpoState>) {
+    let issuance = issuances();
+    assert_eq!(&issuance, issuance_at_genesis, "funds were created or destroyed");
+    assert_eq!(account_totals(), issuance, "account balances do not add up to the issuance");
+
+    let report = BulletTrain::check_invariants();
+    assert!(report.is_ok(), "invariants broken: {:?}", report);
+
+    let states_after = dpo_states();
+    for (idx, before) in states_before {
+        let after = states_after.get(idx).expect("a dpo was removed");
+        assert!(
+            is_legal_transition(*before, *after),
+            "dpo {} went from {:?} to {:?}",
+            idx,
+            before,
+            after
+        );
+    }
+}
+
+/// applies the call encoded at the head of the input, ignoring dispatch errors
+fn apply_call(input: &mut Input) {
+    let op = input.byte() % 15;
+    let _ = match op {
+        0 => BulletTrain::create_travel_cabin(
+            Origin::signed(ALICE),
+            if input.byte() % 2 == 0 { BOLT } else { WUSD },
+            b"fuzz".to_vec(),
+            1_000 * (input.byte() as Balance % 20 + 1),
+            10 * (input.byte() as Balance % 10),
+            10 * (input.byte() as Balance % 50),
+            input.byte() as BlockNumber % 30 + 1,
+            input.byte() as TravelCabinInventoryIndex % 4 + 1,
+            YieldSchedule::Linear,
+        ),
+        1 => BulletTrain::passenger_buy_travel_cabin(Origin::signed(input.account()), input.travel_cabin()),
+        2 => {
+            let manager = input.account();
+            let target = input.target();
+            let amount = target_amount(&target) * input.percent() / 100;
+            let end = System::block_number() + input.byte() as BlockNumber % 40 + 1;
+            BulletTrain::create_dpo(
+                Origin::signed(manager),
+                b"fuzz".to_vec(),
+                target,
+                amount,
+                input.byte() as u32 % 60,
+                input.byte() as u32 * 4,
+                end,
+                None,
+                DpoAccessPolicy::Public,
+            )
+        }
+        3 => {
+            let who = input.account();
+            let dpo_idx = input.dpo();
+            let amount = BulletTrain::dpos(dpo_idx).map_or(0, |dpo| dpo.target_amount) * input.percent() / 100;
+            BulletTrain::passenger_buy_dpo_share(Origin::signed(who), dpo_idx, amount, None)
+        }
+        4 => {
+            let dpo_idx = input.dpo();
+            let signer = manager_or_account(input, dpo_idx);
+            BulletTrain::dpo_buy_travel_cabin(Origin::signed(signer), dpo_idx, input.travel_cabin())
+        }
+        5 => {
+            let dpo_idx = input.dpo();
+            let signer = manager_or_account(input, dpo_idx);
+            let target_dpo_idx = input.dpo();
+            let amount = BulletTrain::dpos(target_dpo_idx).map_or(0, |dpo| dpo.target_amount) * input.percent() / 100;
+            BulletTrain::dpo_buy_dpo_share(Origin::signed(signer), dpo_idx, target_dpo_idx, amount)
+        }
+        6 => {
+            let dpo_idx = input.dpo();
+            let signer = manager_or_account(input, dpo_idx);
+            BulletTrain::dpo_change_target(Origin::signed(signer), dpo_idx, input.target())
+        }
+        7 | 8 => {
+            let who = input.account();
+            let idx = input.travel_cabin();
+            let sold = BulletTrain::travel_cabin_inventory(idx).map_or(0, |(sold, _)| sold);
+            let number = input.byte() as TravelCabinInventoryIndex % sold.max(1);
+            if op == 7 {
+                BulletTrain::withdraw_yield_from_travel_cabin(Origin::signed(who), idx, number)
+            } else {
+                BulletTrain::withdraw_fare_from_travel_cabin(Origin::signed(who), idx, number)
+            }
+        }
+        9 => BulletTrain::release_yield_from_dpo(Origin::signed(input.account()), input.dpo()),
+        10 => BulletTrain::release_bonus_from_dpo(Origin::signed(input.account()), input.dpo()),
+        11 => BulletTrain::release_fare_from_dpo(Origin::signed(input.account()), input.dpo()),
+        12 => BulletTrain::continue_dpo_payout(Origin::signed(input.account()), input.dpo()),
+        13 => {
+            let who = input.account();
+            let dpo_idx = input.dpo();
+            let member = if input.byte() % 2 == 0 {
+                Buyer::Passenger(who)
+            } else {
+                Buyer::Dpo(input.dpo())
+            };
+            BulletTrain::claim_dpo_rewards(Origin::signed(who), dpo_idx, member)
+        }
+        _ => {
+            run_to_block_with_hooks(System::block_number() + input.byte() as BlockNumber % 16 + 1);
+            Ok(().into())
+        }
+    };
+}
+
+/// the dpo manager most of the time, so that the grace period does not reject every buy
+fn manager_or_account(input: &mut Input, dpo_idx: DpoIndex) -> AccountId {
+    match BulletTrain::dpos(dpo_idx) {
+        Some(dpo) if input.byte() % 4 != 0 => dpo.manager,
+        _ => input.account(),
+    }
+}
+
+/// runs the calls encoded by `data` against a fresh mock runtime, panicking on the first
+/// property that does not hold
+pub fn run(data: &[u8]) {
+    let mut input = Input { data };
+    // the runtime config is thread local, pick it before building the externalities
+    set_dpo_payout_members_per_call(input.byte() as u32 % 8 + 1);
+    set_dpo_expiry_config(input.byte() as u32 % 4 + 1, input.byte() % 2 == 0);
+    set_dpo_creation_deposit(input.byte() as Balance % 3 * 100);
+
+    ExtBuilder::default().build().execute_with(|| {
+        System::set_block_number(1);
+        let issuance_at_genesis = issuances();
+        for _ in 0..MAX_OPS {
+            if input.is_empty() {
+                break;
+            }
+            let states_before = dpo_states();
+            apply_call(&mut input);
+            check_properties(&issuance_at_genesis, &states_before);
+        }
+    });
+}
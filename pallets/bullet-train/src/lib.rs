@@ -53,29 +53,35 @@ use frame_support::{
     dispatch::DispatchResult,
     ensure,
     pallet_prelude::*,
+    storage::{with_transaction, TransactionOutcome},
     traits::{EnsureOrigin, Get},
     transactional,
 };
 use frame_system::{ensure_signed, pallet_prelude::*};
-use orml_traits::{MultiCurrency, MultiCurrencyExtended};
+#[cfg(feature = "std")]
+use node_type_registry::TypeInfo;
+use orml_traits::{MultiCurrency, MultiCurrencyExtended, MultiReservableCurrency};
 use pallet_bullet_train_primitives::*;
 use parity_scale_codec::{Decode, Encode};
 use primitives::{Balance, CurrencyId};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_arithmetic::Percent;
+use sp_core::{sr25519, H256, H512};
 use sp_runtime::{
-    traits::{AccountIdConversion, UniqueSaturatedInto, Zero},
+    traits::{AccountIdConversion, AtLeast32BitUnsigned, One, UniqueSaturatedInto, Verify, Zero},
     DispatchError, ModuleId, Permill, FixedU128, FixedPointNumber, FixedPointOperand,
 };
-use sp_std::prelude::*;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
-#[cfg(test)]
-mod mock;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
@@ -88,6 +94,7 @@ pub const TARGET_AMOUNT_MINIMUM: Balance = 100;
 pub const DPO_YIELD_REWARD_MINIMUM: Balance = 100;
 
 #[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct TravelCabinInfo<Balance, AccountId, BlockNumber> {
     name: Vec<u8>,
     creator: AccountId,
@@ -97,9 +104,65 @@ pub struct TravelCabinInfo<Balance, AccountId, BlockNumber> {
     bonus_total: Balance,
     yield_total: Balance,
     maturity: BlockNumber,
+    yield_schedule: YieldSchedule<BlockNumber>,
+}
+
+/// how the yield of a travel cabin accrues between purchase and maturity
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
+pub enum YieldSchedule<BlockNumber> {
+    /// accrues evenly over the maturity
+    Linear,
+    /// nothing accrues before the cliff (in blocks), then it catches up linearly
+    CliffLinear(BlockNumber),
+    /// paid as equal coupons, one at the end of every period (in blocks)
+    Periodic(BlockNumber),
+    /// accrues quadratically, most of the yield comes close to maturity
+    BackLoaded,
+}
+
+impl<BlockNumber> Default for YieldSchedule<BlockNumber> {
+    fn default() -> Self {
+        YieldSchedule::Linear
+    }
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> YieldSchedule<BlockNumber> {
+    /// the schedule must fit in the maturity of the cabin
+    pub fn is_valid(&self, maturity: BlockNumber) -> bool {
+        match *self {
+            YieldSchedule::Linear | YieldSchedule::BackLoaded => true,
+            YieldSchedule::CliffLinear(cliff) => cliff <= maturity,
+            YieldSchedule::Periodic(period) => !period.is_zero() && period <= maturity,
+        }
+    }
+
+    /// the portion of the total yield accrued `elapsed` blocks after purchase
+    pub fn accrued(&self, elapsed: BlockNumber, maturity: BlockNumber) -> Permill {
+        if maturity.is_zero() || elapsed >= maturity {
+            return Permill::one();
+        }
+        let linear = Permill::from_rational_approximation(elapsed, maturity);
+        match *self {
+            YieldSchedule::Linear => linear,
+            YieldSchedule::CliffLinear(cliff) => {
+                if elapsed < cliff { Permill::zero() } else { linear }
+            }
+            YieldSchedule::Periodic(period) => {
+                if period.is_zero() {
+                    return linear;
+                }
+                // the last coupon may cover a shorter period
+                let coupons_total = (maturity + period - One::one()) / period;
+                Permill::from_rational_approximation(elapsed / period, coupons_total)
+            }
+            YieldSchedule::BackLoaded => linear.square(),
+        }
+    }
 }
 
 #[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct TravelCabinBuyerInfo<Balance, AccountId, BlockNumber> {
     buyer: Buyer<AccountId>,
     purchase_blk: BlockNumber,
@@ -108,13 +171,57 @@ pub struct TravelCabinBuyerInfo<Balance, AccountId, BlockNumber> {
 }
 
 #[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct MilestoneRewardInfo<Balance> {
     token_id: CurrencyId,
     deposited: Balance,
     milestones: Vec<(Balance, Balance)>,
 }
 
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct MilestoneRewardReleaseInfo<Balance, AccountId, BlockNumber> {
+    milestone: Balance,
+    reward: Balance,
+    // total ticket fares of the token at the time of release
+    deposited: Balance,
+    // reward actually paid out, the rounding remainder stays in the pallet account
+    total_paid: Balance,
+    recipients: u32,
+    released_by: AccountId,
+    released_blk: BlockNumber,
+}
+
+/// a milestone reward being paid out over several calls
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct MilestoneRewardReleaseProgress<Balance, BlockNumber> {
+    release_idx: MilestoneRewardReleaseIndex,
+    milestone: Balance,
+    reward: Balance,
+    // the share of the reward paid to the callers
+    incentive: Balance,
+    incentive_paid: Balance,
+    // snapshot on start, cabins bought afterwards are not paid
+    deposited: Balance,
+    total_payouts: u32,
+    started_blk: BlockNumber,
+    // cursor, the next sold cabin to pay
+    travel_cabin_idx: TravelCabinIndex,
+    inventory_idx: TravelCabinInventoryIndex,
+    total_paid: Balance,
+    recipients: u32,
+}
+
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct MilestoneRewardPayoutInfo<Balance, AccountId> {
+    buyer: Buyer<AccountId>,
+    amount: Balance,
+}
+
 #[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub enum DpoState {
     /// on_creation
     CREATED,
@@ -135,10 +242,13 @@ impl Default for DpoState {
 }
 
 #[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub enum Target<Balance> {
     // Balance: purchase token amount, e.g. 1000 BOLT
     Dpo(DpoIndex, Balance),
     TravelCabin(TravelCabinIndex),
+    // legs kept in DpoPortfolioLegs of the dpo. Balance: total purchase token amount of the legs
+    Portfolio(Balance),
 }
 
 impl<Balance> Default for Target<Balance> {
@@ -158,7 +268,7 @@ pub enum TargetCompare {
 }
 
 #[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
 pub enum Buyer<AccountId> {
     Dpo(DpoIndex),
     Passenger(AccountId),
@@ -172,6 +282,7 @@ impl<AccountId> Default for Buyer<AccountId> {
 }
 
 #[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct DpoInfo<Balance, BlockNumber, AccountId> {
     //meta
     index: DpoIndex,
@@ -212,9 +323,58 @@ pub struct DpoInfo<Balance, BlockNumber, AccountId> {
     referrer: Option<AccountId>,
     fare_withdrawn: bool,
     direct_referral_rate: u32, // per thousand
+    access_policy: DpoAccessPolicy,
+}
+
+/// who can buy the shares of a dpo, besides its manager
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub enum DpoAccessPolicy {
+    Public,
+    // only the buyers on the allow list of the dpo
+    AllowList,
+    // the buyers on the allow list, who can also join it with an invitation signed by the
+    // sr25519 invite key of the manager, of this public key
+    InviteCode(H256),
+}
+
+impl Default for DpoAccessPolicy {
+    fn default() -> Self {
+        DpoAccessPolicy::Public
+    }
+}
+
+/// a travel cabin or a dpo share bought by a portfolio dpo
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct PortfolioLeg<Balance> {
+    target: Target<Balance>,
+    // purchase token amount of the leg
+    amount: Balance,
+    state: PortfolioLegState,
+    // the travel cabin inventory index bought by a cabin leg
+    cabin_number: Option<TravelCabinInventoryIndex>,
+}
+
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub enum PortfolioLegState {
+    Pending,
+    Purchased,
+    // the fare of the leg came back
+    Completed,
+    // the target became unavailable before purchase or failed after it, the fund is returned
+    Abandoned,
+}
+
+impl Default for PortfolioLegState {
+    fn default() -> Self {
+        PortfolioLegState::Pending
+    }
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct DpoMemberInfo<AccountId, Balance> {
     buyer: Buyer<AccountId>,
     // dpo internal share, tokenization in the future
@@ -222,7 +382,66 @@ pub struct DpoMemberInfo<AccountId, Balance> {
     referrer: Referrer<AccountId>,
 }
 
+/// a release of fare being paid to the dpo members over several calls
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct DpoPayoutProgress<Balance> {
+    payment_type: PaymentType,
+    // amount to be shared by the members, fixed on start
+    total_amount: Balance,
+    // not paid yet, goes to the manager once all members are paid
+    remainder: Balance,
+    // cursor, the next member to pay by its joining order
+    next_member_idx: u32,
+}
+
+impl<Balance: Copy> DpoPayoutProgress<Balance> {
+    fn new(payment_type: PaymentType, total_amount: Balance) -> Self {
+        DpoPayoutProgress {
+            payment_type,
+            total_amount,
+            remainder: total_amount,
+            next_member_idx: 0,
+        }
+    }
+}
+
+/// yield and bonus released by a dpo, claimed by its members by share
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct DpoRewardPoolInfo<Balance> {
+    total_yield: Balance,
+    total_yield_withdrawn: Balance,
+    total_bonus: Balance,
+    total_bonus_withdrawn: Balance,
+    // the bonus left by the rounding of the member shares, claimed by the manager of a dpo targeting a dpo
+    bonus_remainder: Balance,
+}
+
+/// referral economics of a dpo, the Config defaults unless set by its manager
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct DpoReferralPolicy {
+    external_referral_rate: u32, // per thousand
+    // number of referrers sharing the bonus of a member
+    referral_chain_depth: u32,
+}
+
+/// a base fee change of a dpo, applied once its timelock is over
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct DpoBaseFeeChangeInfo<BlockNumber> {
+    old_base_fee: u32, // per thousand
+    new_base_fee: u32,
+    // effective manager fee before and after the change
+    old_fee: u32,
+    new_fee: u32,
+    scheduled_blk: BlockNumber,
+    changed_blk: BlockNumber,
+}
+
 #[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub enum Referrer<AccountId> {
     None,
     MemberOfDpo(Buyer<AccountId>),
@@ -235,7 +454,8 @@ impl<AccountId> Default for Referrer<AccountId> {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub enum PaymentType {
     Deposit,
     Bonus,
@@ -284,7 +504,10 @@ pub mod module {
             Self::AccountId,
             CurrencyId=CurrencyId,
             Balance=Balance,
-        >;
+        > + MultiReservableCurrency<Self::AccountId, CurrencyId=CurrencyId, Balance=Balance>;
+
+        #[pallet::constant]
+        type GetNativeCurrencyId: Get<CurrencyId>;
 
         #[pallet::constant]
         type ModuleId: Get<ModuleId>;
@@ -298,6 +521,26 @@ pub mod module {
         #[pallet::constant]
         type MilestoneRewardMinimum: Get<Balance>;
 
+        /// share of a milestone reward paid to the accounts releasing it
+        #[pallet::constant]
+        type MilestoneRewardReleaseIncentive: Get<Permill>;
+
+        /// max number of sold travel cabins paid in one milestone reward release call
+        #[pallet::constant]
+        type MilestoneRewardPayoutsPerCall: Get<u32>;
+
+        /// max number of dpo members paid in one fare release call
+        #[pallet::constant]
+        type DpoPayoutMembersPerCall: Get<u32>;
+
+        /// max number of expired dpos failed in one block, the rest are failed in the next blocks
+        #[pallet::constant]
+        type DpoExpiriesPerBlock: Get<u32>;
+
+        /// whether an expired dpo starts refunding its members when it is failed
+        #[pallet::constant]
+        type RefundExpiredDpos: Get<bool>;
+
         #[pallet::constant]
         type CabinYieldRewardMinimum: Get<Balance>;
 
@@ -328,6 +571,40 @@ pub mod module {
         #[pallet::constant]
         type ManagementBaseFeeCap: Get<u32>; //per thousand
 
+        /// share of the bonus of a member going to its external referrer
+        #[pallet::constant]
+        type ExternalReferralRate: Get<u32>; //per thousand
+
+        /// number of referrers sharing the bonus of a member
+        #[pallet::constant]
+        type ReferralChainDepth: Get<u32>;
+
+        #[pallet::constant]
+        type ExternalReferralRateCap: Get<u32>; //per thousand
+
+        #[pallet::constant]
+        type ReferralChainDepthCap: Get<u32>;
+
+        /// max number of cabins and dpo shares a portfolio dpo can target
+        #[pallet::constant]
+        type MaxPortfolioLegs: Get<u32>;
+
+        /// max number of buyers added to or removed from the allow list of a dpo at once
+        #[pallet::constant]
+        type MaxAllowListLength: Get<u32>;
+
+        /// native token reserved from the manager on creating a dpo, returned once it completes or fails
+        #[pallet::constant]
+        type DpoCreationDeposit: Get<Balance>;
+
+        /// max length of the name of a travel cabin or a dpo
+        #[pallet::constant]
+        type MaxNameLength: Get<u32>;
+
+        /// blocks between a base fee change being set by the engineers and taking effect
+        #[pallet::constant]
+        type DpoBaseFeeChangeDelay: Get<Self::BlockNumber>;
+
         type EngineerOrigin: EnsureOrigin<Self::Origin, Success=Self::AccountId>;
 
         type WeightInfo: WeightInfo;
@@ -383,6 +660,8 @@ pub mod module {
         DefaultTargetAvailable,
         /// on retargeting, Target type must be the same
         InvalidTargetForDpo,
+        /// yield schedule does not fit in the maturity of the travel cabin
+        InvalidYieldSchedule,
         /// setting reward for a past milestone
         RewardMilestoneInvalid,
         /// must have at least one stockpile
@@ -395,6 +674,28 @@ pub mod module {
         NotAllowedToChangeLargerTarget,
         /// dpo target to its child dpo
         DpoTargetToChild,
+        /// the dpo is paying the fare to its members, finish it with continue_dpo_payout first
+        DpoPayoutInProgress,
+        /// the dpo is not paying the fare to its members
+        NoDpoPayoutInProgress,
+        /// referral chain depth must be at least 1 and within the cap
+        InvalidReferralChainDepth,
+        /// the dpo is private and the buyer is not on its allow list
+        NotOnDpoAllowList,
+        /// the dpo takes no invitations or the invitation is not signed by its invite key
+        InvalidInviteCode,
+        /// the buyer is already on the allow list of the dpo, or has been removed from it
+        InviteCodeAlreadyUsed,
+        /// more than MaxAllowListLength buyers at once
+        TooManyBuyers,
+        /// a portfolio needs 2 to MaxPortfolioLegs legs of cabins or distinct dpos in the same token
+        InvalidPortfolio,
+        /// the portfolio leg has been purchased or abandoned
+        PortfolioLegNotPending,
+        /// the name is longer than MaxNameLength
+        NameTooLong,
+        /// the base fee of the dpo is the same
+        DpoBaseFeeUnchanged,
     }
 
     #[pallet::event]
@@ -404,7 +705,23 @@ pub mod module {
         IssuedAdditionalTravelCabin(T::AccountId, CurrencyId, TravelCabinIndex, u8),
         CreatedDpo(T::AccountId, DpoIndex),
         CreatedMilestoneReward(T::AccountId, CurrencyId, Balance, Balance),
-        MilestoneRewardReleased(T::AccountId, CurrencyId, Balance, Balance),
+        /// who, token, release index, milestone, reward, total paid
+        MilestoneRewardReleased(
+            T::AccountId,
+            CurrencyId,
+            MilestoneRewardReleaseIndex,
+            Balance,
+            Balance,
+            Balance,
+        ),
+        /// who, token, release index, number of travel cabins processed, incentive
+        MilestoneRewardReleaseProgressed(
+            T::AccountId,
+            CurrencyId,
+            MilestoneRewardReleaseIndex,
+            u32,
+            Balance,
+        ),
         TravelCabinTargetPurchased(
             T::AccountId,
             Buyer<T::AccountId>,
@@ -415,6 +732,10 @@ pub mod module {
         WithdrewFareFromDpo(T::AccountId, DpoIndex),
         YieldReleased(T::AccountId, DpoIndex),
         BonusReleased(T::AccountId, DpoIndex),
+        /// who, dpo, number of members paid. WithdrewFareFromDpo is emitted once all members are paid
+        DpoPayoutProgressed(T::AccountId, DpoIndex, u32),
+        /// who, dpo, member, yield, bonus
+        DpoRewardsClaimed(T::AccountId, DpoIndex, Buyer<T::AccountId>, Balance, Balance),
         YieldWithdrawnFromTravelCabin(
             T::AccountId,
             TravelCabinIndex,
@@ -423,6 +744,23 @@ pub mod module {
         ),
         FareWithdrawnFromTravelCabin(T::AccountId, TravelCabinIndex, TravelCabinInventoryIndex),
         DpoTargetChanged(T::AccountId, DpoIndex, Target<Balance>),
+        /// who, dpo, external referral rate, referral chain depth
+        DpoReferralPolicySet(T::AccountId, DpoIndex, u32, u32),
+        /// a dpo not filled by its expiry block
+        DpoFailed(DpoIndex),
+        /// who, dpo, number of buyers, allowed or not
+        DpoAllowListUpdated(T::AccountId, DpoIndex, u32, bool),
+        DpoInviteCodeRedeemed(T::AccountId, DpoIndex),
+        /// who, dpo, leg index
+        DpoPortfolioLegPurchased(T::AccountId, DpoIndex, u32),
+        /// who, dpo, leg index
+        DpoPortfolioLegAbandoned(T::AccountId, DpoIndex, u32),
+        /// manager, dpo, amount unreserved
+        DpoCreationDepositReturned(T::AccountId, DpoIndex, Balance),
+        /// dpo, new base fee, block it takes effect
+        DpoBaseFeeChangeScheduled(DpoIndex, u32, T::BlockNumber),
+        /// dpo, old base fee, new base fee, new effective fee
+        DpoBaseFeeChanged(DpoIndex, u32, u32, u32),
     }
 
     #[pallet::storage]
@@ -483,6 +821,131 @@ pub mod module {
         OptionQuery,
     >;
 
+    /// number of members ever joined a dpo, members are never removed
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_member_count)]
+    pub type DpoMemberCount<T: Config> = StorageMap<_, Blake2_128Concat, DpoIndex, u32, ValueQuery>;
+
+    /// dpo members by their joining order, for paying them over several calls
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_member_by_index)]
+    pub type DpoMemberByIndex<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        DpoIndex,
+        Blake2_128Concat,
+        u32,
+        Buyer<T::AccountId>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_reward_pools)]
+    pub type DpoRewardPools<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, DpoRewardPoolInfo<Balance>, ValueQuery>;
+
+    /// yield and bonus claimed by a dpo member
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_member_rewards_withdrawn)]
+    pub type DpoMemberRewardsWithdrawn<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        DpoIndex,
+        Blake2_128Concat,
+        Buyer<T::AccountId>,
+        (Balance, Balance),
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_payout_in_progress)]
+    pub type DpoPayoutInProgress<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        DpoIndex,
+        DpoPayoutProgress<Balance>,
+        OptionQuery,
+    >;
+
+    /// dpos to be failed at the block if they are still not filled
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_expiry_queue)]
+    pub type DpoExpiryQueue<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::BlockNumber, Vec<DpoIndex>, ValueQuery>;
+
+    /// buyers allowed to join a private dpo, and the ones removed from its allow list as not allowed
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_allow_list)]
+    pub type DpoAllowList<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        DpoIndex,
+        Blake2_128Concat,
+        Buyer<T::AccountId>,
+        bool,
+        ValueQuery,
+    >;
+
+    /// manager and amount of the creation deposit reserved for a dpo, until it completes or fails
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_creation_deposits)]
+    pub type DpoCreationDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, (T::AccountId, Balance), OptionQuery>;
+
+    /// base fee of a dpo waiting for its timelock, and the block it takes effect
+    #[pallet::storage]
+    #[pallet::getter(fn pending_dpo_base_fees)]
+    pub type PendingDpoBaseFees<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, (u32, T::BlockNumber, T::BlockNumber), OptionQuery>;
+
+    /// dpos whose pending base fee takes effect at the block
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_base_fee_change_queue)]
+    pub type DpoBaseFeeChangeQueue<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::BlockNumber, Vec<DpoIndex>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_base_fee_change_count)]
+    pub type DpoBaseFeeChangeCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, u32, ValueQuery>;
+
+    /// history of the base fee changes of a dpo
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_base_fee_changes)]
+    pub type DpoBaseFeeChanges<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        DpoIndex,
+        Twox64Concat,
+        u32,
+        DpoBaseFeeChangeInfo<T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    /// legs of a portfolio dpo and their purchase state
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_portfolio_legs)]
+    pub type DpoPortfolioLegs<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, Vec<PortfolioLeg<Balance>>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dpo_referral_policies)]
+    pub type DpoReferralPolicies<T: Config> =
+        StorageMap<_, Blake2_128Concat, DpoIndex, DpoReferralPolicy, OptionQuery>;
+
+    /// referral bonus ever received by an account
+    #[pallet::storage]
+    #[pallet::getter(fn referral_earnings)]
+    pub type ReferralEarnings<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        CurrencyId,
+        Balance,
+        ValueQuery,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn dpo_count)]
     pub type DpoCount<T: Config> = StorageValue<_, DpoIndex, ValueQuery>;
@@ -491,13 +954,152 @@ pub mod module {
     #[pallet::getter(fn milestone_reward)]
     pub type MilestoneReward<T: Config> = StorageMap<_, Blake2_128Concat, CurrencyId, MilestoneRewardInfo<Balance>, OptionQuery>;
 
+    /// the number of travel cabins sold per token, paid by each milestone reward release
+    #[pallet::storage]
+    #[pallet::getter(fn travel_cabins_sold)]
+    pub type TravelCabinsSold<T: Config> = StorageMap<_, Blake2_128Concat, CurrencyId, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn milestone_reward_release_count)]
+    pub type MilestoneRewardReleaseCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, CurrencyId, MilestoneRewardReleaseIndex, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn milestone_reward_releases)]
+    pub type MilestoneRewardReleases<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        CurrencyId,
+        Blake2_128Concat,
+        MilestoneRewardReleaseIndex,
+        MilestoneRewardReleaseInfo<Balance, T::AccountId, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn milestone_reward_release_in_progress)]
+    pub type MilestoneRewardReleaseInProgress<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        CurrencyId,
+        MilestoneRewardReleaseProgress<Balance, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn milestone_reward_payouts)]
+    pub type MilestoneRewardPayouts<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        (CurrencyId, MilestoneRewardReleaseIndex),
+        Blake2_128Concat,
+        (TravelCabinIndex, TravelCabinInventoryIndex),
+        MilestoneRewardPayoutInfo<Balance, T::AccountId>,
+        OptionQuery,
+    >;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// minted to the engineer account before the travel cabins and milestone rewards are funded
+        pub engineer_endowment: Vec<(CurrencyId, Balance)>,
+        /// (token_id, name, deposit_amount, bonus_total, yield_total, maturity, stockpile, yield_schedule),
+        /// created by the engineer account in order from index 0
+        pub initial_travel_cabins: Vec<(
+            CurrencyId,
+            Vec<u8>,
+            Balance,
+            Balance,
+            Balance,
+            T::BlockNumber,
+            TravelCabinInventoryIndex,
+            YieldSchedule<T::BlockNumber>,
+        )>,
+        /// (token_id, milestone, reward)
+        pub initial_milestone_rewards: Vec<(CurrencyId, Balance, Balance)>,
+        /// public dpos buying a travel cabin: (manager, name, travel_cabin_idx, manager_purchase_amount, base_fee, end)
+        pub initial_dpos: Vec<(T::AccountId, Vec<u8>, TravelCabinIndex, Balance, u32, T::BlockNumber)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            GenesisConfig {
+                engineer_endowment: vec![],
+                initial_travel_cabins: vec![],
+                initial_milestone_rewards: vec![],
+                initial_dpos: vec![],
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            self.engineer_endowment.iter().for_each(|(token_id, amount)| {
+                let result = T::Currency::deposit(*token_id, &Pallet::<T>::eng_account_id(), *amount);
+                assert!(result.is_ok(), "genesis engineer endowment failed.");
+            });
+
+            self.initial_travel_cabins.iter().for_each(
+                |(token_id, name, deposit_amount, bonus_total, yield_total, maturity, stockpile, yield_schedule)| {
+                    let result = Pallet::<T>::do_create_travel_cabin(
+                        Pallet::<T>::eng_account_id(),
+                        *token_id,
+                        name.clone(),
+                        *deposit_amount,
+                        *bonus_total,
+                        *yield_total,
+                        *maturity,
+                        *stockpile,
+                        *yield_schedule,
+                    );
+                    assert!(result.is_ok(), "genesis create travel cabin failed.");
+                },
+            );
+
+            self.initial_milestone_rewards.iter().for_each(|(token_id, milestone, reward)| {
+                let result = Pallet::<T>::do_create_milestone_reward(
+                    Pallet::<T>::eng_account_id(),
+                    *token_id,
+                    *milestone,
+                    *reward,
+                );
+                assert!(result.is_ok(), "genesis create milestone reward failed.");
+            });
+
+            // the managers pay for their shares, their balances must be built before this pallet
+            self.initial_dpos.iter().for_each(
+                |(manager, name, travel_cabin_idx, manager_purchase_amount, base_fee, end)| {
+                    let result = Pallet::<T>::do_create_target_dpo(
+                        manager.clone(),
+                        name.clone(),
+                        Target::TravelCabin(*travel_cabin_idx),
+                        *manager_purchase_amount,
+                        *base_fee,
+                        0,
+                        *end,
+                        None,
+                        DpoAccessPolicy::Public,
+                    );
+                    assert!(result.is_ok(), "genesis create dpo failed.");
+                },
+            );
+        }
+    }
+
     #[pallet::pallet]
     pub struct Pallet<T>(PhantomData<T>);
 
     #[pallet::hooks]
     impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::fail_expired_dpos(now)
+                .saturating_add(Self::apply_dpo_base_fee_changes(now))
+        }
+
         fn on_runtime_upgrade() -> frame_support::weights::Weight {
             migration::migrate_to_v3::<T>()
+                .saturating_add(migration::migrate_to_v3_1::<T>())
         }
     }
 
@@ -515,62 +1117,37 @@ pub mod module {
             reward: Balance,
         ) -> DispatchResultWithPostInfo {
             let who = T::EngineerOrigin::ensure_origin(origin)?;
-            ensure!(
-                reward >= T::MilestoneRewardMinimum::get(),
-                Error::<T>::RewardValueTooSmall
-            );
-            ensure!(
-                matches!(token_id, CurrencyId::Token(_)),
-                Error::<T>::CurrencyNotSupported
-            );
-
-            let mut milestone_reward_info = match Self::milestone_reward(token_id) {
-                Some(info) => info,
-                //if not, create it
-                None => MilestoneRewardInfo {
-                    token_id,
-                    deposited: Zero::zero(),
-                    milestones: Vec::new(),
-                },
-            };
-
-            ensure!(
-                milestone > milestone_reward_info.deposited,
-                Error::<T>::RewardMilestoneInvalid
-            );
-
-            T::Currency::transfer(
-                token_id,
-                &Self::eng_account_id(),
-                &Self::account_id(),
-                reward.unique_saturated_into(),
-            )?;
-
-            milestone_reward_info.milestones.push((milestone, reward));
-
-            //update storage
-            MilestoneReward::<T>::insert(token_id, milestone_reward_info);
-            Self::deposit_event(Event::CreatedMilestoneReward(
-                who, token_id, milestone, reward,
-            ));
+            Self::do_create_milestone_reward(who, token_id, milestone, reward)?;
             Ok(().into())
         }
 
-        #[pallet::weight(< T as Config >::WeightInfo::release_milestone_reward())]
+        /// anyone can call this function once the total ticket fares of the token reach a milestone.
+        /// the payout is chunked by MilestoneRewardPayoutsPerCall, and the caller is paid
+        /// a share of the reward for each chunk released
+        #[pallet::weight(< T as Config >::WeightInfo::release_milestone_reward(
+            T::MilestoneRewardPayoutsPerCall::get()
+        ))]
         #[transactional]
         pub fn release_milestone_reward(
             origin: OriginFor<T>,
             token_id: CurrencyId,
         ) -> DispatchResultWithPostInfo {
-            let who = T::EngineerOrigin::ensure_origin(origin)?;
+            let who = ensure_signed(origin)?;
             let mut milestone_reward_info =
                 Self::milestone_reward(token_id).ok_or(Error::<T>::InvalidIndex)?;
-            if milestone_reward_info.milestones.is_empty() {
-                Err(Error::<T>::NoMilestoneRewardWaiting)?
-            }
-            Self::do_release_milestone_reward(who, &mut milestone_reward_info)?;
+            let deposited = milestone_reward_info.deposited;
+            ensure!(
+                Self::milestone_reward_release_in_progress(token_id).is_some()
+                    || milestone_reward_info.milestones.iter().any(|(milestone, _)| *milestone <= deposited),
+                Error::<T>::NoMilestoneRewardWaiting
+            );
+            let processed = Self::do_release_milestone_reward(
+                who,
+                &mut milestone_reward_info,
+                T::MilestoneRewardPayoutsPerCall::get(),
+            )?;
             MilestoneReward::<T>::insert(token_id, milestone_reward_info);
-            Ok(().into())
+            Ok(Some(T::WeightInfo::release_milestone_reward(processed)).into())
         }
 
         /// create a type and number of travel_cabin
@@ -587,64 +1164,22 @@ pub mod module {
             yield_total: Balance,
             maturity: T::BlockNumber,
             stockpile: TravelCabinInventoryIndex,
+            yield_schedule: YieldSchedule<T::BlockNumber>,
         ) -> DispatchResultWithPostInfo {
             let creator = T::EngineerOrigin::ensure_origin(origin)?;
-
-            match token_id {
-                CurrencyId::Token(_) => (),
-                _ => Err(Error::<T>::CurrencyNotSupported)?,
-            }
-            // deposit required cannot be zero
-            ensure!(
-                deposit_amount > Zero::zero(),
-                Error::<T>::TargetValueTooSmall
-            );
-            ensure!(
-                bonus_total >= T::CabinBonusRewardMinimum::get(),
-                Error::<T>::RewardValueTooSmall
-            );
-            ensure!(
-                yield_total >= T::CabinYieldRewardMinimum::get(),
-                Error::<T>::RewardValueTooSmall
-            );
-            ensure!(stockpile > 0, Error::<T>::TooLittleIssued);
-
-            let total_reward = yield_total
-                .saturating_add(bonus_total)
-                .saturating_mul(stockpile.into());
-
-            T::Currency::transfer(
-                token_id,
-                &Self::eng_account_id(),
-                &Self::account_id(),
-                total_reward.unique_saturated_into(),
-            )?;
-
-            // Create TravelCabin
-            let travel_cabin_idx = Self::travel_cabin_count();
-            TravelCabinCount::<T>::put(travel_cabin_idx + 1);
-            TravelCabinInventory::<T>::insert(travel_cabin_idx, (0, stockpile));
-
-            TravelCabins::<T>::insert(
-                travel_cabin_idx,
-                TravelCabinInfo {
-                    name,
-                    creator: creator.clone(),
-                    token_id,
-                    index: travel_cabin_idx,
-                    deposit_amount,
-                    bonus_total,
-                    yield_total,
-                    maturity,
-                },
-            );
-            Self::deposit_event(Event::CreatedTravelCabin(
-                creator,
-                token_id,
-                travel_cabin_idx,
-            ));
-            Ok(().into())
-        }
+            Self::do_create_travel_cabin(
+                creator,
+                token_id,
+                name,
+                deposit_amount,
+                bonus_total,
+                yield_total,
+                maturity,
+                stockpile,
+                yield_schedule,
+            )?;
+            Ok(().into())
+        }
 
         #[pallet::weight(< T as Config >::WeightInfo::issue_additional_travel_cabin())]
         #[transactional]
@@ -700,6 +1235,15 @@ pub mod module {
                         travel_cabin.deposit_amount,
                         PaymentType::WithdrawOnCompletion,
                     )?;
+                    if Self::is_portfolio_dpo(&receiver_dpo) {
+                        Self::settle_portfolio_leg(
+                            &mut receiver_dpo,
+                            Target::TravelCabin(travel_cabin_idx),
+                            Some(travel_cabin_number),
+                            travel_cabin.deposit_amount,
+                            PaymentType::WithdrawOnCompletion,
+                        )?;
+                    }
                     //persist the dpo after used. not gonna use it anywhere else
                     Dpos::<T>::insert(receiver_dpo_idx, receiver_dpo);
                 }
@@ -750,15 +1294,10 @@ pub mod module {
             );
 
             //calculate amount to be withdrawn
-            let percentage;
-            if travel_cabin.maturity.is_zero() {
-                percentage = Permill::from_percent(100);
-            } else {
-                let blk_since_purchase = now - buyer_info.purchase_blk;
-                percentage =
-                    Permill::from_rational_approximation(blk_since_purchase, travel_cabin.maturity);
-            }
-            let accumulated_yield: Balance = percentage * travel_cabin.yield_total;
+            let accumulated_yield = Self::calc_travel_cabin_accrued_yield(
+                &travel_cabin,
+                now.saturating_sub(buyer_info.purchase_blk),
+            );
             let amount = accumulated_yield.saturating_sub(buyer_info.yield_withdrawn);
             ensure!(amount > Zero::zero(), Error::<T>::NoYieldToRelease);
 
@@ -807,85 +1346,122 @@ pub mod module {
             direct_referral_rate: u32,
             end: T::BlockNumber,
             referrer: Option<T::AccountId>,
+            access_policy: DpoAccessPolicy,
         ) -> DispatchResultWithPostInfo {
-            // (a) ensure target available
-            let target_entity = Self::is_target_available(&target)?;
-
-            // (b) ensure target min and cap
-            let new_dpo_idx = Self::dpo_count();
-            if let TargetEntity::Dpo(target_dpo, target_amount) = target_entity.clone() {
-                Self::ensure_valid_dpo_purchase_amount(
-                    &target_dpo,
-                    target_amount,
-                    Buyer::Dpo(new_dpo_idx),
-                )?;
-            }
-
-            // (c) verify the attributes of new dpo
-            //check commission rate base does not exceed cap
-            ensure!(base_fee <= T::ManagementBaseFeeCap::get(), Error::<T>::ExceededRateCap);
-            ensure!(direct_referral_rate <= 1000, Error::<T>::ExceededRateCap);
-            // ending of this dpo must be in the future
-            let now = <frame_system::Module<T>>::block_number();
-            ensure!(end > now, Error::<T>::InvalidEndTime);
+            let manager = ensure_signed(origin)?;
+            Self::do_create_target_dpo(
+                manager,
+                name,
+                target,
+                manager_purchase_amount,
+                base_fee,
+                direct_referral_rate,
+                end,
+                referrer,
+                access_policy,
+            )?;
+            Ok(().into())
+        }
 
-            // check if the amount that the manager buys is over the cap
+        /// a dpo buying several travel cabins and dpo shares of the same token.
+        /// the legs are bought one by one by dpo_buy_portfolio_leg once it is filled
+        #[pallet::weight(< T as Config >::WeightInfo::create_portfolio_dpo(legs.len() as u32))]
+        #[transactional]
+        pub fn create_portfolio_dpo(
+            origin: OriginFor<T>,
+            name: Vec<u8>,
+            legs: Vec<Target<Balance>>,
+            manager_purchase_amount: Balance, // target token amount, not internal share
+            base_fee: u32,
+            direct_referral_rate: u32,
+            end: T::BlockNumber,
+            referrer: Option<T::AccountId>,
+            access_policy: DpoAccessPolicy,
+        ) -> DispatchResultWithPostInfo {
             let manager = ensure_signed(origin)?;
-            let (_, max_amount_for_manager) = Self::legit_dpo_shares_purchase_constraints(
-                target_entity.target_amount(),
-                Buyer::Passenger(manager.clone())
+            ensure!(
+                legs.len() >= 2 && legs.len() as u32 <= T::MaxPortfolioLegs::get(),
+                Error::<T>::InvalidPortfolio
             );
-            ensure!(manager_purchase_amount <= max_amount_for_manager, Error::<T>::ExceededShareCap);
 
-            // (d) construct the new dpo
-            let fee = Self::calc_dpo_manager_fee(
-                base_fee,
-                manager_purchase_amount,
-                target_entity.target_amount(),
-                false
-            );
+            let new_dpo_idx = Self::dpo_count();
             let mut new_dpo = DpoInfo {
                 index: new_dpo_idx,
                 name,
-                target,
                 manager: manager.clone(),
                 share_rate: (1, 1), // default rate=1
                 base_fee,
-                fee,
                 fee_slashed: false,
                 expiry_blk: end,
                 state: DpoState::CREATED,
                 fare_withdrawn: false,
                 direct_referral_rate,
                 referrer: referrer.clone(),
+                access_policy,
                 ..Default::default()
             };
-            Self::refresh_dpo_target_info_for_new_target(&mut new_dpo, &target_entity, false)?;
+            let mut portfolio_legs: Vec<PortfolioLeg<Balance>> = Vec::new();
+            for (i, leg) in legs.into_iter().enumerate() {
+                // (a) ensure the leg available, a portfolio is never available as a target
+                let target_entity = Self::is_target_available(&leg)?;
 
-            Self::dpo_inflow(
-                &manager,
-                &mut new_dpo,
-                manager_purchase_amount,
-                PaymentType::Deposit,
-            )?;
-            // fill the token and share, and add the manager as a new member
-            Self::insert_buyer_to_target_dpo(
-                &mut new_dpo,
-                manager_purchase_amount,
-                Buyer::Passenger(manager.clone()),
-                referrer,
-            )?;
+                // (b) ensure the leg min and cap, and the manager may join a private target
+                if let TargetEntity::Dpo(target_dpo, target_amount) = &target_entity {
+                    ensure!(
+                        !portfolio_legs.iter().any(|l| Self::compare_targets(&l.target, &leg) != TargetCompare::Different),
+                        Error::<T>::InvalidPortfolio
+                    );
+                    Self::ensure_valid_dpo_purchase_amount(
+                        target_dpo,
+                        *target_amount,
+                        Buyer::Dpo(new_dpo_idx),
+                    )?;
+                    Self::ensure_buyer_allowed_in_dpo(target_dpo, &Buyer::Passenger(manager.clone()))?;
+                }
+
+                // (c) sum up the target info, the portfolio matures with its last leg
+                let (yield_est, bonus_est, maturity) = match &target_entity {
+                    TargetEntity::Dpo(target_dpo, target_amount) => {
+                        let (yield_est, bonus_est) =
+                            Self::get_dpo_reward_estimates(target_dpo, *target_amount);
+                        (yield_est, bonus_est, target_dpo.target_maturity)
+                    }
+                    TargetEntity::TravelCabin(travel_cabin, _) => (
+                        travel_cabin.yield_total,
+                        travel_cabin.bonus_total,
+                        travel_cabin.maturity,
+                    ),
+                };
+                if i == 0 {
+                    new_dpo.token_id = target_entity.token_id();
+                }
+                ensure!(target_entity.token_id() == new_dpo.token_id, Error::<T>::InvalidPortfolio);
+                new_dpo.target_amount = new_dpo.target_amount.saturating_add(target_entity.target_amount());
+                new_dpo.target_yield_estimate = new_dpo.target_yield_estimate.saturating_add(yield_est);
+                new_dpo.target_bonus_estimate = new_dpo.target_bonus_estimate.saturating_add(bonus_est);
+                if maturity > new_dpo.target_maturity {
+                    new_dpo.target_maturity = maturity;
+                }
 
-            // update storage
-            DpoCount::<T>::put(new_dpo_idx + 1);
-            Dpos::<T>::insert(new_dpo_idx, new_dpo);
+                portfolio_legs.push(PortfolioLeg {
+                    target: leg,
+                    amount: target_entity.target_amount(),
+                    state: PortfolioLegState::Pending,
+                    cabin_number: None,
+                });
+            }
+            new_dpo.target = Target::Portfolio(new_dpo.target_amount);
 
-            //emit final event
-            Self::deposit_event(Event::CreatedDpo(manager, new_dpo_idx));
+            // (d) keep the legs and create the dpo
+            DpoPortfolioLegs::<T>::insert(new_dpo_idx, portfolio_legs);
+            Self::do_create_dpo(manager, new_dpo, manager_purchase_amount, referrer)?;
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
+        /// the new base fee takes effect after DpoBaseFeeChangeDelay, so that the members can
+        /// watch the change. it replaces a change still pending.
+        /// only for dpos not completed or failed yet
+        #[pallet::weight(< T as Config >::WeightInfo::set_dpo_base_fee())]
         #[transactional]
         pub fn set_dpo_base_fee(
             origin: OriginFor<T>,
@@ -893,9 +1469,123 @@ pub mod module {
             base_fee: u32,
         ) -> DispatchResultWithPostInfo {
             let _ = T::EngineerOrigin::ensure_origin(origin)?;
-            let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
-            dpo.base_fee = base_fee;
-            Dpos::<T>::insert(dpo_idx, dpo);
+            let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            match dpo.state {
+                DpoState::CREATED | DpoState::ACTIVE | DpoState::RUNNING => (),
+                _ => Err(Error::<T>::DpoWrongState)?,
+            }
+            ensure!(base_fee <= T::ManagementBaseFeeCap::get(), Error::<T>::ExceededRateCap);
+            ensure!(base_fee != dpo.base_fee, Error::<T>::DpoBaseFeeUnchanged);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let effective_blk = now + T::DpoBaseFeeChangeDelay::get();
+            // a replaced change stays in the queue, and is skipped as its block does not match
+            PendingDpoBaseFees::<T>::insert(dpo_idx, (base_fee, now, effective_blk));
+            DpoBaseFeeChangeQueue::<T>::append(effective_blk, dpo_idx);
+            Self::deposit_event(Event::DpoBaseFeeChangeScheduled(dpo_idx, base_fee, effective_blk));
+            Ok(().into())
+        }
+
+        /// only the manager can add buyers to or remove them from the allow list of a dpo.
+        /// a dpo buyer is also allowed if its manager is on the list. a removed buyer can not
+        /// join again with an invitation
+        #[pallet::weight(< T as Config >::WeightInfo::update_dpo_allow_list(buyers.len() as u32))]
+        #[transactional]
+        pub fn update_dpo_allow_list(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+            buyers: Vec<Buyer<T::AccountId>>,
+            allowed: bool,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                buyers.len() as u32 <= T::MaxAllowListLength::get(),
+                Error::<T>::TooManyBuyers
+            );
+            let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(dpo.manager == who, Error::<T>::NoPermission);
+
+            for buyer in buyers.iter() {
+                if let Buyer::InvalidBuyer = buyer {
+                    Err(Error::<T>::InvalidBuyerType)?
+                }
+                // removed buyers are kept as not allowed, so that their invitations are spent
+                DpoAllowList::<T>::insert(dpo_idx, buyer, allowed);
+            }
+            Self::deposit_event(Event::DpoAllowListUpdated(who, dpo_idx, buyers.len() as u32, allowed));
+            Ok(().into())
+        }
+
+        /// a buyer invited to a dpo adds itself to its allow list, with the signature of the invite
+        /// key of the dpo over the dpo index and the buyer. an invitation is used once, and can not
+        /// be used by anyone else
+        #[pallet::weight(< T as Config >::WeightInfo::redeem_dpo_invite_code())]
+        #[transactional]
+        pub fn redeem_dpo_invite_code(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+            signature: H512,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            let invite_key = match dpo.access_policy {
+                DpoAccessPolicy::InviteCode(invite_key) => sr25519::Public::from_raw(invite_key.0),
+                _ => Err(Error::<T>::InvalidInviteCode)?,
+            };
+            let buyer = Buyer::Passenger(who.clone());
+            ensure!(
+                !DpoAllowList::<T>::contains_key(dpo_idx, &buyer),
+                Error::<T>::InviteCodeAlreadyUsed
+            );
+            ensure!(
+                sr25519::Signature::from_raw(signature.0)
+                    .verify(&Self::dpo_invitation(dpo_idx, &who)[..], &invite_key),
+                Error::<T>::InvalidInviteCode
+            );
+
+            DpoAllowList::<T>::insert(dpo_idx, buyer, true);
+            Self::deposit_event(Event::DpoInviteCodeRedeemed(who, dpo_idx));
+            Ok(().into())
+        }
+
+        /// only the manager can set the referral policy of a dpo, before any other member joins
+        #[pallet::weight(< T as Config >::WeightInfo::set_dpo_referral_policy())]
+        #[transactional]
+        pub fn set_dpo_referral_policy(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+            external_referral_rate: u32,
+            referral_chain_depth: u32,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(dpo.manager == who, Error::<T>::NoPermission);
+            ensure!(
+                dpo.state == DpoState::CREATED && Self::dpo_member_count(dpo_idx) <= 1,
+                Error::<T>::DpoWrongState
+            );
+            ensure!(
+                external_referral_rate <= T::ExternalReferralRateCap::get(),
+                Error::<T>::ExceededRateCap
+            );
+            ensure!(
+                referral_chain_depth >= 1 && referral_chain_depth <= T::ReferralChainDepthCap::get(),
+                Error::<T>::InvalidReferralChainDepth
+            );
+
+            DpoReferralPolicies::<T>::insert(
+                dpo_idx,
+                DpoReferralPolicy {
+                    external_referral_rate,
+                    referral_chain_depth,
+                },
+            );
+            Self::deposit_event(Event::DpoReferralPolicySet(
+                who,
+                dpo_idx,
+                external_referral_rate,
+                referral_chain_depth,
+            ));
             Ok(().into())
         }
 
@@ -970,6 +1660,9 @@ pub mod module {
             // ensure target is available
             let target_entity = Self::is_target_available(&new_target)?;
             let mut buyer_dpo = Self::dpos(buyer_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_no_dpo_payout_in_progress(buyer_dpo_idx)?;
+            // a portfolio gives up its unavailable legs by dpo_abandon_portfolio_leg instead
+            ensure!(!Self::is_portfolio_dpo(&buyer_dpo), Error::<T>::InvalidTargetForDpo);
             // not retarget to the same dpo
             ensure!(
                 Self::compare_targets(&new_target, &buyer_dpo.target) == TargetCompare::Different,
@@ -1040,9 +1733,132 @@ pub mod module {
             Ok(().into())
         }
 
+        /// buy a pending leg of a portfolio dpo, the portfolio runs once its first leg is bought.
+        /// only for the dpo manager to call within the grace period.
+        /// any member can call after the grace period
+        #[pallet::weight(< T as Config >::WeightInfo::dpo_buy_portfolio_leg())]
+        #[transactional]
+        pub fn dpo_buy_portfolio_leg(
+            origin: OriginFor<T>,
+            buyer_dpo_idx: DpoIndex,
+            leg_idx: u32,
+        ) -> DispatchResultWithPostInfo {
+            let signer = ensure_signed(origin)?;
+            let mut buyer_dpo = Self::dpos(buyer_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_no_dpo_payout_in_progress(buyer_dpo_idx)?;
+            ensure!(
+                buyer_dpo.state == DpoState::ACTIVE || buyer_dpo.state == DpoState::RUNNING,
+                Error::<T>::DpoWrongState
+            );
+            let mut legs = Self::dpo_portfolio_legs(buyer_dpo_idx);
+            let leg = legs.get_mut(leg_idx as usize).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(leg.state == PortfolioLegState::Pending, Error::<T>::PortfolioLegNotPending);
+
+            // (a) ensure the leg available and affordable
+            let target_entity = Self::is_target_available(&leg.target)?;
+            ensure!(buyer_dpo.vault_deposit >= leg.amount, Error::<T>::TargetValueTooBig);
+
+            // (b) ensure the signer has the right, slash the manager if needed
+            let should_slash_manager = Self::if_should_slash_manager_on_buying(&buyer_dpo, signer.clone())?;
+            if should_slash_manager && !buyer_dpo.fee_slashed {
+                buyer_dpo.fee =
+                    Permill::from_perthousand(T::ManagerSlashPerThousand::get()) * buyer_dpo.fee;
+                buyer_dpo.fee_slashed = true;
+            }
+
+            // (c) do buy action, the rest of the vault_deposit is kept for the other legs
+            let buyer = Buyer::Dpo(buyer_dpo_idx);
+            match target_entity.clone() {
+                TargetEntity::TravelCabin(travel_cabin, inv_idx) => {
+                    Self::update_dpo_outflow(
+                        &mut buyer_dpo,
+                        travel_cabin.deposit_amount,
+                        PaymentType::Deposit,
+                    )?;
+                    Self::insert_cabin_purchase_record(&travel_cabin, inv_idx, buyer.clone());
+                    Self::update_milestone_record(&travel_cabin);
+                    leg.cabin_number = Some(inv_idx.0);
+
+                    // dpo receives bonus from the cabin (pallet account)
+                    if travel_cabin.bonus_total > Zero::zero() {
+                        Self::update_dpo_inflow(
+                            &mut buyer_dpo,
+                            travel_cabin.bonus_total,
+                            PaymentType::Bonus,
+                        )?;
+                    }
+                }
+                TargetEntity::Dpo(mut target_dpo, target_amount) => {
+                    Self::ensure_buyer_allowed_in_dpo(&target_dpo, &buyer)?;
+                    // the target dpo may have changed its target since the portfolio was created
+                    Self::ensure_valid_dpo_purchase_amount(&target_dpo, target_amount, buyer.clone())?;
+                    Self::dpo_outflow_to_dpo(
+                        &mut buyer_dpo,
+                        &mut target_dpo,
+                        target_amount,
+                        PaymentType::Deposit,
+                    )?;
+                    Self::insert_buyer_to_target_dpo(
+                        &mut target_dpo,
+                        target_amount,
+                        buyer.clone(),
+                        None,
+                    )?;
+                    Dpos::<T>::insert(target_dpo.index, &target_dpo); // save target dpo
+                }
+            }
+            leg.state = PortfolioLegState::Purchased;
+            buyer_dpo.state = DpoState::RUNNING;
+
+            DpoPortfolioLegs::<T>::insert(buyer_dpo_idx, legs);
+            Dpos::<T>::insert(buyer_dpo_idx, &buyer_dpo);
+            Self::deposit_event_for_buying_a_target(signer.clone(), buyer, target_entity);
+            Self::deposit_event(Event::DpoPortfolioLegPurchased(signer, buyer_dpo_idx, leg_idx));
+            Ok(().into())
+        }
+
+        /// anyone can call this function to give up a pending leg of a portfolio dpo
+        /// when its target is unavailable. the fund of the leg is returned by release_fare_from_dpo
+        #[pallet::weight(< T as Config >::WeightInfo::dpo_abandon_portfolio_leg())]
+        #[transactional]
+        pub fn dpo_abandon_portfolio_leg(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+            leg_idx: u32,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_no_dpo_payout_in_progress(dpo_idx)?;
+            ensure!(
+                dpo.state == DpoState::ACTIVE || dpo.state == DpoState::RUNNING,
+                Error::<T>::DpoWrongState
+            );
+            let mut legs = Self::dpo_portfolio_legs(dpo_idx);
+            let leg = legs.get_mut(leg_idx as usize).ok_or(Error::<T>::InvalidIndex)?;
+            ensure!(leg.state == PortfolioLegState::Pending, Error::<T>::PortfolioLegNotPending);
+            ensure!(
+                Self::is_target_available(&leg.target).is_err(),
+                Error::<T>::DefaultTargetAvailable
+            );
+
+            leg.state = PortfolioLegState::Abandoned;
+            let unused_amount = leg.amount.min(dpo.vault_deposit);
+            dpo.vault_deposit = dpo.vault_deposit.saturating_sub(unused_amount);
+            Self::update_dpo_inflow(&mut dpo, unused_amount, PaymentType::UnusedFund)?;
+            Self::complete_portfolio_if_settled(&mut dpo, &legs);
+
+            DpoPortfolioLegs::<T>::insert(dpo_idx, legs);
+            Dpos::<T>::insert(dpo_idx, &dpo);
+            Self::deposit_event(Event::DpoPortfolioLegAbandoned(who, dpo_idx, leg_idx));
+            Ok(().into())
+        }
+
         /// anyone can call this function
         /// can only withdraw from COMPLETED or FAILED state
-        #[pallet::weight(< T as Config >::WeightInfo::release_fare_from_dpo())]
+        /// the members are paid by DpoPayoutMembersPerCall, the rest by continue_dpo_payout
+        #[pallet::weight(< T as Config >::WeightInfo::release_fare_from_dpo(
+            T::DpoPayoutMembersPerCall::get()
+        ))]
         #[transactional]
         pub fn release_fare_from_dpo(
             origin: OriginFor<T>,
@@ -1051,9 +1867,11 @@ pub mod module {
             let who = ensure_signed(origin)?;
             let now = <frame_system::Module<T>>::block_number();
             let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            Self::ensure_no_dpo_payout_in_progress(dpo_idx)?;
             if let DpoState::CREATED = dpo.state {
                 if dpo.expiry_blk < now {
                     dpo.state = DpoState::FAILED;
+                    Self::return_dpo_creation_deposit(dpo_idx);
                 }
             }
             ensure!(dpo.state != DpoState::CREATED, Error::<T>::DpoWrongState);
@@ -1074,17 +1892,18 @@ pub mod module {
                 _ => Err(Error::<T>::DpoWrongState)?,
             };
 
-            Self::dpo_outflow_to_members_by_share(&mut dpo, total_amount, payment_type)?;
-            if dpo.state == DpoState::FAILED || dpo.state == DpoState::COMPLETED {
-                dpo.fare_withdrawn = true;
-            }
+            let processed = Self::do_dpo_payout(
+                who,
+                &mut dpo,
+                DpoPayoutProgress::new(payment_type, total_amount),
+                T::DpoPayoutMembersPerCall::get(),
+            )?;
             Dpos::<T>::insert(dpo.index, &dpo);
-
-            Self::deposit_event(Event::WithdrewFareFromDpo(who, dpo.index));
-            Ok(().into())
+            Ok(Some(T::WeightInfo::release_fare_from_dpo(processed)).into())
         }
 
         /// anyone can call this function
+        /// the yield is booked for the members, who get it by claim_dpo_rewards
         #[pallet::weight(< T as Config >::WeightInfo::release_yield_from_dpo())]
         #[transactional]
         pub fn release_yield_from_dpo(
@@ -1092,6 +1911,7 @@ pub mod module {
             dpo_idx: DpoIndex,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
+            Self::claim_dpo_rewards_from_target(&who, dpo_idx)?;
             let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
             match dpo.state {
                 DpoState::ACTIVE | DpoState::RUNNING | DpoState::COMPLETED => (),
@@ -1110,27 +1930,86 @@ pub mod module {
         }
 
         /// anyone can call this function
-        #[pallet::weight(< T as Config >::WeightInfo::release_bonus_from_dpo())]
+        /// the bonus is booked for the members, it goes to their referrers by claim_dpo_rewards
+        #[pallet::weight(< T as Config >::WeightInfo::release_bonus_from_dpo(Pallet::<T>::max_dpo_members()))]
         #[transactional]
         pub fn release_bonus_from_dpo(
             origin: OriginFor<T>,
             dpo_idx: DpoIndex,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
+            Self::claim_dpo_rewards_from_target(&who, dpo_idx)?;
             let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
             match dpo.state {
                 DpoState::ACTIVE | DpoState::RUNNING | DpoState::COMPLETED => (),
                 _ => Err(Error::<T>::DpoWrongState)?,
             }
 
-            Self::do_release_bonus_from_dpo(who, &mut dpo)?;
+            let members = Self::do_release_bonus_from_dpo(who, &mut dpo)?;
             //update to dpo storage
             Dpos::<T>::insert(dpo_idx, &dpo);
+            Ok(Some(T::WeightInfo::release_bonus_from_dpo(members)).into())
+        }
+
+        /// anyone can call this function to pay the next members of a fare release.
+        /// WithdrewFareFromDpo is emitted once all members are paid
+        #[pallet::weight(< T as Config >::WeightInfo::continue_dpo_payout(
+            T::DpoPayoutMembersPerCall::get()
+        ))]
+        #[transactional]
+        pub fn continue_dpo_payout(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let progress = Self::dpo_payout_in_progress(dpo_idx)
+                .ok_or(Error::<T>::NoDpoPayoutInProgress)?;
+            let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            let processed = Self::do_dpo_payout(
+                who,
+                &mut dpo,
+                progress,
+                T::DpoPayoutMembersPerCall::get(),
+            )?;
+            Dpos::<T>::insert(dpo_idx, &dpo);
+            Ok(Some(T::WeightInfo::continue_dpo_payout(processed)).into())
+        }
+
+        /// anyone can call this function to pay a member its share of the yield and bonus released
+        /// by a dpo since its last claim. the bonus of a member goes to its referrers.
+        /// a dpo member is paid into its vault, to be released to its own members.
+        /// the manager of a dpo targeting a dpo claims the bonus left by the rounding
+        #[pallet::weight(< T as Config >::WeightInfo::claim_dpo_rewards())]
+        #[transactional]
+        pub fn claim_dpo_rewards(
+            origin: OriginFor<T>,
+            dpo_idx: DpoIndex,
+            member: Buyer<T::AccountId>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let (yield_claimed, bonus_claimed) = Self::do_claim_dpo_rewards(&who, dpo_idx, member)?;
+            ensure!(
+                yield_claimed > 0 || bonus_claimed > 0,
+                Error::<T>::ZeroBalanceToWithdraw
+            );
             Ok(().into())
         }
     }
 }
 
+#[cfg(feature = "try-runtime")]
+impl<T: Config> Pallet<T> {
+    /// Checks the storage before `on_runtime_upgrade` runs the migrations
+    pub fn pre_upgrade() -> Result<(), &'static str> {
+        migration::pre_upgrade::<T>()
+    }
+
+    /// Checks the invariants of the storage migrated by `on_runtime_upgrade`
+    pub fn post_upgrade() -> Result<(), &'static str> {
+        migration::post_upgrade::<T>()
+    }
+}
+
 impl<T: Config> Pallet<T> {
     /// The account ID for bullet train
     pub fn account_id() -> T::AccountId {
@@ -1145,6 +2024,14 @@ impl<T: Config> Pallet<T> {
         T::ModuleId::get().into_sub_account(b"eng")
     }
 
+    /// yield accrued by a travel cabin `elapsed` blocks after its purchase, by its yield schedule
+    fn calc_travel_cabin_accrued_yield(
+        travel_cabin: &TravelCabinInfo<Balance, T::AccountId, T::BlockNumber>,
+        elapsed: T::BlockNumber,
+    ) -> Balance {
+        travel_cabin.yield_schedule.accrued(elapsed, travel_cabin.maturity) * travel_cabin.yield_total
+    }
+
     /// (a) add a record
     /// (b) update the inventory count
     /// (c) update the milestone record if any
@@ -1168,6 +2055,9 @@ impl<T: Config> Pallet<T> {
 
         // (b) update the inventory count
         TravelCabinInventory::<T>::insert(travel_cabin.index, (inv_idx.0 + 1, inv_idx.1));
+
+        // (c) update the milestone record if any
+        TravelCabinsSold::<T>::mutate(travel_cabin.token_id, |sold| *sold = sold.saturating_add(1));
     }
 
     /// as a generic check to see if the target available
@@ -1199,74 +2089,415 @@ impl<T: Config> Pallet<T> {
                 ensure!(inv_idx < inv_supply, Error::<T>::CabinNotAvailable);
                 Ok(TargetEntity::TravelCabin(travel_cabin, (inv_idx, inv_supply)))
             }
+            // only a dpo is created with a portfolio, its legs are the targets
+            Target::Portfolio(_) => Err(Error::<T>::InvalidTargetForDpo.into()),
         }
     }
 
-    /// find out all the triggered milestone drops and do it one by one
-    fn do_release_milestone_reward(
+    fn do_create_milestone_reward(
         who: T::AccountId,
-        milestone_reward_info: &mut MilestoneRewardInfo<Balance>,
-    ) -> DispatchResult {
-        let mut i = 0;
-        //remove while iterating the milestones list
-        //cant use vec.retain, as the inner_release function does not return bool
-        while i < milestone_reward_info.milestones.len() {
-            let (milestone, reward) = milestone_reward_info.milestones[i];
-            if milestone <= milestone_reward_info.deposited {
-                Self::do_milestone_reward_linear_payout(milestone_reward_info, reward)?;
-                Self::deposit_event(Event::MilestoneRewardReleased(
-                    who.clone(),
-                    milestone_reward_info.token_id,
-                    milestone,
-                    reward,
-                ));
-                milestone_reward_info.milestones.remove(i); //dont need to increment i
-            } else {
-                i += 1;
-            }
-        }
-        Ok(())
-    }
-
-    /// do linear reward payout to all passengers
-    /// todo: tally all buyer contribution and do one transfer only
-    fn do_milestone_reward_linear_payout(
-        milestone_reward_info: &mut MilestoneRewardInfo<Balance>,
+        token_id: CurrencyId,
+        milestone: Balance,
         reward: Balance,
     ) -> DispatchResult {
-        let account_id = Self::account_id();
-        let travel_cabin_count = Self::travel_cabin_count();
-        for travel_cabin_idx in 0..travel_cabin_count {
-            let travel_cabin =
-                Self::travel_cabins(travel_cabin_idx).ok_or(Error::<T>::InvalidIndex)?;
-            if travel_cabin.token_id != milestone_reward_info.token_id {
-                continue;
-            }
-            let (number_sold, _) =
-                Self::travel_cabin_inventory(travel_cabin_idx).ok_or(Error::<T>::InvalidIndex)?;
-            for inventory_idx in 0..number_sold {
-                let buyer_info = Self::travel_cabin_buyer(travel_cabin_idx, inventory_idx)
-                    .ok_or(Error::<T>::InvalidIndex)?;
-                let amount = travel_cabin.deposit_amount.saturating_mul(reward)
-                    / milestone_reward_info.deposited;
-                match buyer_info.buyer {
-                    Buyer::Dpo(dpo_idx) => {
-                        let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
-                        Self::update_dpo_inflow(&mut dpo, amount, PaymentType::MilestoneReward)?;
-                        Dpos::<T>::insert(dpo_idx, &dpo);
-                    }
-                    Buyer::Passenger(acc) => T::Currency::transfer(
-                        milestone_reward_info.token_id,
-                        &account_id,
-                        &acc,
-                        amount,
-                    )?,
-                    Buyer::InvalidBuyer => Err(Error::<T>::InvalidBuyerType)?,
+        ensure!(
+            reward >= T::MilestoneRewardMinimum::get(),
+            Error::<T>::RewardValueTooSmall
+        );
+        ensure!(
+            matches!(token_id, CurrencyId::Token(_)),
+            Error::<T>::CurrencyNotSupported
+        );
+
+        let mut milestone_reward_info = match Self::milestone_reward(token_id) {
+            Some(info) => info,
+            //if not, create it
+            None => MilestoneRewardInfo {
+                token_id,
+                deposited: Zero::zero(),
+                milestones: Vec::new(),
+            },
+        };
+
+        ensure!(
+            milestone > milestone_reward_info.deposited,
+            Error::<T>::RewardMilestoneInvalid
+        );
+
+        T::Currency::transfer(
+            token_id,
+            &Self::eng_account_id(),
+            &Self::account_id(),
+            reward.unique_saturated_into(),
+        )?;
+
+        milestone_reward_info.milestones.push((milestone, reward));
+
+        //update storage
+        MilestoneReward::<T>::insert(token_id, milestone_reward_info);
+        Self::deposit_event(Event::CreatedMilestoneReward(
+            who, token_id, milestone, reward,
+        ));
+        Ok(())
+    }
+
+    fn do_create_travel_cabin(
+        creator: T::AccountId,
+        token_id: CurrencyId,
+        name: Vec<u8>,
+        deposit_amount: Balance,
+        bonus_total: Balance,
+        yield_total: Balance,
+        maturity: T::BlockNumber,
+        stockpile: TravelCabinInventoryIndex,
+        yield_schedule: YieldSchedule<T::BlockNumber>,
+    ) -> DispatchResult {
+        match token_id {
+            CurrencyId::Token(_) => (),
+            _ => Err(Error::<T>::CurrencyNotSupported)?,
+        }
+        ensure!(name.len() as u32 <= T::MaxNameLength::get(), Error::<T>::NameTooLong);
+        // deposit required cannot be zero
+        ensure!(
+            deposit_amount > Zero::zero(),
+            Error::<T>::TargetValueTooSmall
+        );
+        ensure!(
+            bonus_total >= T::CabinBonusRewardMinimum::get(),
+            Error::<T>::RewardValueTooSmall
+        );
+        ensure!(
+            yield_total >= T::CabinYieldRewardMinimum::get(),
+            Error::<T>::RewardValueTooSmall
+        );
+        ensure!(stockpile > 0, Error::<T>::TooLittleIssued);
+        ensure!(
+            yield_schedule.is_valid(maturity),
+            Error::<T>::InvalidYieldSchedule
+        );
+
+        let total_reward = yield_total
+            .saturating_add(bonus_total)
+            .saturating_mul(stockpile.into());
+
+        T::Currency::transfer(
+            token_id,
+            &Self::eng_account_id(),
+            &Self::account_id(),
+            total_reward.unique_saturated_into(),
+        )?;
+
+        // Create TravelCabin
+        let travel_cabin_idx = Self::travel_cabin_count();
+        TravelCabinCount::<T>::put(travel_cabin_idx + 1);
+        TravelCabinInventory::<T>::insert(travel_cabin_idx, (0, stockpile));
+
+        TravelCabins::<T>::insert(
+            travel_cabin_idx,
+            TravelCabinInfo {
+                name,
+                creator: creator.clone(),
+                token_id,
+                index: travel_cabin_idx,
+                deposit_amount,
+                bonus_total,
+                yield_total,
+                maturity,
+                yield_schedule,
+            },
+        );
+        Self::deposit_event(Event::CreatedTravelCabin(
+            creator,
+            token_id,
+            travel_cabin_idx,
+        ));
+        Ok(())
+    }
+
+    /// a dpo buying a single target, a travel cabin or the shares of another dpo
+    fn do_create_target_dpo(
+        manager: T::AccountId,
+        name: Vec<u8>,
+        target: Target<Balance>,
+        manager_purchase_amount: Balance,
+        base_fee: u32,
+        direct_referral_rate: u32,
+        end: T::BlockNumber,
+        referrer: Option<T::AccountId>,
+        access_policy: DpoAccessPolicy,
+    ) -> DispatchResult {
+        // (a) ensure target available
+        let target_entity = Self::is_target_available(&target)?;
+
+        // (b) ensure target min and cap, and the manager may join a private target
+        let new_dpo_idx = Self::dpo_count();
+        if let TargetEntity::Dpo(target_dpo, target_amount) = target_entity.clone() {
+            Self::ensure_valid_dpo_purchase_amount(
+                &target_dpo,
+                target_amount,
+                Buyer::Dpo(new_dpo_idx),
+            )?;
+            Self::ensure_buyer_allowed_in_dpo(&target_dpo, &Buyer::Passenger(manager.clone()))?;
+        }
+
+        // (c) construct the new dpo
+        let mut new_dpo = DpoInfo {
+            index: new_dpo_idx,
+            name,
+            target,
+            manager: manager.clone(),
+            share_rate: (1, 1), // default rate=1
+            base_fee,
+            fee_slashed: false,
+            expiry_blk: end,
+            state: DpoState::CREATED,
+            fare_withdrawn: false,
+            direct_referral_rate,
+            referrer: referrer.clone(),
+            access_policy,
+            ..Default::default()
+        };
+        Self::refresh_dpo_target_info_for_new_target(&mut new_dpo, &target_entity, false)?;
+        Self::do_create_dpo(manager, new_dpo, manager_purchase_amount, referrer)
+    }
+
+    /// verify the attributes of a new dpo with its target info filled, then add the manager
+    /// as its first member
+    fn do_create_dpo(
+        manager: T::AccountId,
+        mut new_dpo: DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        manager_purchase_amount: Balance,
+        referrer: Option<T::AccountId>,
+    ) -> DispatchResult {
+        // (a) verify the attributes of new dpo
+        ensure!(new_dpo.name.len() as u32 <= T::MaxNameLength::get(), Error::<T>::NameTooLong);
+        //check commission rate base does not exceed cap
+        ensure!(new_dpo.base_fee <= T::ManagementBaseFeeCap::get(), Error::<T>::ExceededRateCap);
+        ensure!(new_dpo.direct_referral_rate <= 1000, Error::<T>::ExceededRateCap);
+        // ending of this dpo must be in the future
+        let now = <frame_system::Module<T>>::block_number();
+        let end = new_dpo.expiry_blk;
+        ensure!(end > now, Error::<T>::InvalidEndTime);
+
+        // check if the amount that the manager buys is over the cap
+        let (_, max_amount_for_manager) = Self::legit_dpo_shares_purchase_constraints(
+            new_dpo.target_amount,
+            Buyer::Passenger(manager.clone())
+        );
+        ensure!(manager_purchase_amount <= max_amount_for_manager, Error::<T>::ExceededShareCap);
+
+        // (b) the manager fee by its purchase
+        new_dpo.fee = Self::calc_dpo_manager_fee(
+            new_dpo.base_fee,
+            manager_purchase_amount,
+            new_dpo.target_amount,
+            false
+        );
+
+        Self::dpo_inflow(
+            &manager,
+            &mut new_dpo,
+            manager_purchase_amount,
+            PaymentType::Deposit,
+        )?;
+        // fill the token and share, and add the manager as a new member
+        Self::insert_buyer_to_target_dpo(
+            &mut new_dpo,
+            manager_purchase_amount,
+            Buyer::Passenger(manager.clone()),
+            referrer,
+        )?;
+
+        // (c) reserve the creation deposit from the manager
+        let new_dpo_idx = new_dpo.index;
+        let deposit = T::DpoCreationDeposit::get();
+        if deposit > Zero::zero() {
+            T::Currency::reserve(T::GetNativeCurrencyId::get(), &manager, deposit)?;
+            DpoCreationDeposits::<T>::insert(new_dpo_idx, (manager.clone(), deposit));
+        }
+
+        // update storage
+        DpoCount::<T>::put(new_dpo_idx + 1);
+        Dpos::<T>::insert(new_dpo_idx, new_dpo);
+        DpoExpiryQueue::<T>::append(end + One::one(), new_dpo_idx);
+
+        //emit final event
+        Self::deposit_event(Event::CreatedDpo(manager, new_dpo_idx));
+        Ok(())
+    }
+
+    /// find out all the triggered milestone drops and do it one by one,
+    /// paying at most `max_payouts` sold travel cabins. returning the number of cabins processed
+    fn do_release_milestone_reward(
+        who: T::AccountId,
+        milestone_reward_info: &mut MilestoneRewardInfo<Balance>,
+        max_payouts: u32,
+    ) -> Result<u32, DispatchError> {
+        let token_id = milestone_reward_info.token_id;
+        let mut processed: u32 = 0;
+        loop {
+            let mut progress = match Self::milestone_reward_release_in_progress(token_id) {
+                Some(progress) => progress,
+                None => match Self::start_milestone_reward_release(milestone_reward_info)? {
+                    Some(progress) => progress,
+                    None => break,
+                },
+            };
+
+            let (done, payouts) = Self::do_milestone_reward_linear_payout(
+                &mut progress,
+                token_id,
+                max_payouts.saturating_sub(processed),
+            )?;
+            processed = processed.saturating_add(payouts);
+
+            // pay the caller its share of the incentive by the number of cabins processed
+            let incentive_left = progress.incentive.saturating_sub(progress.incentive_paid);
+            let incentive = if done {
+                incentive_left
+            } else {
+                (Permill::from_rational_approximation(payouts, progress.total_payouts) * progress.incentive)
+                    .min(incentive_left)
+            };
+            if incentive > Zero::zero() {
+                T::Currency::transfer(token_id, &Self::account_id(), &who, incentive)?;
+                progress.incentive_paid = progress.incentive_paid.saturating_add(incentive);
+            }
+            Self::deposit_event(Event::MilestoneRewardReleaseProgressed(
+                who.clone(),
+                token_id,
+                progress.release_idx,
+                payouts,
+                incentive,
+            ));
+
+            if !done {
+                MilestoneRewardReleaseInProgress::<T>::insert(token_id, progress);
+                break;
+            }
+            MilestoneRewardReleaseInProgress::<T>::remove(token_id);
+            MilestoneRewardReleases::<T>::insert(
+                token_id,
+                progress.release_idx,
+                MilestoneRewardReleaseInfo {
+                    milestone: progress.milestone,
+                    reward: progress.reward,
+                    deposited: progress.deposited,
+                    total_paid: progress.total_paid,
+                    recipients: progress.recipients,
+                    released_by: who.clone(),
+                    released_blk: <frame_system::Module<T>>::block_number(),
+                },
+            );
+            Self::deposit_event(Event::MilestoneRewardReleased(
+                who.clone(),
+                token_id,
+                progress.release_idx,
+                progress.milestone,
+                progress.reward,
+                progress.total_paid,
+            ));
+        }
+        Ok(processed)
+    }
+
+    /// take the first triggered milestone off the waiting list and snapshot what is needed to pay it
+    fn start_milestone_reward_release(
+        milestone_reward_info: &mut MilestoneRewardInfo<Balance>,
+    ) -> Result<Option<MilestoneRewardReleaseProgress<Balance, T::BlockNumber>>, DispatchError> {
+        let token_id = milestone_reward_info.token_id;
+        let deposited = milestone_reward_info.deposited;
+        let i = match milestone_reward_info.milestones
+            .iter()
+            .position(|(milestone, _)| *milestone <= deposited) {
+            Some(i) => i,
+            None => return Ok(None),
+        };
+        let (milestone, reward) = milestone_reward_info.milestones.remove(i);
+        let total_payouts = Self::travel_cabins_sold(token_id);
+
+        let release_idx = Self::milestone_reward_release_count(token_id);
+        MilestoneRewardReleaseCount::<T>::insert(token_id, release_idx + 1);
+        Ok(Some(MilestoneRewardReleaseProgress {
+            release_idx,
+            milestone,
+            reward,
+            incentive: T::MilestoneRewardReleaseIncentive::get() * reward,
+            incentive_paid: Zero::zero(),
+            deposited,
+            total_payouts,
+            started_blk: <frame_system::Module<T>>::block_number(),
+            travel_cabin_idx: 0,
+            inventory_idx: 0,
+            total_paid: Zero::zero(),
+            recipients: 0,
+        }))
+    }
+
+    /// do linear reward payout to the passengers from the cursor on, and keep a payout record for each cabin.
+    /// returning whether all the cabins are paid and the number of cabins processed
+    /// todo: tally all buyer contribution and do one transfer only
+    fn do_milestone_reward_linear_payout(
+        progress: &mut MilestoneRewardReleaseProgress<Balance, T::BlockNumber>,
+        token_id: CurrencyId,
+        max_payouts: u32,
+    ) -> Result<(bool, u32), DispatchError> {
+        let account_id = Self::account_id();
+        let distributable = progress.reward.saturating_sub(progress.incentive);
+        let mut processed: u32 = 0;
+        let travel_cabin_count = Self::travel_cabin_count();
+        while progress.travel_cabin_idx < travel_cabin_count {
+            let travel_cabin_idx = progress.travel_cabin_idx;
+            let travel_cabin =
+                Self::travel_cabins(travel_cabin_idx).ok_or(Error::<T>::InvalidIndex)?;
+            if travel_cabin.token_id == token_id {
+                let (number_sold, _) =
+                    Self::travel_cabin_inventory(travel_cabin_idx).ok_or(Error::<T>::InvalidIndex)?;
+                while progress.inventory_idx < number_sold {
+                    if processed >= max_payouts {
+                        return Ok((false, processed));
+                    }
+                    let inventory_idx = progress.inventory_idx;
+                    let buyer_info = Self::travel_cabin_buyer(travel_cabin_idx, inventory_idx)
+                        .ok_or(Error::<T>::InvalidIndex)?;
+                    if buyer_info.purchase_blk <= progress.started_blk {
+                        let amount = (travel_cabin.deposit_amount.saturating_mul(distributable)
+                            / progress.deposited)
+                            .min(distributable.saturating_sub(progress.total_paid));
+                        match buyer_info.buyer.clone() {
+                            Buyer::Dpo(dpo_idx) => {
+                                let mut dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+                                Self::update_dpo_inflow(&mut dpo, amount, PaymentType::MilestoneReward)?;
+                                Dpos::<T>::insert(dpo_idx, &dpo);
+                            }
+                            Buyer::Passenger(acc) => T::Currency::transfer(
+                                token_id,
+                                &account_id,
+                                &acc,
+                                amount,
+                            )?,
+                            Buyer::InvalidBuyer => Err(Error::<T>::InvalidBuyerType)?,
+                        }
+                        MilestoneRewardPayouts::<T>::insert(
+                            (token_id, progress.release_idx),
+                            (travel_cabin_idx, inventory_idx),
+                            MilestoneRewardPayoutInfo {
+                                buyer: buyer_info.buyer,
+                                amount,
+                            },
+                        );
+                        progress.total_paid = progress.total_paid.saturating_add(amount);
+                        progress.recipients = progress.recipients.saturating_add(1);
+                    }
+                    progress.inventory_idx += 1;
+                    processed += 1;
                 }
             }
+            progress.travel_cabin_idx += 1;
+            progress.inventory_idx = 0;
         }
 
-        Ok(())
+        Ok((true, processed))
     }
 
     /// Return enum DpoRole
@@ -1350,6 +2581,10 @@ impl<T: Config> Pallet<T> {
     fn refresh_dpo_target_info_if_needed(
         dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
     ) -> DispatchResult {
+        // the legs of a portfolio keep their targets
+        if Self::is_portfolio_dpo(dpo) {
+            return Ok(());
+        }
         let target = Self::get_dpo_latest_target_from_its_target(dpo)?;
         if target.1 != dpo.target { // new target, to refresh dpo target info
             Self::refresh_dpo_target_info_for_new_target(dpo, &target.0, true)?;
@@ -1445,7 +2680,8 @@ impl<T: Config> Pallet<T> {
                 if dpo.state == DpoState::CREATED || dpo.state == DpoState::ACTIVE {
                     Self::refresh_dpo_target_info_if_needed(dpo)?;
                     dpo.state = DpoState::RUNNING;
-                    if dpo.vault_deposit > 0 {
+                    // a portfolio keeps the fund of its legs not bought yet
+                    if dpo.vault_deposit > 0 && !Self::is_portfolio_dpo(dpo) {
                         Self::update_dpo_inflow(
                             dpo,
                             dpo.vault_deposit,
@@ -1485,18 +2721,28 @@ impl<T: Config> Pallet<T> {
                 // vault_deposit into vault_withdraw.
                 // case 2: if unused fund comes from parent dpo, vault_deposit of child dpo has already
                 // been 0. It is still 0 after saturating_sub.
-                dpo.vault_deposit = dpo.vault_deposit.saturating_sub(amount);
+                // case 3: a portfolio keeps the fund of its other legs in vault_deposit, the caller
+                // takes the unused fund of its own leg out of it.
+                if !Self::is_portfolio_dpo(dpo) {
+                    dpo.vault_deposit = dpo.vault_deposit.saturating_sub(amount);
+                }
                 dpo.total_fund = dpo.total_fund.saturating_sub(amount);
                 dpo.vault_withdraw = dpo.vault_withdraw.saturating_add(amount);
                 dpo.share_rate = (dpo.total_fund, dpo.issued_shares); // refresh rate
             }
             PaymentType::WithdrawOnCompletion => {
                 dpo.vault_withdraw = dpo.vault_withdraw.saturating_add(amount);
-                dpo.state = DpoState::COMPLETED; // mark as COMPLETED by the V1 rule
+                // a portfolio completes once all its legs are settled, by settle_portfolio_leg
+                if !Self::is_portfolio_dpo(dpo) {
+                    dpo.state = DpoState::COMPLETED; // mark as COMPLETED by the V1 rule
+                    Self::return_dpo_creation_deposit(dpo.index);
+                }
             }
             PaymentType::WithdrawOnFailure => {
                 dpo.vault_deposit = dpo.vault_deposit.saturating_add(amount);
-                Self::activate_dpo(dpo);
+                if !Self::is_portfolio_dpo(dpo) {
+                    Self::activate_dpo(dpo);
+                }
             }
         }
         Ok(())
@@ -1519,6 +2765,15 @@ impl<T: Config> Pallet<T> {
                 ancestors.push(target_dpo_id);
             }
             Target::TravelCabin(_) => {}
+            Target::Portfolio(_) => {
+                for leg in Self::dpo_portfolio_legs(dpo.index).into_iter() {
+                    if let Target::Dpo(target_dpo_id, _) = leg.target {
+                        let target_dpo = Self::dpos(target_dpo_id).ok_or(Error::<T>::InvalidIndex)?;
+                        ancestors.append(&mut Self::get_ancestor_dpo_ids_by_child_dpo(&target_dpo)?);
+                        ancestors.push(target_dpo_id);
+                    }
+                }
+            }
         }
         Ok(ancestors)
     }
@@ -1564,6 +2819,7 @@ impl<T: Config> Pallet<T> {
                     dpo.target.clone()
                 ))
             }
+            Target::Portfolio(_) => Err(Error::<T>::InvalidTargetForDpo.into()),
         };
     }
 
@@ -1573,6 +2829,35 @@ impl<T: Config> Pallet<T> {
         buyer: Buyer<T::AccountId>,
         amount: Balance,
         payment_type: PaymentType,
+    ) -> DispatchResult {
+        Self::transfer_to_buyer(dpo.token_id, buyer.clone(), amount, payment_type)?;
+        Self::update_dpo_outflow(dpo, amount, payment_type)?;
+
+        // a portfolio member settles its leg of this dpo
+        if let Buyer::Dpo(member_dpo_idx) = buyer {
+            if matches!(payment_type, PaymentType::WithdrawOnCompletion | PaymentType::WithdrawOnFailure) {
+                let mut member_dpo = Self::dpos(member_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+                if Self::is_portfolio_dpo(&member_dpo) {
+                    Self::settle_portfolio_leg(
+                        &mut member_dpo,
+                        Target::Dpo(dpo.index, amount),
+                        None,
+                        amount,
+                        payment_type,
+                    )?;
+                    Dpos::<T>::insert(member_dpo_idx, member_dpo);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// pay a passenger from the pallet account, or book the payment in the vault of a dpo
+    fn transfer_to_buyer(
+        token_id: CurrencyId,
+        buyer: Buyer<T::AccountId>,
+        amount: Balance,
+        payment_type: PaymentType,
     ) -> DispatchResult {
         match buyer {
             Buyer::Dpo(receiver_dpo_idx) => {
@@ -1583,23 +2868,10 @@ impl<T: Config> Pallet<T> {
                 Dpos::<T>::insert(receiver_dpo_idx, receiver_dpo);
             }
             Buyer::Passenger(to_acc) => {
-                T::Currency::transfer(dpo.token_id, &Self::account_id(), &to_acc, amount)?
+                T::Currency::transfer(token_id, &Self::account_id(), &to_acc, amount)?
             }
             Buyer::InvalidBuyer => Err(Error::<T>::InvalidBuyerType)?,
         };
-        Self::update_dpo_outflow(dpo, amount, payment_type)?;
-        Ok(())
-    }
-
-    /// this function is primarily used for paying external members
-    fn dpo_outflow_to_external_account(
-        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
-        account: T::AccountId,
-        amount: Balance,
-        payment_type: PaymentType,
-    ) -> DispatchResult {
-        T::Currency::transfer(dpo.token_id, &Self::account_id(), &account, amount)?;
-        Self::update_dpo_outflow(dpo, amount, payment_type)?;
         Ok(())
     }
 
@@ -1616,6 +2888,7 @@ impl<T: Config> Pallet<T> {
     }
 
     /// release the cached yield of a dpo. slash the manager commission fee upon slashable condition
+    /// the commission is paid to the manager, the rest is booked for the members to claim by share
     fn do_release_yield_from_dpo(
         who: T::AccountId,
         dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
@@ -1623,14 +2896,14 @@ impl<T: Config> Pallet<T> {
         //check if the accumulation has started
         match dpo.blk_of_last_yield {
             None => Err(Error::<T>::NoYieldToRelease)?,
-            Some(_) => {
+            Some(blk_of_last_yield) => {
                 let mut fee = dpo.fee;
                 let now = <frame_system::Module<T>>::block_number();
                 let grace_period_over =
-                    now - dpo.blk_of_last_yield.unwrap() > T::ReleaseYieldGracePeriod::get();
+                    now - blk_of_last_yield > T::ReleaseYieldGracePeriod::get();
                 //slash (1) if grace period over and (2) not signed by manager
                 if grace_period_over {
-                    let signer_role = Self::get_signer_role_of_dpo(&dpo, &who, true)?;
+                    let signer_role = Self::get_signer_role_of_dpo(dpo, &who, true)?;
                     let mut slash_commission = true;
                     if Self::is_buyer_manager(dpo, &signer_role) {
                         slash_commission = false;
@@ -1643,12 +2916,6 @@ impl<T: Config> Pallet<T> {
                 let manager_commission = Permill::from_perthousand(fee) * dpo.vault_yield;
                 let total_reward_to_members = dpo.vault_yield.saturating_sub(manager_commission);
 
-                // weighted release to user_members
-                Self::dpo_outflow_to_members_by_share(
-                    dpo,
-                    total_reward_to_members,
-                    PaymentType::Yield,
-                )?;
                 // transfer the commission to manager.
                 Self::dpo_outflow_to_member_account(
                     dpo,
@@ -1656,6 +2923,11 @@ impl<T: Config> Pallet<T> {
                     manager_commission,
                     PaymentType::Yield,
                 )?;
+                // book the weighted release to members
+                Self::update_dpo_outflow(dpo, total_reward_to_members, PaymentType::Yield)?;
+                DpoRewardPools::<T>::mutate(dpo.index, |pool| {
+                    pool.total_yield = pool.total_yield.saturating_add(total_reward_to_members);
+                });
                 // restart the yield slashing timer
                 dpo.blk_of_last_yield = None;
                 Self::deposit_event(Event::YieldReleased(who, dpo.index));
@@ -1664,6 +2936,168 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// book the cached bonus of a dpo. bonus happens (1) on travel_cabin purchase
+    /// 'who' here is for event logging only. returning the number of members read
+    fn do_release_bonus_from_dpo(
+        who: T::AccountId,
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+    ) -> Result<u32, DispatchError> {
+        let total_bonus = dpo.vault_bonus;
+        Self::update_dpo_outflow(dpo, total_bonus, PaymentType::Bonus)?;
+        let mut pool = Self::dpo_reward_pools(dpo.index);
+        pool.total_bonus = pool.total_bonus.saturating_add(total_bonus);
+        let mut members = 0;
+        if let Target::Dpo(_, _) = dpo.target {
+            members = Self::dpo_member_count(dpo.index);
+            pool.bonus_remainder = Self::dpo_bonus_remainder(dpo, pool.total_bonus)?;
+        }
+        DpoRewardPools::<T>::insert(dpo.index, pool);
+        Self::deposit_event(Event::BonusReleased(who, dpo.index));
+        Ok(members)
+    }
+
+    /// pay a member its share of the yield and bonus booked by the dpo since its last claim.
+    /// returning the yield and bonus claimed
+    fn do_claim_dpo_rewards(
+        who: &T::AccountId,
+        dpo_idx: DpoIndex,
+        member: Buyer<T::AccountId>,
+    ) -> Result<(Balance, Balance), DispatchError> {
+        let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+        let member_info = Self::dpo_members(dpo_idx, member.clone()).ok_or(Error::<T>::InvalidIndex)?;
+        let mut pool = Self::dpo_reward_pools(dpo_idx);
+        let (mut yield_withdrawn, mut bonus_withdrawn) =
+            Self::dpo_member_rewards_withdrawn(dpo_idx, member.clone());
+
+        let yield_to_claim = Self::percentage_from_num_tuple((member_info.share, dpo.issued_shares))
+            .saturating_mul_int(pool.total_yield)
+            .saturating_sub(yield_withdrawn)
+            .min(pool.total_yield.saturating_sub(pool.total_yield_withdrawn));
+        let bonus_excluded = Self::is_bonus_excluded_manager(&dpo, &member);
+        let bonus_to_claim = if bonus_excluded {
+            pool.bonus_remainder
+                .saturating_sub(bonus_withdrawn)
+                .min(pool.total_bonus.saturating_sub(pool.total_bonus_withdrawn))
+        } else {
+            Self::percentage_from_num_tuple(
+                Self::dpo_bonus_receivable_shares(&dpo, &member_info)?
+            )
+                .saturating_mul_int(pool.total_bonus)
+                .saturating_sub(bonus_withdrawn)
+                .min(pool.total_bonus.saturating_sub(pool.total_bonus_withdrawn))
+        };
+
+        if yield_to_claim > 0 {
+            Self::transfer_to_buyer(dpo.token_id, member.clone(), yield_to_claim, PaymentType::Yield)?;
+            yield_withdrawn = yield_withdrawn.saturating_add(yield_to_claim);
+            pool.total_yield_withdrawn = pool.total_yield_withdrawn.saturating_add(yield_to_claim);
+        }
+        if bonus_to_claim > 0 {
+            if bonus_excluded {
+                Self::transfer_to_buyer(dpo.token_id, member.clone(), bonus_to_claim, PaymentType::Bonus)?;
+            } else {
+                Self::dpo_distribute_bonus_of_member(&dpo, member_info, bonus_to_claim)?;
+            }
+            bonus_withdrawn = bonus_withdrawn.saturating_add(bonus_to_claim);
+            pool.total_bonus_withdrawn = pool.total_bonus_withdrawn.saturating_add(bonus_to_claim);
+        }
+        if yield_to_claim > 0 || bonus_to_claim > 0 {
+            DpoRewardPools::<T>::insert(dpo_idx, pool);
+            DpoMemberRewardsWithdrawn::<T>::insert(dpo_idx, member.clone(), (yield_withdrawn, bonus_withdrawn));
+            Self::deposit_event(Event::DpoRewardsClaimed(
+                who.clone(),
+                dpo_idx,
+                member,
+                yield_to_claim,
+                bonus_to_claim,
+            ));
+        }
+        Ok((yield_to_claim, bonus_to_claim))
+    }
+
+    /// a dpo targeting other dpos claims its rewards from the targets before releasing its own
+    fn claim_dpo_rewards_from_target(who: &T::AccountId, dpo_idx: DpoIndex) -> DispatchResult {
+        let dpo = Self::dpos(dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+        let target_dpo_ids: Vec<DpoIndex> = match dpo.target {
+            Target::Dpo(target_dpo_idx, _) => sp_std::iter::once(target_dpo_idx).collect(),
+            Target::TravelCabin(_) => Vec::new(),
+            Target::Portfolio(_) => Self::dpo_portfolio_legs(dpo_idx)
+                .into_iter()
+                .filter_map(|leg| match leg.target {
+                    Target::Dpo(target_dpo_idx, _) => Some(target_dpo_idx),
+                    _ => None,
+                })
+                .collect(),
+        };
+        for target_dpo_idx in target_dpo_ids.into_iter() {
+            if Self::dpo_members(target_dpo_idx, Buyer::Dpo(dpo_idx)).is_some() {
+                Self::do_claim_dpo_rewards(who, target_dpo_idx, Buyer::Dpo(dpo_idx))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// the manager of a dpo targeting a dpo receives no bonus by share
+    fn is_bonus_excluded_manager(
+        dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        buyer: &Buyer<T::AccountId>,
+    ) -> bool {
+        matches!(dpo.target, Target::Dpo(_, _)) && Self::is_buyer_manager(dpo, buyer)
+    }
+
+    /// the part of the total bonus booked by a dpo targeting a dpo that its members can not claim
+    /// by share, left by the rounding. it goes to the manager
+    fn dpo_bonus_remainder(
+        dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        total_bonus: Balance,
+    ) -> Result<Balance, DispatchError> {
+        let mut receivable: Balance = Zero::zero();
+        for member_idx in 0..Self::dpo_member_count(dpo.index) {
+            let buyer = Self::dpo_member_by_index(dpo.index, member_idx).ok_or(Error::<T>::InvalidIndex)?;
+            if Self::is_buyer_manager(dpo, &buyer) {
+                continue;
+            }
+            let member_info = Self::dpo_members(dpo.index, buyer).ok_or(Error::<T>::InvalidIndex)?;
+            let bonus = Self::percentage_from_num_tuple(
+                Self::dpo_bonus_receivable_shares(dpo, &member_info)?
+            ).saturating_mul_int(total_bonus);
+            receivable = receivable.saturating_add(bonus);
+        }
+        Ok(total_bonus.saturating_sub(receivable))
+    }
+
+    /// the most members a dpo can have, each holding the minimum share
+    fn max_dpo_members() -> u32 {
+        [
+            T::PassengerSharePercentMinimum::get(),
+            T::DpoSharePercentMinimum::get(),
+            T::DpoPartialBuySharePercentMin::get(),
+        ]
+            .iter()
+            .map(|(numerator, denominator)| (*denominator as u32) / (*numerator as u32).max(1))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// bonus are firstly given to each receiving share (if targeting a cabin or a portfolio, then 100% share. Otherwise, remove the Manager's)
+    /// returning the receiving share of the member and the total receiving share
+    fn dpo_bonus_receivable_shares(
+        dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        member_info: &DpoMemberInfo<T::AccountId, Balance>,
+    ) -> Result<(Balance, Balance), DispatchError> {
+        match dpo.target {
+            Target::Dpo(_, _) => {
+                if Self::is_buyer_manager(dpo, &member_info.buyer) {
+                    return Ok((Zero::zero(), Zero::zero()));
+                }
+                let manager_info = Self::dpo_members(dpo.index, Buyer::Passenger(dpo.manager.clone()))
+                    .ok_or(Error::<T>::InvalidIndex)?;
+                Ok((member_info.share, dpo.issued_shares.saturating_sub(manager_info.share)))
+            }
+            Target::TravelCabin(_) | Target::Portfolio(_) => Ok((member_info.share, dpo.issued_shares)),
+        }
+    }
+
     fn legit_dpo_shares_purchase_constraints(
         target_dpo_amount: Balance,
         buyer: Buyer<T::AccountId>,
@@ -1727,140 +3161,122 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
-    /// payout bonus. any one can call. bonus happens (1) on travel_cabin purchase
-    /// 'who' here is for event logging only
-    fn do_release_bonus_from_dpo(
-        who: T::AccountId,
-        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+    /// the emit-catch of the bonus claimed for a member, paid from the booked bonus of the dpo
+    fn dpo_distribute_bonus_of_member(
+        dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        member_info: DpoMemberInfo<T::AccountId, Balance>,
+        bonus: Balance,
     ) -> DispatchResult {
-        // the emit-catch-divide is implemented as divide-emit-catch
-        let manager_info = Self::dpo_members(dpo.index, Buyer::Passenger(dpo.manager.clone()))
-            .ok_or(Error::<T>::InvalidIndex)?;
-
-        // step 1 (divide): bonus are firstly given to each receiving share (if targeting a cabin, then 100% share. Otherwise, remove the Manager's)
-        let (is_lead_dpo, total_receivable_share) = match dpo.target {
-            Target::Dpo(_, _) => (false, dpo.issued_shares.saturating_sub(manager_info.share)),
-            Target::TravelCabin(_) => (true, dpo.issued_shares),
-        };
+        let policy = Self::referral_policy_of_dpo(dpo.index);
+        let external_rate = Permill::from_perthousand(policy.external_referral_rate);
+        let mut emit_bonus = bonus;
+        // this is the only case that requires special handling, only in a lead dpo
+        // just wire manager's portion to him
+        if Self::is_buyer_manager(dpo, &member_info.buyer) {
+            if let Referrer::External(ext_acc, _) = member_info.referrer {
+                let external_bonus = external_rate * emit_bonus;
+                T::Currency::transfer(dpo.token_id, &Self::account_id(), &ext_acc, external_bonus)?;
+                Self::record_referral_earnings(&ext_acc, dpo.token_id, external_bonus);
+                emit_bonus -= external_bonus;
+            }
+            return Self::transfer_to_buyer(dpo.token_id, member_info.buyer, emit_bonus, PaymentType::Bonus);
+        }
 
         // step 2 (emit): compute the distributable bonus (if the member is a dpo, only its managers portion (in parent dpo). Otherwise, all of them)
-        let dpo_members = DpoMembers::<T>::iter_prefix_values(dpo.index);
-        let total_bonus = dpo.vault_bonus;
-        let mut bonus_remainder = dpo.vault_bonus;
-        for member_info in dpo_members.into_iter() {
-            // this is the only case that requires special handling
-            if Self::is_buyer_manager(dpo, &member_info.buyer) {
-                if is_lead_dpo {
-                    // just wire manager's portion to him
-                    let mut manager_portion = Self::percentage_from_num_tuple(
-                        (manager_info.share, total_receivable_share)
-                    ).saturating_mul_int(total_bonus);
-                    bonus_remainder = bonus_remainder.saturating_sub(manager_portion);
-                    if let Referrer::External(ext_acc, _) = member_info.referrer {
-                        let external_bonus = Percent::from_percent(30) * manager_portion;
-                        Self::dpo_outflow_to_external_account(
-                            dpo,
-                            ext_acc,
-                            external_bonus,
-                            PaymentType::Bonus,
-                        )?;
-                        manager_portion -= external_bonus;
-                    }
-
-                    Self::dpo_outflow_to_member_account(
-                        dpo,
-                        member_info.buyer,
-                        manager_portion,
-                        PaymentType::Bonus,
-                    )?;
-                }
-                continue;
+        if let Buyer::Dpo(member_dpo_idx) = member_info.buyer {
+            let member_dpo = Self::dpos(member_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            // a portfolio is the lead dpo of its legs, all the bonus goes to its members
+            if Self::is_portfolio_dpo(&member_dpo) {
+                return Self::transfer_to_buyer(dpo.token_id, member_info.buyer, emit_bonus, PaymentType::Bonus);
             }
+            let member_manager_info =
+                Self::dpo_members(member_dpo_idx, Buyer::Passenger(member_dpo.manager))
+                    .ok_or(Error::<T>::InvalidIndex)?;
+            let reserve_bonus = Self::percentage_from_num_tuple(
+                (member_dpo.issued_shares.saturating_sub(member_manager_info.share), member_dpo.issued_shares)
+            ).saturating_mul_int(emit_bonus);
+
+            Self::transfer_to_buyer(
+                dpo.token_id,
+                member_info.buyer,
+                reserve_bonus,
+                PaymentType::Bonus,
+            )?;
+            emit_bonus -= reserve_bonus;
+        }
 
-            let mut emit_bonus = Permill::from_rational_approximation(member_info.share, total_receivable_share) * total_bonus;
-            bonus_remainder = bonus_remainder.saturating_sub(emit_bonus);
-
-            if let Buyer::Dpo(member_dpo_idx) = member_info.buyer {
-                let member_dpo = Self::dpos(member_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
-                let member_manager_info =
-                    Self::dpo_members(member_dpo_idx, Buyer::Passenger(member_dpo.manager))
-                        .ok_or(Error::<T>::InvalidIndex)?;
-                let reserve_bonus = Self::percentage_from_num_tuple(
-                    (member_dpo.issued_shares.saturating_sub(member_manager_info.share), member_dpo.issued_shares)
-                ).saturating_mul_int(emit_bonus);
-
-                Self::dpo_outflow_to_member_account(
-                    dpo,
-                    member_info.buyer,
-                    reserve_bonus,
-                    PaymentType::Bonus,
-                )?;
-                emit_bonus -= reserve_bonus;
-            }
+        // step 3 (catch-1): if the member has an external referrer, gives him the external referral rate. Otherwise, as is.
+        if let Referrer::External(ext_acc, _) = member_info.referrer.clone() {
+            let external_bonus = external_rate * emit_bonus;
+            T::Currency::transfer(dpo.token_id, &Self::account_id(), &ext_acc, external_bonus)?;
+            Self::record_referral_earnings(&ext_acc, dpo.token_id, external_bonus);
+            emit_bonus -= external_bonus;
+        };
 
-            // step 3 (catch-1): if the member has an external referrer, gives him 30%. Otherwise, as is.
-            if let Referrer::External(ext_acc, _) = member_info.referrer.clone() {
-                let external_bonus = Percent::from_percent(30) * emit_bonus;
-                Self::dpo_outflow_to_external_account(
-                    dpo,
-                    ext_acc,
-                    external_bonus,
-                    PaymentType::Bonus,
-                )?;
-                emit_bonus -= external_bonus;
-            };
+        // step 4 (catch-2): distributable bonus goes up the referral chain by the direct_referral_rate rule
+        Self::dpo_distribute_bonus_to_referral_chain(
+            dpo,
+            member_info.referrer,
+            emit_bonus,
+            policy.referral_chain_depth,
+        )
+    }
 
-            // step 4 (catch-2): distributable bonus goes to referrers by the direct_referral_rate/1-direct_referral_rate rule
-            let parent_buyer = match member_info.referrer.clone() {
+    /// walk up the referral chain of a member. each referrer keeps direct_referral_rate of the
+    /// bonus left to it, the manager or the last referrer within the depth keeps all of it
+    fn dpo_distribute_bonus_to_referral_chain(
+        dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        referrer: Referrer<T::AccountId>,
+        bonus: Balance,
+        depth: u32,
+    ) -> DispatchResult {
+        let mut referrer = referrer;
+        let mut remaining_bonus = bonus;
+        let depth = depth.max(1);
+        for level in 1..=depth {
+            let referrer_buyer = match referrer {
                 Referrer::MemberOfDpo(buyer) | Referrer::External(_, buyer) => buyer,
                 Referrer::None => Err(Error::<T>::InvalidReferrerType)?,
             };
-
-            if Self::is_buyer_manager(dpo, &parent_buyer) {
-                //manager
-                Self::dpo_outflow_to_member_account(
-                    dpo,
-                    parent_buyer.clone(),
-                    emit_bonus,
-                    PaymentType::Bonus,
-                )?;
-            } else {
-                //member
-                let parent_bonus = Permill::from_perthousand(dpo.direct_referral_rate) * emit_bonus;
-                let grandpa_bonus = emit_bonus - parent_bonus;
-                Self::dpo_outflow_to_member_account(
-                    dpo,
-                    parent_buyer.clone(),
-                    parent_bonus,
-                    PaymentType::Bonus,
-                )?;
-                let parent =
-                    Self::dpo_members(dpo.index, parent_buyer).ok_or(Error::<T>::InvalidIndex)?;
-                let grandpa_buyer = match parent.referrer {
-                    Referrer::MemberOfDpo(buyer) | Referrer::External(_, buyer) => buyer,
-                    Referrer::None => Err(Error::<T>::InvalidReferrerType)?,
-                };
-                Self::dpo_outflow_to_member_account(
-                    dpo,
-                    grandpa_buyer,
-                    grandpa_bonus,
-                    PaymentType::Bonus,
-                )?;
+            if level == depth || Self::is_buyer_manager(dpo, &referrer_buyer) {
+                return Self::transfer_referral_bonus(dpo.token_id, referrer_buyer, remaining_bonus);
             }
+            let referrer_bonus = Permill::from_perthousand(dpo.direct_referral_rate) * remaining_bonus;
+            Self::transfer_referral_bonus(dpo.token_id, referrer_buyer.clone(), referrer_bonus)?;
+            remaining_bonus -= referrer_bonus;
+            referrer = Self::dpo_members(dpo.index, referrer_buyer)
+                .ok_or(Error::<T>::InvalidIndex)?
+                .referrer;
         }
+        Ok(())
+    }
 
-        // give the remainder bonus to the manager
-        if bonus_remainder > 0 {
-            Self::dpo_outflow_to_member_account(
-                dpo,
-                manager_info.buyer,
-                bonus_remainder,
-                PaymentType::Bonus,
-            )?;
+    /// pay a referrer inside the dpo, keeping the earnings of the accounts
+    fn transfer_referral_bonus(
+        token_id: CurrencyId,
+        buyer: Buyer<T::AccountId>,
+        amount: Balance,
+    ) -> DispatchResult {
+        if let Buyer::Passenger(acc) = &buyer {
+            Self::record_referral_earnings(acc, token_id, amount);
         }
-        Self::deposit_event(Event::BonusReleased(who, dpo.index));
+        Self::transfer_to_buyer(token_id, buyer, amount, PaymentType::Bonus)
+    }
 
-        Ok(())
+    fn record_referral_earnings(who: &T::AccountId, token_id: CurrencyId, amount: Balance) {
+        if amount > Zero::zero() {
+            ReferralEarnings::<T>::mutate(who, token_id, |earnings| {
+                *earnings = earnings.saturating_add(amount)
+            });
+        }
+    }
+
+    /// the referral policy set by the manager, or the Config defaults
+    pub fn referral_policy_of_dpo(dpo_idx: DpoIndex) -> DpoReferralPolicy {
+        Self::dpo_referral_policies(dpo_idx).unwrap_or(DpoReferralPolicy {
+            external_referral_rate: T::ExternalReferralRate::get(),
+            referral_chain_depth: T::ReferralChainDepth::get(),
+        })
     }
 
     /// reward of this dpo
@@ -1902,29 +3318,303 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
-    /// helper function for distributing weighted AMOUNT to dpo memebers
-    fn dpo_outflow_to_members_by_share(
+    /// helper function for distributing weighted AMOUNT to a dpo memeber
+    fn dpo_outflow_to_member_by_share(
         dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
-        total_amount: Balance,
-        payment_type: PaymentType,
+        progress: &mut DpoPayoutProgress<Balance>,
+        member_info: DpoMemberInfo<T::AccountId, Balance>,
     ) -> DispatchResult {
-        let mut remainder = total_amount;
-        let dpo_members = DpoMembers::<T>::iter_prefix_values(dpo.index);
-        for member_info in dpo_members.into_iter() {
-            if Self::is_buyer_manager(dpo, &member_info.buyer) { continue; };
-            let percent = Self::percentage_from_num_tuple(
-                (member_info.share, dpo.issued_shares)
-            );
-            let amount = percent.saturating_mul_int(total_amount);
-            Self::dpo_outflow_to_member_account(dpo, member_info.buyer, amount, payment_type)?;
-            remainder = remainder.saturating_sub(amount);
+        if Self::is_buyer_manager(dpo, &member_info.buyer) { return Ok(()); };
+        let percent = Self::percentage_from_num_tuple(
+            (member_info.share, dpo.issued_shares)
+        );
+        let amount = percent.saturating_mul_int(progress.total_amount);
+        Self::dpo_outflow_to_member_account(dpo, member_info.buyer, amount, progress.payment_type)?;
+        progress.remainder = progress.remainder.saturating_sub(amount);
+        Ok(())
+    }
+
+    /// pay the dpo members from the cursor on, at most max_members of them.
+    /// returning if all members are paid and the number of members paid
+    fn do_dpo_payout_members(
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        progress: &mut DpoPayoutProgress<Balance>,
+        max_members: u32,
+    ) -> Result<(bool, u32), DispatchError> {
+        let member_count = Self::dpo_member_count(dpo.index);
+        let mut processed: u32 = 0;
+        while progress.next_member_idx < member_count {
+            if processed >= max_members {
+                return Ok((false, processed));
+            }
+            let buyer = Self::dpo_member_by_index(dpo.index, progress.next_member_idx)
+                .ok_or(Error::<T>::InvalidIndex)?;
+            let member_info = Self::dpo_members(dpo.index, buyer).ok_or(Error::<T>::InvalidIndex)?;
+            Self::dpo_outflow_to_member_by_share(dpo, progress, member_info)?;
+            progress.next_member_idx += 1;
+            processed += 1;
         }
+        Ok((true, processed))
+    }
+
+    /// pay what is left to the manager once all members are paid
+    /// 'who' here is for event logging only
+    fn finish_dpo_payout(
+        who: T::AccountId,
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        progress: &DpoPayoutProgress<Balance>,
+    ) -> DispatchResult {
         Self::dpo_outflow_to_member_account(
             dpo,
             Buyer::Passenger(dpo.manager.clone()),
-            remainder,
-            payment_type,
+            progress.remainder,
+            progress.payment_type,
         )?;
+        if dpo.state == DpoState::FAILED || dpo.state == DpoState::COMPLETED {
+            dpo.fare_withdrawn = true;
+        }
+        Self::deposit_event(Event::WithdrewFareFromDpo(who, dpo.index));
+        Ok(())
+    }
+
+    /// pay at most max_members of the dpo members, the progress is saved until all of them are paid.
+    /// returning the number of members paid
+    fn do_dpo_payout(
+        who: T::AccountId,
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        mut progress: DpoPayoutProgress<Balance>,
+        max_members: u32,
+    ) -> Result<u32, DispatchError> {
+        let (done, processed) = Self::do_dpo_payout_members(dpo, &mut progress, max_members)?;
+        if done {
+            DpoPayoutInProgress::<T>::remove(dpo.index);
+            Self::finish_dpo_payout(who, dpo, &progress)?;
+        } else {
+            DpoPayoutInProgress::<T>::insert(dpo.index, &progress);
+            Self::deposit_event(Event::DpoPayoutProgressed(who, dpo.index, processed));
+        }
+        Ok(processed)
+    }
+
+    /// the message signed by the invite key of a dpo to invite a buyer
+    pub fn dpo_invitation(dpo_idx: DpoIndex, invitee: &T::AccountId) -> Vec<u8> {
+        (b"dpo_invite", dpo_idx, invitee).encode()
+    }
+
+    /// anyone can join a public dpo. a private one only its manager and the buyers on its allow list,
+    /// a dpo buyer also if its manager is on the list
+    fn ensure_buyer_allowed_in_dpo(
+        target_dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        buyer: &Buyer<T::AccountId>,
+    ) -> DispatchResult {
+        if target_dpo.access_policy == DpoAccessPolicy::Public
+            || Self::is_buyer_manager(target_dpo, buyer)
+            || Self::dpo_allow_list(target_dpo.index, buyer)
+        {
+            return Ok(());
+        }
+        if let Buyer::Dpo(buyer_dpo_idx) = buyer {
+            let buyer_dpo = Self::dpos(buyer_dpo_idx).ok_or(Error::<T>::InvalidIndex)?;
+            if Self::dpo_allow_list(target_dpo.index, Buyer::Passenger(buyer_dpo.manager)) {
+                return Ok(());
+            }
+        }
+        Err(Error::<T>::NotOnDpoAllowList.into())
+    }
+
+    fn is_portfolio_dpo(dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>) -> bool {
+        matches!(dpo.target, Target::Portfolio(_))
+    }
+
+    /// the purchased leg of a portfolio is completed when its fare comes back.
+    /// a failed dpo leg is abandoned, its refund is returned to the members as unused fund
+    fn settle_portfolio_leg(
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        leg_target: Target<Balance>,
+        cabin_number: Option<TravelCabinInventoryIndex>,
+        amount: Balance,
+        payment_type: PaymentType,
+    ) -> DispatchResult {
+        let mut legs = Self::dpo_portfolio_legs(dpo.index);
+        let leg = legs.iter_mut().find(|leg| {
+            leg.state == PortfolioLegState::Purchased
+                && leg.cabin_number == cabin_number
+                && Self::compare_targets(&leg.target, &leg_target) != TargetCompare::Different
+        });
+        if let Some(leg) = leg {
+            if let PaymentType::WithdrawOnFailure = payment_type {
+                leg.state = PortfolioLegState::Abandoned;
+                dpo.vault_deposit = dpo.vault_deposit.saturating_sub(amount);
+                Self::update_dpo_inflow(dpo, amount, PaymentType::UnusedFund)?;
+            } else {
+                leg.state = PortfolioLegState::Completed;
+            }
+            Self::complete_portfolio_if_settled(dpo, &legs);
+            DpoPortfolioLegs::<T>::insert(dpo.index, legs);
+        }
+        Ok(())
+    }
+
+    /// a portfolio completes once none of its legs is pending or purchased and one has completed.
+    /// it fails if all of them are abandoned, the unused fund is refunded as on failure
+    fn complete_portfolio_if_settled(
+        dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
+        legs: &[PortfolioLeg<Balance>],
+    ) {
+        let settled = legs.iter().all(|leg| {
+            leg.state == PortfolioLegState::Completed || leg.state == PortfolioLegState::Abandoned
+        });
+        if !settled {
+            return;
+        }
+        if legs.iter().any(|leg| leg.state == PortfolioLegState::Completed) {
+            dpo.state = DpoState::COMPLETED;
+        } else {
+            dpo.state = DpoState::FAILED;
+            dpo.vault_deposit = dpo.vault_deposit.saturating_add(dpo.vault_withdraw);
+            dpo.vault_withdraw = Zero::zero();
+            Self::deposit_event(Event::DpoFailed(dpo.index));
+        }
+        Self::return_dpo_creation_deposit(dpo.index);
+    }
+
+    /// the creation deposit goes back to the manager once the dpo completes or fails
+    fn return_dpo_creation_deposit(dpo_idx: DpoIndex) {
+        if let Some((manager, deposit)) = DpoCreationDeposits::<T>::take(dpo_idx) {
+            T::Currency::unreserve(T::GetNativeCurrencyId::get(), &manager, deposit);
+            Self::deposit_event(Event::DpoCreationDepositReturned(manager, dpo_idx, deposit));
+        }
+    }
+
+    /// fail the dpos expiring at the block, at most DpoExpiriesPerBlock of them.
+    /// the rest are moved to the front of the queue of the next block
+    fn fail_expired_dpos(now: T::BlockNumber) -> Weight {
+        let mut expired = DpoExpiryQueue::<T>::take(now);
+        if expired.is_empty() {
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+        let max_dpos = T::DpoExpiriesPerBlock::get() as usize;
+        if expired.len() > max_dpos {
+            let mut rest = expired.split_off(max_dpos);
+            DpoExpiryQueue::<T>::mutate(now + One::one(), |next| {
+                rest.append(next);
+                *next = rest;
+            });
+        }
+
+        let mut weight = T::WeightInfo::fail_expired_dpos(expired.len() as u32);
+        for dpo_idx in expired {
+            weight = weight.saturating_add(Self::fail_expired_dpo(dpo_idx, now));
+        }
+        weight
+    }
+
+    /// applies the base fee changes whose timelock is over at the block, returning the weight
+    fn apply_dpo_base_fee_changes(now: T::BlockNumber) -> Weight {
+        let dpo_idxs = DpoBaseFeeChangeQueue::<T>::take(now);
+        if dpo_idxs.is_empty() {
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+        let weight = T::WeightInfo::apply_dpo_base_fee_changes(dpo_idxs.len() as u32);
+        for dpo_idx in dpo_idxs {
+            match Self::pending_dpo_base_fees(dpo_idx) {
+                Some((base_fee, scheduled_blk, effective_blk)) if effective_blk == now => {
+                    PendingDpoBaseFees::<T>::remove(dpo_idx);
+                    Self::apply_dpo_base_fee_change(dpo_idx, base_fee, scheduled_blk, now);
+                }
+                _ => (),
+            }
+        }
+        weight
+    }
+
+    /// the effective fee is recomputed from the manager share with the new base fee.
+    /// a dpo completed or failed during the timelock keeps its fee
+    fn apply_dpo_base_fee_change(
+        dpo_idx: DpoIndex,
+        base_fee: u32,
+        scheduled_blk: T::BlockNumber,
+        now: T::BlockNumber,
+    ) {
+        let mut dpo = match Self::dpos(dpo_idx) {
+            Some(dpo) if dpo.state != DpoState::COMPLETED && dpo.state != DpoState::FAILED => dpo,
+            _ => return,
+        };
+        let manager_share = Self::dpo_members(dpo_idx, Buyer::Passenger(dpo.manager.clone()))
+            .map(|member_info| member_info.share)
+            .unwrap_or_else(Zero::zero);
+        let manager_amount = Self::percentage_from_num_tuple(dpo.share_rate)
+            .saturating_mul_int(manager_share);
+        let fee = Self::calc_dpo_manager_fee(
+            base_fee,
+            manager_amount,
+            dpo.target_amount,
+            dpo.fee_slashed,
+        );
+
+        let change_idx = Self::dpo_base_fee_change_count(dpo_idx);
+        DpoBaseFeeChanges::<T>::insert(dpo_idx, change_idx, DpoBaseFeeChangeInfo {
+            old_base_fee: dpo.base_fee,
+            new_base_fee: base_fee,
+            old_fee: dpo.fee,
+            new_fee: fee,
+            scheduled_blk,
+            changed_blk: now,
+        });
+        DpoBaseFeeChangeCount::<T>::insert(dpo_idx, change_idx + 1);
+        Self::deposit_event(Event::DpoBaseFeeChanged(dpo_idx, dpo.base_fee, base_fee, fee));
+
+        dpo.base_fee = base_fee;
+        dpo.fee = fee;
+        Dpos::<T>::insert(dpo_idx, dpo);
+    }
+
+    /// a dpo filled, or failed by release_fare_from_dpo, in time is left as it is.
+    /// returning the weight of the refund
+    fn fail_expired_dpo(dpo_idx: DpoIndex, now: T::BlockNumber) -> Weight {
+        let mut dpo = match Self::dpos(dpo_idx) {
+            Some(dpo) if dpo.state == DpoState::CREATED && dpo.expiry_blk < now => dpo,
+            _ => return 0,
+        };
+        dpo.state = DpoState::FAILED;
+        Self::return_dpo_creation_deposit(dpo_idx);
+        Self::deposit_event(Event::DpoFailed(dpo_idx));
+
+        let mut weight = 0;
+        if T::RefundExpiredDpos::get() {
+            // the members not paid here are paid by continue_dpo_payout,
+            // all of them by release_fare_from_dpo if the refund fails
+            let refund = with_transaction(|| {
+                let mut refunding_dpo = dpo.clone();
+                let progress = DpoPayoutProgress::new(
+                    PaymentType::WithdrawOnFailure,
+                    refunding_dpo.vault_deposit,
+                );
+                match Self::do_dpo_payout(
+                    Self::account_id(),
+                    &mut refunding_dpo,
+                    progress,
+                    T::DpoPayoutMembersPerCall::get(),
+                ) {
+                    Ok(processed) => TransactionOutcome::Commit(Some((refunding_dpo, processed))),
+                    Err(_) => TransactionOutcome::Rollback(None),
+                }
+            });
+            if let Some((refunded_dpo, processed)) = refund {
+                dpo = refunded_dpo;
+                weight = T::WeightInfo::release_fare_from_dpo(processed);
+            }
+        }
+        Dpos::<T>::insert(dpo_idx, &dpo);
+        weight
+    }
+
+    /// shares and vaults of a dpo must not change while it is paying the fare to its members
+    fn ensure_no_dpo_payout_in_progress(dpo_idx: DpoIndex) -> DispatchResult {
+        ensure!(
+            Self::dpo_payout_in_progress(dpo_idx).is_none(),
+            Error::<T>::DpoPayoutInProgress
+        );
         Ok(())
     }
 
@@ -1968,6 +3658,60 @@ impl<T: Config> Pallet<T> {
         result
     }
 
+    /// for rpc, the yield of a sold travel cabin that can be withdrawn now
+    pub fn get_travel_cabin_withdrawable_yield(
+        travel_cabin_idx: TravelCabinIndex,
+        travel_cabin_number: TravelCabinInventoryIndex,
+    ) -> Balance {
+        match (
+            Self::travel_cabins(travel_cabin_idx),
+            Self::travel_cabin_buyer(travel_cabin_idx, travel_cabin_number),
+        ) {
+            (Some(travel_cabin), Some(buyer_info)) => {
+                let now = <frame_system::Module<T>>::block_number();
+                Self::calc_travel_cabin_accrued_yield(
+                    &travel_cabin,
+                    now.saturating_sub(buyer_info.purchase_blk),
+                ).saturating_sub(buyer_info.yield_withdrawn)
+            }
+            _ => Zero::zero(),
+        }
+    }
+
+    /// for rpc, all the releases of the milestone rewards of a token
+    /// (release index, release block, milestone, reward, total paid)
+    pub fn get_milestone_reward_releases(
+        token_id: CurrencyId,
+    ) -> Vec<(MilestoneRewardReleaseIndex, T::BlockNumber, Balance, Balance, Balance)> {
+        let mut result: Vec<_> = MilestoneRewardReleases::<T>::iter_prefix(token_id)
+            .map(|(idx, info)| (idx, info.released_blk, info.milestone, info.reward, info.total_paid))
+            .collect();
+        result.sort_by_key(|release| release.0);
+        result
+    }
+
+    /// for rpc, milestone rewards paid to the travel cabins of an account, dpo members excluded
+    /// (token, release index, travel cabin index, inventory index, amount)
+    pub fn get_milestone_reward_payouts_of_account(
+        who: &T::AccountId,
+    ) -> Vec<(CurrencyId, MilestoneRewardReleaseIndex, TravelCabinIndex, TravelCabinInventoryIndex, Balance)> {
+        let mut result = Vec::new();
+        MilestoneRewardPayouts::<T>::iter().for_each(
+            |((token_id, release_idx), (idx, inv_idx), payout)| match payout.buyer {
+                Buyer::Passenger(acc) if *who == acc => {
+                    result.push((token_id, release_idx, idx, inv_idx, payout.amount))
+                }
+                _ => (),
+            },
+        );
+        result
+    }
+
+    /// for rpc, the referral bonus an account ever received in each token
+    pub fn get_referral_earnings_of_account(who: &T::AccountId) -> Vec<(CurrencyId, Balance)> {
+        ReferralEarnings::<T>::iter_prefix(who).collect()
+    }
+
     /// for rpc, only for user accounts
     pub fn get_dpos_of_account(who: T::AccountId) -> Vec<DpoIndex> {
         let mut result: Vec<DpoIndex> = Vec::new();
@@ -1980,6 +3724,118 @@ impl<T: Config> Pallet<T> {
         result
     }
 
+    /// for tests, try-runtime and rpc. the balance of the pallet account in each currency against
+    /// the obligations it backs, and the travel cabins and dpos breaking a per-entity invariant
+    pub fn check_invariants() -> InvariantReport<Balance> {
+        let mut report = InvariantReport::default();
+        let mut obligations: BTreeMap<CurrencyId, Balance> = BTreeMap::new();
+        let mut owe = |token_id: CurrencyId, amount: Balance| {
+            let owed = obligations.entry(token_id).or_insert_with(Zero::zero);
+            *owed = owed.saturating_add(amount);
+        };
+
+        for (travel_cabin_idx, travel_cabin) in TravelCabins::<T>::iter() {
+            let mut violations: Vec<InvariantViolation> = Vec::new();
+            let (number_sold, stockpile) =
+                Self::travel_cabin_inventory(travel_cabin_idx).unwrap_or_default();
+            if number_sold > stockpile {
+                violations.push(InvariantViolation::TravelCabinOversold);
+            }
+            // rewards of the cabins not sold yet
+            let unsold: Balance = stockpile.saturating_sub(number_sold).into();
+            owe(
+                travel_cabin.token_id,
+                travel_cabin.yield_total.saturating_add(travel_cabin.bonus_total).saturating_mul(unsold),
+            );
+            // yield and fare of the cabins sold
+            for inv_idx in 0..number_sold {
+                match Self::travel_cabin_buyer(travel_cabin_idx, inv_idx) {
+                    Some(buyer_info) => {
+                        if buyer_info.yield_withdrawn > travel_cabin.yield_total
+                            && !violations.contains(&InvariantViolation::TravelCabinYieldOverWithdrawn) {
+                            violations.push(InvariantViolation::TravelCabinYieldOverWithdrawn);
+                        }
+                        owe(travel_cabin.token_id, travel_cabin.yield_total.saturating_sub(buyer_info.yield_withdrawn));
+                        if !buyer_info.fare_withdrawn {
+                            owe(travel_cabin.token_id, travel_cabin.deposit_amount);
+                        }
+                    }
+                    None if !violations.contains(&InvariantViolation::TravelCabinBuyerMissing) => {
+                        violations.push(InvariantViolation::TravelCabinBuyerMissing)
+                    }
+                    None => (),
+                }
+            }
+            report.travel_cabins.extend(violations.into_iter().map(|v| (travel_cabin_idx, v)));
+        }
+
+        for (dpo_idx, dpo) in Dpos::<T>::iter() {
+            owe(
+                dpo.token_id,
+                dpo.vault_deposit
+                    .saturating_add(dpo.vault_withdraw)
+                    .saturating_add(dpo.vault_yield)
+                    .saturating_add(dpo.vault_bonus),
+            );
+            // released rewards booked for the members to claim
+            let pool = Self::dpo_reward_pools(dpo_idx);
+            if pool.total_yield_withdrawn > pool.total_yield || pool.total_bonus_withdrawn > pool.total_bonus {
+                report.dpos.push((dpo_idx, InvariantViolation::DpoRewardsOverWithdrawn));
+            }
+            owe(
+                dpo.token_id,
+                pool.total_yield.saturating_sub(pool.total_yield_withdrawn)
+                    .saturating_add(pool.total_bonus.saturating_sub(pool.total_bonus_withdrawn)),
+            );
+
+            let (members, shares) = DpoMembers::<T>::iter_prefix_values(dpo_idx)
+                .fold((0u32, Balance::zero()), |(members, shares), member_info| {
+                    (members.saturating_add(1), shares.saturating_add(member_info.share))
+                });
+            if shares != dpo.issued_shares {
+                report.dpos.push((dpo_idx, InvariantViolation::DpoSharesMismatch));
+            }
+            let member_count = Self::dpo_member_count(dpo_idx);
+            let indexed = (0..member_count).all(|member_idx| {
+                Self::dpo_member_by_index(dpo_idx, member_idx)
+                    .map_or(false, |buyer| DpoMembers::<T>::contains_key(dpo_idx, buyer))
+            });
+            if members != member_count || !indexed {
+                report.dpos.push((dpo_idx, InvariantViolation::DpoMemberCountMismatch));
+            }
+        }
+
+        // milestone rewards waiting for a release, and the rest of the release in progress
+        for (token_id, milestone_reward_info) in MilestoneReward::<T>::iter() {
+            let pending = milestone_reward_info.milestones
+                .iter()
+                .fold(Balance::zero(), |pending, (_, reward)| pending.saturating_add(*reward));
+            owe(token_id, pending);
+        }
+        for (token_id, progress) in MilestoneRewardReleaseInProgress::<T>::iter() {
+            owe(
+                token_id,
+                progress.reward.saturating_sub(progress.incentive_paid).saturating_sub(progress.total_paid),
+            );
+        }
+
+        let account_id = Self::account_id();
+        report.currencies = obligations
+            .into_iter()
+            .map(|(currency_id, obligations)| {
+                let balance = T::Currency::free_balance(currency_id, &account_id);
+                CurrencyAccounting {
+                    currency_id,
+                    balance,
+                    obligations,
+                    surplus: balance.saturating_sub(obligations),
+                    deficit: obligations.saturating_sub(balance),
+                }
+            })
+            .collect();
+        report
+    }
+
     fn do_dpo_post_buy_dpo(
         buyer_dpo: &mut DpoInfo<Balance, T::BlockNumber, T::AccountId>,
         target_dpo: &DpoInfo<Balance, T::BlockNumber, T::AccountId>,
@@ -2137,6 +3993,9 @@ impl<T: Config> Pallet<T> {
                 referrer: typed_referrer,
             },
         );
+        let member_idx = Self::dpo_member_count(dpo.index);
+        DpoMemberByIndex::<T>::insert(dpo.index, member_idx, buyer);
+        DpoMemberCount::<T>::insert(dpo.index, member_idx.saturating_add(1));
         Ok(())
     }
 
@@ -2166,6 +4025,7 @@ impl<T: Config> Pallet<T> {
     ) -> DispatchResult {
         // (a) ensure target available
         let target_entity = Self::is_target_available(&target)?;
+        Self::ensure_no_dpo_payout_in_progress(buyer_dpo.index)?;
         let buyer = Buyer::Dpo(buyer_dpo.index);
         let target_compare = Self::compare_targets(&target, &buyer_dpo.target);
         match target_entity.clone() {
@@ -2189,6 +4049,7 @@ impl<T: Config> Pallet<T> {
             }
             TargetEntity::Dpo(mut target_dpo, target_amount) => {
                 // (b) ensure buyer and target compliance
+                Self::ensure_buyer_allowed_in_dpo(&target_dpo, &buyer)?;
                 // same target or to same dpo
                 ensure!(target_compare != TargetCompare::Different, Error::<T>::NotAllowedToChangeTarget);
                 // if the buyer_dpo in a correct state
@@ -2284,6 +4145,7 @@ impl<T: Config> Pallet<T> {
             let target_entity = Self::is_target_available(&target)?;
             match target_entity.clone() {
                 TargetEntity::Dpo(mut target_dpo, target_amount) => {
+                    Self::ensure_buyer_allowed_in_dpo(&target_dpo, &buyer)?;
                     //ensure share min and cap
                     let target_remainder = target_dpo.target_amount.saturating_sub(target_dpo.total_fund);
                     let (min_amount, max_amount) = Self::legit_dpo_shares_purchase_constraints(
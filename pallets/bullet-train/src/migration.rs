@@ -12,6 +12,18 @@ pub struct DeprecatedTravelCabinBuyerInfo<Balance, AccountId, BlockNumber> {
     blk_of_last_withdraw: BlockNumber, //used to Govern Treasure Hunting Rule
 }
 
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+pub struct DeprecatedTravelCabinInfo<Balance, AccountId, BlockNumber> {
+    name: Vec<u8>,
+    creator: AccountId,
+    token_id: CurrencyId,
+    index: TravelCabinIndex,
+    deposit_amount: Balance,
+    bonus_total: Balance,
+    yield_total: Balance,
+    maturity: BlockNumber,
+}
+
 #[derive(Encode, Decode, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum DeprecatedTarget {
     // dpo index, number of seats
@@ -62,6 +74,45 @@ pub struct DeprecatedDpoInfo<Balance, BlockNumber, AccountId> {
     fee_slashed: bool,
 }
 
+/// DpoInfo of 3_0_0, before the access policy
+#[derive(Encode, Decode, Default, PartialEq, Eq, Clone, Debug)]
+pub struct DeprecatedDpoInfoV3<Balance, BlockNumber, AccountId> {
+    //meta
+    index: DpoIndex,
+    name: Vec<u8>,
+    token_id: CurrencyId,
+    manager: AccountId,
+    //target
+    target: Target<Balance>,
+    target_maturity: BlockNumber,
+    target_amount: Balance,
+    target_yield_estimate: Balance,
+    target_bonus_estimate: Balance,
+    issued_shares: Balance,
+    share_rate: (Balance, Balance),
+    fifo: Vec<Buyer<AccountId>>,
+    base_fee: u32,
+    fee: u32,
+    fee_slashed: bool,
+    //money
+    vault_deposit: Balance,
+    vault_withdraw: Balance,
+    vault_yield: Balance,
+    vault_bonus: Balance,
+    total_fund: Balance,
+    total_yield_received: Balance,
+    total_bonus_received: Balance,
+    total_milestone_received: Balance,
+    //time
+    blk_of_last_yield: Option<BlockNumber>,
+    blk_of_dpo_filled: Option<BlockNumber>,
+    expiry_blk: BlockNumber,
+    state: DpoState,
+    referrer: Option<AccountId>,
+    fare_withdrawn: bool,
+    direct_referral_rate: u32,
+}
+
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Copy, Debug)]
 pub struct DeprecatedDpoMemberInfo<AccountId> {
     buyer: Buyer<AccountId>,
@@ -88,10 +139,10 @@ pub fn migrate_to_v3<T: Config>() -> Weight {
         );
         if storage_version == PalletVersion::new(2, 0, 0) {
             // do migrations
-            migrate_travel_cabin_buyers::<T>();
-            migrate_dpos_and_members::<T>();
+            let weight = migrate_travel_cabin_buyers::<T>()
+                .saturating_add(migrate_dpos_and_members::<T>());
             frame_support::debug::info!("successful migration");
-            return Weight::max_value();
+            return weight;
         }
     }
     frame_support::debug::warn!(
@@ -101,13 +152,173 @@ pub fn migrate_to_v3<T: Config>() -> Weight {
     0
 }
 
-pub fn migrate_travel_cabin_buyers<T: Config>() {
+/// Apply the migration from 3_0_0 to 3_1_0. It also runs right after `migrate_to_v3`
+/// when upgrading from 2_0_0, as travel cabins were not changed by that migration.
+/// Returning the weight of the entries read and written.
+pub fn migrate_to_v3_1<T: Config>() -> Weight {
+    let maybe_storage_version = <Pallet<T>>::storage_version();
+    if let Some(storage_version) = maybe_storage_version {
+        if storage_version < PalletVersion::new(3, 1, 0) {
+            let mut weight = migrate_travel_cabins::<T>()
+                .saturating_add(migrate_travel_cabins_sold::<T>())
+                .saturating_add(migrate_dpo_member_indexes::<T>());
+            // `migrate_to_v3` already writes the dpos with their access policy
+            if storage_version >= PalletVersion::new(3, 0, 0) {
+                weight = weight.saturating_add(migrate_dpo_access_policies::<T>());
+            }
+            weight = weight
+                .saturating_add(migrate_dpo_expiry_queue::<T>())
+                .saturating_add(migrate_names::<T>());
+            frame_support::debug::info!("successful migration to 3.1.0");
+            return weight;
+        }
+    }
+    0
+}
+
+pub fn migrate_names<T: Config>() -> Weight {
+    // names longer than MaxNameLength are truncated. existing dpos hold no creation deposit
+    let max_len = T::MaxNameLength::get() as usize;
+    let mut translated: Weight = 0;
+    TravelCabins::<T>::translate_values::<TravelCabinInfo<Balance, T::AccountId, T::BlockNumber>, _>(
+        |mut travel_cabin| {
+            translated += 1;
+            travel_cabin.name.truncate(max_len);
+            Some(travel_cabin)
+        }
+    );
+    Dpos::<T>::translate_values::<DpoInfo<Balance, T::BlockNumber, T::AccountId>, _>(
+        |mut dpo| {
+            translated += 1;
+            dpo.name.truncate(max_len);
+            Some(dpo)
+        }
+    );
+    T::DbWeight::get().reads_writes(translated, translated)
+}
+
+pub fn migrate_dpo_expiry_queue<T: Config>() -> Weight {
+    // the dpos not filled yet are queued by their expiry, the expired ones are failed in this block
+    let now = <frame_system::Module<T>>::block_number();
+    let (mut reads, mut writes): (Weight, Weight) = (1, 0);
+    for (dpo_idx, dpo) in Dpos::<T>::iter() {
+        reads += 1;
+        if dpo.state == DpoState::CREATED {
+            let expiry = sp_std::cmp::max(dpo.expiry_blk + One::one(), now);
+            DpoExpiryQueue::<T>::append(expiry, dpo_idx);
+            writes += 1;
+        }
+    }
+    T::DbWeight::get().reads_writes(reads, writes)
+}
+
+pub fn migrate_dpo_access_policies<T: Config>() -> Weight {
+    // existing dpos stay open to anyone
+    let mut translated: Weight = 0;
+    Dpos::<T>::translate::<
+        DeprecatedDpoInfoV3<Balance, T::BlockNumber, T::AccountId>,
+        _
+    >(
+        |_dpo_id, dpo| {
+            translated += 1;
+            Some(DpoInfo{
+                index: dpo.index,
+                name: dpo.name,
+                token_id: dpo.token_id,
+                manager: dpo.manager,
+                target: dpo.target,
+                target_maturity: dpo.target_maturity,
+                target_amount: dpo.target_amount,
+                target_yield_estimate: dpo.target_yield_estimate,
+                target_bonus_estimate: dpo.target_bonus_estimate,
+                issued_shares: dpo.issued_shares,
+                share_rate: dpo.share_rate,
+                fifo: dpo.fifo,
+                base_fee: dpo.base_fee,
+                fee: dpo.fee,
+                fee_slashed: dpo.fee_slashed,
+                vault_deposit: dpo.vault_deposit,
+                vault_withdraw: dpo.vault_withdraw,
+                vault_yield: dpo.vault_yield,
+                vault_bonus: dpo.vault_bonus,
+                total_fund: dpo.total_fund,
+                total_yield_received: dpo.total_yield_received,
+                total_bonus_received: dpo.total_bonus_received,
+                total_milestone_received: dpo.total_milestone_received,
+                blk_of_last_yield: dpo.blk_of_last_yield,
+                blk_of_dpo_filled: dpo.blk_of_dpo_filled,
+                expiry_blk: dpo.expiry_blk,
+                state: dpo.state,
+                referrer: dpo.referrer,
+                fare_withdrawn: dpo.fare_withdrawn,
+                direct_referral_rate: dpo.direct_referral_rate,
+                access_policy: DpoAccessPolicy::Public,
+            })
+        }
+    );
+    T::DbWeight::get().reads_writes(translated, translated)
+}
+
+pub fn migrate_dpo_member_indexes<T: Config>() -> Weight {
+    // the joining order of existing members is unknown, index them in storage order
+    let mut members: Weight = 0;
+    for (dpo_id, buyer, _) in DpoMembers::<T>::iter() {
+        members += 1;
+        let member_idx = DpoMemberCount::<T>::get(dpo_id);
+        DpoMemberByIndex::<T>::insert(dpo_id, member_idx, buyer);
+        DpoMemberCount::<T>::insert(dpo_id, member_idx.saturating_add(1));
+    }
+    T::DbWeight::get().reads_writes(members.saturating_mul(2), members.saturating_mul(2))
+}
+
+pub fn migrate_travel_cabins<T: Config>() -> Weight {
+    // existing travel cabins keep releasing their yield linearly
+    let mut translated: Weight = 0;
+    TravelCabins::<T>::translate::<
+        DeprecatedTravelCabinInfo<Balance, T::AccountId, T::BlockNumber>,
+        _
+    >(
+        |_cabin_id, travel_cabin| {
+            translated += 1;
+            Some(TravelCabinInfo{
+                name: travel_cabin.name,
+                creator: travel_cabin.creator,
+                token_id: travel_cabin.token_id,
+                index: travel_cabin.index,
+                deposit_amount: travel_cabin.deposit_amount,
+                bonus_total: travel_cabin.bonus_total,
+                yield_total: travel_cabin.yield_total,
+                maturity: travel_cabin.maturity,
+                yield_schedule: YieldSchedule::Linear,
+            })
+        }
+    );
+    T::DbWeight::get().reads_writes(translated, translated)
+}
+
+pub fn migrate_travel_cabins_sold<T: Config>() -> Weight {
+    // count the travel cabins sold so far per token
+    let mut cabins: Weight = 0;
+    for (travel_cabin_idx, (number_sold, _)) in TravelCabinInventory::<T>::iter() {
+        if let Some(travel_cabin) = TravelCabins::<T>::get(travel_cabin_idx) {
+            cabins += 1;
+            TravelCabinsSold::<T>::mutate(travel_cabin.token_id, |sold| {
+                *sold = sold.saturating_add(number_sold.into())
+            });
+        }
+    }
+    T::DbWeight::get().reads_writes(cabins.saturating_mul(3), cabins)
+}
+
+pub fn migrate_travel_cabin_buyers<T: Config>() -> Weight {
     // transform the storage values from the old TravelCabinBuyerInfo into the new format.
+    let mut translated: Weight = 0;
     TravelCabinBuyer::<T>::translate::<
         DeprecatedTravelCabinBuyerInfo<Balance, T::AccountId, T::BlockNumber>,
         _
     >(
         |_cabin_id, _inv_id, cabin_buyer_info| {
+            translated += 1;
             Some(TravelCabinBuyerInfo{
                 buyer: cabin_buyer_info.buyer,
                 purchase_blk: cabin_buyer_info.purchase_blk,
@@ -116,16 +327,19 @@ pub fn migrate_travel_cabin_buyers<T: Config>() {
             })
         }
     );
+    T::DbWeight::get().reads_writes(translated, translated)
 }
 
-pub fn migrate_dpos_and_members<T: Config>() {
+pub fn migrate_dpos_and_members<T: Config>() -> Weight {
     // transform the storage values from the old DpoInfo into the new format.
+    let mut translated: Weight = 0;
     let mut dpos = vec![DpoInfo{..Default::default()}; DpoCount::<T>::get() as usize];
     Dpos::<T>::translate::<
         DeprecatedDpoInfo<Balance, T::BlockNumber, T::AccountId>,
         _
     >(
         |_dpo_id, dpo| {
+            translated += 1;
             let target = match dpo.target.clone() {
                 DeprecatedTarget::TravelCabin(cabin_id) => Target::TravelCabin(cabin_id),
                 DeprecatedTarget::Dpo(dpo_id, _) => Target::Dpo(dpo_id, dpo.target_amount), // seat to token amount
@@ -168,6 +382,7 @@ pub fn migrate_dpos_and_members<T: Config>() {
                 referrer: dpo.referrer,
                 fare_withdrawn: dpo.fare_withdrawn,
                 direct_referral_rate: dpo.direct_referral_rate,
+                access_policy: DpoAccessPolicy::Public,
             };
             dpos[dpo.index as usize] = new_dpo.clone();
             Some(new_dpo)
@@ -180,6 +395,7 @@ pub fn migrate_dpos_and_members<T: Config>() {
         _
     >(
         |dpo_id, _buyer, member_info| {
+            translated += 1;
             let dpo = &dpos[dpo_id as usize];
             let share = dpo.target_amount.saturating_mul(member_info.number_of_seats.into())
                 .checked_div(100).unwrap_or_else(Zero::zero);
@@ -190,6 +406,119 @@ pub fn migrate_dpos_and_members<T: Config>() {
             })
         }
     );
+    // the dpo count is read once
+    T::DbWeight::get().reads_writes(translated.saturating_add(1), translated)
+}
+
+/// storage key holding the counts recorded by `pre_upgrade`, for `post_upgrade` to compare against
+#[cfg(feature = "try-runtime")]
+const TRY_RUNTIME_COUNTS_KEY: &[u8] = b":bullet-train:try-runtime:counts";
+
+/// Checks the storage the migrations start from and records the cabin and dpo counts.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+    let storage_version = <Pallet<T>>::storage_version().ok_or("bullet-train storage version is missing")?;
+    if storage_version < PalletVersion::new(2, 0, 0) {
+        return Err("bullet-train storage version is older than 2.0.0");
+    }
+    frame_support::storage::unhashed::put(
+        TRY_RUNTIME_COUNTS_KEY,
+        &(TravelCabinCount::<T>::get(), DpoCount::<T>::get()),
+    );
+    Ok(())
+}
+
+/// Checks the invariants of the migrated storage, logging every one that does not hold.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+    let (cabin_count, dpo_count): (TravelCabinIndex, DpoIndex) =
+        frame_support::storage::unhashed::take(TRY_RUNTIME_COUNTS_KEY)
+            .ok_or("bullet-train pre_upgrade did not run")?;
+    let max_len = T::MaxNameLength::get() as usize;
+    let queued_dpos: sp_std::collections::btree_set::BTreeSet<DpoIndex> =
+        DpoExpiryQueue::<T>::iter_values().flatten().collect();
+    let mut failures: u32 = 0;
+    let mut fail = |message: &str, index: u32| {
+        frame_support::debug::error!("bullet-train invariant failed: {} ({})", message, index);
+        failures = failures.saturating_add(1);
+    };
+
+    if TravelCabinCount::<T>::get() != cabin_count {
+        fail("travel cabin count changed", cabin_count);
+    }
+    if DpoCount::<T>::get() != dpo_count {
+        fail("dpo count changed", dpo_count);
+    }
+    for cabin_idx in 0..cabin_count {
+        match TravelCabins::<T>::get(cabin_idx) {
+            Some(travel_cabin) => {
+                if travel_cabin.index != cabin_idx {
+                    fail("travel cabin stored under another index", cabin_idx);
+                }
+                if travel_cabin.name.len() > max_len {
+                    fail("travel cabin name too long", cabin_idx);
+                }
+            }
+            None => fail("travel cabin missing or not decodable", cabin_idx),
+        }
+    }
+    for dpo_idx in 0..dpo_count {
+        let dpo = match Dpos::<T>::get(dpo_idx) {
+            Some(dpo) => dpo,
+            None => {
+                fail("dpo missing or not decodable", dpo_idx);
+                continue;
+            }
+        };
+        if dpo.index != dpo_idx {
+            fail("dpo stored under another index", dpo_idx);
+        }
+        if dpo.name.len() > max_len {
+            fail("dpo name too long", dpo_idx);
+        }
+        let member_count = DpoMemberCount::<T>::get(dpo_idx);
+        if DpoMembers::<T>::iter_prefix_values(dpo_idx).count() as u32 != member_count {
+            fail("dpo member count does not match its members", dpo_idx);
+        }
+        for member_idx in 0..member_count {
+            let indexed = DpoMemberByIndex::<T>::get(dpo_idx, member_idx)
+                .map_or(false, |buyer| DpoMembers::<T>::contains_key(dpo_idx, buyer));
+            if !indexed {
+                fail("dpo member index does not point to a member", dpo_idx);
+            }
+        }
+        if dpo.state == DpoState::CREATED && !queued_dpos.contains(&dpo_idx) {
+            fail("created dpo not queued for expiry", dpo_idx);
+        }
+    }
+    for (dpo_idx, progress) in DpoPayoutInProgress::<T>::iter() {
+        if let PaymentType::Yield | PaymentType::Bonus = progress.payment_type {
+            fail("yield or bonus payout still in progress", dpo_idx);
+        }
+    }
+
+    let report = <Pallet<T>>::check_invariants();
+    for accounting in report.currencies.iter().filter(|accounting| accounting.deficit > 0) {
+        frame_support::debug::error!(
+            "bullet-train invariant failed: {:?} in deficit by {}",
+            accounting.currency_id,
+            accounting.deficit,
+        );
+        failures = failures.saturating_add(1);
+    }
+    for (cabin_idx, violation) in report.travel_cabins.iter() {
+        frame_support::debug::error!("bullet-train invariant failed: {:?} ({})", violation, cabin_idx);
+        failures = failures.saturating_add(1);
+    }
+    for (dpo_idx, violation) in report.dpos.iter() {
+        frame_support::debug::error!("bullet-train invariant failed: {:?} ({})", violation, dpo_idx);
+        failures = failures.saturating_add(1);
+    }
+
+    if failures > 0 {
+        return Err("bullet-train invariants do not hold after the migrations");
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -303,17 +632,37 @@ mod tests {
         pub const ManagerSlashPerThousand: u32 = 500;
         pub const ManagementFeeCap: u32 = 200; // per thousand
         pub const ManagementBaseFeeCap: u32 = 50; // per thousand
+        pub const ExternalReferralRate: u32 = 300; // per thousand
+        pub const ReferralChainDepth: u32 = 2;
+        pub const ExternalReferralRateCap: u32 = 500; // per thousand
+        pub const ReferralChainDepthCap: u32 = 5;
+        pub const MaxPortfolioLegs: u32 = 5;
+        pub const MaxAllowListLength: u32 = 10;
+        pub const DpoCreationDeposit: Balance = 0;
+        pub const MaxNameLength: u32 = 16;
+        pub const DpoBaseFeeChangeDelay: BlockNumber = 5;
         pub const MilestoneRewardMinimum: Balance = 10;
+        pub const MilestoneRewardReleaseIncentive: Permill = Permill::from_percent(1);
+        pub const MilestoneRewardPayoutsPerCall: u32 = 100;
+        pub const DpoPayoutMembersPerCall: u32 = 100;
+        pub const DpoExpiriesPerBlock: u32 = 100;
+        pub const RefundExpiredDpos: bool = false;
         pub const CabinYieldRewardMinimum: Balance = 0;
         pub const CabinBonusRewardMinimum: Balance = 0;
     }
     impl Config for Test {
         type Event = Event;
         type Currency = Currencies;
+        type GetNativeCurrencyId = GetNativeCurrencyId;
         type ModuleId = BulletTrainId;
         type ReleaseYieldGracePeriod = ReleaseYieldGracePeriod;
         type DpoMakePurchaseGracePeriod = DpoMakePurchaseGracePeriod;
         type MilestoneRewardMinimum = MilestoneRewardMinimum;
+        type MilestoneRewardReleaseIncentive = MilestoneRewardReleaseIncentive;
+        type MilestoneRewardPayoutsPerCall = MilestoneRewardPayoutsPerCall;
+        type DpoPayoutMembersPerCall = DpoPayoutMembersPerCall;
+        type DpoExpiriesPerBlock = DpoExpiriesPerBlock;
+        type RefundExpiredDpos = RefundExpiredDpos;
         type CabinYieldRewardMinimum = CabinYieldRewardMinimum;
         type CabinBonusRewardMinimum = CabinBonusRewardMinimum;
         type DpoSharePercentCap = DpoSharePercentCap;
@@ -324,6 +673,15 @@ mod tests {
         type ManagerSlashPerThousand = ManagerSlashPerThousand;
         type ManagementFeeCap = ManagementFeeCap;
         type ManagementBaseFeeCap = ManagementBaseFeeCap;
+        type ExternalReferralRate = ExternalReferralRate;
+        type ReferralChainDepth = ReferralChainDepth;
+        type ExternalReferralRateCap = ExternalReferralRateCap;
+        type ReferralChainDepthCap = ReferralChainDepthCap;
+        type MaxPortfolioLegs = MaxPortfolioLegs;
+        type MaxAllowListLength = MaxAllowListLength;
+        type DpoCreationDeposit = DpoCreationDeposit;
+        type MaxNameLength = MaxNameLength;
+        type DpoBaseFeeChangeDelay = DpoBaseFeeChangeDelay;
         type EngineerOrigin = EnsureSignedBy<Alice, AccountId>;
         type WeightInfo = weights::SubstrateWeight<Test>;
     }
@@ -378,6 +736,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn migrate_travel_cabins_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_travel_cabins::<Test>();
+            let travel_cabin = BulletTrain::travel_cabins(0).unwrap();
+            assert_eq!(travel_cabin.index, 0);
+            assert_eq!(travel_cabin.yield_schedule, YieldSchedule::Linear);
+        });
+    }
+
+    #[test]
+    fn migrate_travel_cabins_sold_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_travel_cabins::<Test>();
+            migrate_travel_cabins_sold::<Test>();
+            let travel_cabin = BulletTrain::travel_cabins(0).unwrap();
+            let mut number_sold: u32 = 0;
+            for travel_cabin_idx in 0..BulletTrain::travel_cabin_count() {
+                if BulletTrain::travel_cabins(travel_cabin_idx).unwrap().token_id == travel_cabin.token_id {
+                    number_sold += BulletTrain::travel_cabin_inventory(travel_cabin_idx).unwrap().0 as u32;
+                }
+            }
+            assert!(number_sold > 0);
+            assert_eq!(BulletTrain::travel_cabins_sold(travel_cabin.token_id), number_sold);
+        });
+    }
+
     #[test]
     fn migrate_dpos_and_members_test() {
         let ext = ExtBuilder{}.build();
@@ -435,6 +822,99 @@ mod tests {
         });
     }
 
+    #[test]
+    fn migrate_dpo_member_indexes_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_dpos_and_members::<Test>();
+            migrate_dpo_member_indexes::<Test>();
+
+            let member_count = BulletTrain::dpo_member_count(15);
+            assert!(member_count > 0);
+            let mut shares = 0;
+            for member_idx in 0..member_count {
+                let buyer = BulletTrain::dpo_member_by_index(15, member_idx).unwrap();
+                shares += BulletTrain::dpo_members(15, buyer).unwrap().share;
+            }
+            // all members of dpo15 indexed
+            assert_eq!(shares, BulletTrain::dpos(15).unwrap().issued_shares);
+        });
+    }
+
+    #[test]
+    fn migrate_dpo_access_policies_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_dpos_and_members::<Test>();
+            // write the dpos back in the 3.0.0 format
+            let dpo_count = BulletTrain::dpo_count();
+            for idx in 0..dpo_count {
+                let dpo = BulletTrain::dpos(idx).unwrap();
+                let old_dpo = DeprecatedDpoInfoV3 {
+                    index: dpo.index,
+                    name: dpo.name,
+                    token_id: dpo.token_id,
+                    manager: dpo.manager,
+                    target: dpo.target,
+                    target_amount: dpo.target_amount,
+                    issued_shares: dpo.issued_shares,
+                    total_fund: dpo.total_fund,
+                    state: dpo.state,
+                    direct_referral_rate: dpo.direct_referral_rate,
+                    ..Default::default()
+                };
+                frame_support::storage::unhashed::put(&Dpos::<Test>::hashed_key_for(idx), &old_dpo);
+            }
+
+            migrate_dpo_access_policies::<Test>();
+            for idx in 0..dpo_count {
+                assert_eq!(BulletTrain::dpos(idx).unwrap().access_policy, DpoAccessPolicy::Public);
+            }
+            let dpo15 = BulletTrain::dpos(15).unwrap();
+            assert_eq!(dpo15.index, 15);
+            assert_eq!(dpo15.target, Target::Dpo(14, 3000000000000u128));
+            assert_eq!(dpo15.state, DpoState::RUNNING);
+        });
+    }
+
+    #[test]
+    fn migrate_dpo_expiry_queue_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_dpos_and_members::<Test>();
+            frame_system::Module::<Test>::set_block_number(10);
+            migrate_dpo_expiry_queue::<Test>();
+
+            // dpo20 before migration: created state
+            let dpo20 = BulletTrain::dpos(20).unwrap();
+            let expiry = sp_std::cmp::max(dpo20.expiry_blk + 1, 10);
+            assert!(BulletTrain::dpo_expiry_queue(expiry).contains(&20));
+            // dpo15 before migration: running state
+            let dpo15 = BulletTrain::dpos(15).unwrap();
+            assert!(!BulletTrain::dpo_expiry_queue(dpo15.expiry_blk + 1).contains(&15));
+        });
+    }
+
+    #[test]
+    fn migrate_names_test() {
+        let ext = ExtBuilder{}.build();
+        assimilate_storage_from_cache(ext).execute_with(|| {
+            migrate_dpos_and_members::<Test>();
+            let mut dpo15 = BulletTrain::dpos(15).unwrap();
+            dpo15.name = vec![b'a'; 20];
+            Dpos::<Test>::insert(15, dpo15);
+
+            migrate_names::<Test>();
+            assert_eq!(BulletTrain::dpos(15).unwrap().name, vec![b'a'; 16]);
+            for (_, dpo) in Dpos::<Test>::iter() {
+                assert!(dpo.name.len() <= 16);
+            }
+            for (_, travel_cabin) in TravelCabins::<Test>::iter() {
+                assert!(travel_cabin.name.len() <= 16);
+            }
+        });
+    }
+
     fn assimilate_storage_from_cache(mut ext: sp_io::TestExternalities) -> sp_io::TestExternalities {
         if let Ok(kv) = read_test_data() {
             for (k, v) in kv {
@@ -1,7 +1,7 @@
 use super::*;
 use crate as pallet_bullet_train;
 use frame_support::ord_parameter_types;
-use frame_support::{construct_runtime, parameter_types, weights::Weight};
+use frame_support::{construct_runtime, parameter_types, traits::OnInitialize, weights::Weight};
 use frame_system::EnsureSignedBy;
 use orml_currencies::BasicCurrencyAdapter;
 use orml_traits::parameter_type_with_key;
@@ -12,6 +12,7 @@ use sp_runtime::{
     traits::{BlakeTwo256, IdentityLookup},
     Perbill,
 };
+use std::cell::RefCell;
 
 pub type Balance = u128;
 pub type AccountId = u128; // u64 is not enough to hold bytes used to generate dpo account
@@ -108,6 +109,75 @@ ord_parameter_types! {
     pub const Alice: AccountId = 0;
 }
 
+thread_local! {
+    static MILESTONE_REWARD_RELEASE_INCENTIVE: RefCell<Permill> = RefCell::new(Permill::zero());
+    static MILESTONE_REWARD_PAYOUTS_PER_CALL: RefCell<u32> = RefCell::new(100);
+    static DPO_PAYOUT_MEMBERS_PER_CALL: RefCell<u32> = RefCell::new(100);
+    static DPO_EXPIRIES_PER_BLOCK: RefCell<u32> = RefCell::new(100);
+    static REFUND_EXPIRED_DPOS: RefCell<bool> = RefCell::new(false);
+    static DPO_CREATION_DEPOSIT: RefCell<Balance> = RefCell::new(0);
+}
+
+pub struct MilestoneRewardReleaseIncentive;
+impl Get<Permill> for MilestoneRewardReleaseIncentive {
+    fn get() -> Permill {
+        MILESTONE_REWARD_RELEASE_INCENTIVE.with(|v| *v.borrow())
+    }
+}
+
+pub struct MilestoneRewardPayoutsPerCall;
+impl Get<u32> for MilestoneRewardPayoutsPerCall {
+    fn get() -> u32 {
+        MILESTONE_REWARD_PAYOUTS_PER_CALL.with(|v| *v.borrow())
+    }
+}
+
+pub struct DpoPayoutMembersPerCall;
+impl Get<u32> for DpoPayoutMembersPerCall {
+    fn get() -> u32 {
+        DPO_PAYOUT_MEMBERS_PER_CALL.with(|v| *v.borrow())
+    }
+}
+
+pub struct DpoExpiriesPerBlock;
+impl Get<u32> for DpoExpiriesPerBlock {
+    fn get() -> u32 {
+        DPO_EXPIRIES_PER_BLOCK.with(|v| *v.borrow())
+    }
+}
+
+pub struct RefundExpiredDpos;
+impl Get<bool> for RefundExpiredDpos {
+    fn get() -> bool {
+        REFUND_EXPIRED_DPOS.with(|v| *v.borrow())
+    }
+}
+
+pub struct DpoCreationDeposit;
+impl Get<Balance> for DpoCreationDeposit {
+    fn get() -> Balance {
+        DPO_CREATION_DEPOSIT.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_dpo_creation_deposit(deposit: Balance) {
+    DPO_CREATION_DEPOSIT.with(|v| *v.borrow_mut() = deposit);
+}
+
+pub fn set_dpo_expiry_config(expiries_per_block: u32, refund: bool) {
+    DPO_EXPIRIES_PER_BLOCK.with(|v| *v.borrow_mut() = expiries_per_block);
+    REFUND_EXPIRED_DPOS.with(|v| *v.borrow_mut() = refund);
+}
+
+pub fn set_dpo_payout_members_per_call(members_per_call: u32) {
+    DPO_PAYOUT_MEMBERS_PER_CALL.with(|v| *v.borrow_mut() = members_per_call);
+}
+
+pub fn set_milestone_reward_release_config(incentive: Permill, payouts_per_call: u32) {
+    MILESTONE_REWARD_RELEASE_INCENTIVE.with(|v| *v.borrow_mut() = incentive);
+    MILESTONE_REWARD_PAYOUTS_PER_CALL.with(|v| *v.borrow_mut() = payouts_per_call);
+}
+
 parameter_types! {
     pub const BulletTrainId: ModuleId = ModuleId(*b"sp/blttn");
     pub const ReleaseYieldGracePeriod: BlockNumber = 10;
@@ -120,6 +190,14 @@ parameter_types! {
     pub const ManagerSlashPerThousand: u32 = 500;
     pub const ManagementFeeCap: u32 = 200; // per thousand
     pub const ManagementBaseFeeCap: u32 = 50; // per thousand
+    pub const ExternalReferralRate: u32 = 300; // per thousand
+    pub const ReferralChainDepth: u32 = 2;
+    pub const ExternalReferralRateCap: u32 = 500; // per thousand
+    pub const ReferralChainDepthCap: u32 = 5;
+    pub const MaxPortfolioLegs: u32 = 5;
+    pub const MaxAllowListLength: u32 = 10;
+    pub const MaxNameLength: u32 = 16;
+    pub const DpoBaseFeeChangeDelay: BlockNumber = 5;
     pub const MilestoneRewardMinimum: Balance = 10;
     pub const CabinYieldRewardMinimum: Balance = 0;
     pub const CabinBonusRewardMinimum: Balance = 0;
@@ -127,10 +205,16 @@ parameter_types! {
 impl Config for Test {
     type Event = Event;
     type Currency = Currencies;
+    type GetNativeCurrencyId = GetNativeCurrencyId;
     type ModuleId = BulletTrainId;
     type ReleaseYieldGracePeriod = ReleaseYieldGracePeriod;
     type DpoMakePurchaseGracePeriod = DpoMakePurchaseGracePeriod;
     type MilestoneRewardMinimum = MilestoneRewardMinimum;
+    type MilestoneRewardReleaseIncentive = MilestoneRewardReleaseIncentive;
+    type MilestoneRewardPayoutsPerCall = MilestoneRewardPayoutsPerCall;
+    type DpoPayoutMembersPerCall = DpoPayoutMembersPerCall;
+    type DpoExpiriesPerBlock = DpoExpiriesPerBlock;
+    type RefundExpiredDpos = RefundExpiredDpos;
     type CabinYieldRewardMinimum = CabinYieldRewardMinimum;
     type CabinBonusRewardMinimum = CabinBonusRewardMinimum;
     type DpoSharePercentCap = DpoSharePercentCap;
@@ -141,6 +225,15 @@ impl Config for Test {
     type ManagerSlashPerThousand = ManagerSlashPerThousand;
     type ManagementFeeCap = ManagementFeeCap;
     type ManagementBaseFeeCap = ManagementBaseFeeCap;
+    type ExternalReferralRate = ExternalReferralRate;
+    type ReferralChainDepth = ReferralChainDepth;
+    type ExternalReferralRateCap = ExternalReferralRateCap;
+    type ReferralChainDepthCap = ReferralChainDepthCap;
+    type MaxPortfolioLegs = MaxPortfolioLegs;
+    type MaxAllowListLength = MaxAllowListLength;
+    type DpoCreationDeposit = DpoCreationDeposit;
+    type MaxNameLength = MaxNameLength;
+    type DpoBaseFeeChangeDelay = DpoBaseFeeChangeDelay;
     type EngineerOrigin = EnsureSignedBy<Alice, AccountId>;
     type WeightInfo = weights::SubstrateWeight<Test>;
 }
@@ -166,6 +259,7 @@ construct_runtime!(
 pub struct ExtBuilder {
     token_endowed_accounts: Vec<(AccountId, CurrencyId, Balance)>,
     balance_endowed_accounts: Vec<(AccountId, Balance)>,
+    bullet_train_genesis: pallet_bullet_train::GenesisConfig<Test>,
 }
 
 pub const DEFAULT_BALANCE_SYSTEM: Balance = 1_000_000_000;
@@ -202,11 +296,17 @@ impl Default for ExtBuilder {
                 (BulletTrain::eng_account_id(), DEFAULT_BALANCE_SYSTEM),
                 (BulletTrain::account_id(), DEFAULT_BALANCE_SYSTEM),
             ],
+            bullet_train_genesis: Default::default(),
         }
     }
 }
 
 impl ExtBuilder {
+    pub fn bullet_train_genesis(mut self, config: pallet_bullet_train::GenesisConfig<Test>) -> Self {
+        self.bullet_train_genesis = config;
+        self
+    }
+
     pub fn build(self) -> sp_io::TestExternalities {
         let mut t = frame_system::GenesisConfig::default()
             .build_storage::<Test>()
@@ -224,6 +324,10 @@ impl ExtBuilder {
         .assimilate_storage(&mut t)
         .unwrap();
 
+        self.bullet_train_genesis
+            .assimilate_storage(&mut t)
+            .unwrap();
+
         t.into()
     }
 }
@@ -233,3 +337,11 @@ pub fn run_to_block(n: u64) {
         System::set_block_number(System::block_number() + 1);
     }
 }
+
+/// also runs the block hooks of the pallet
+pub fn run_to_block_with_hooks(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        BulletTrain::on_initialize(System::block_number());
+    }
+}
@@ -1,12 +1,14 @@
 use crate::{
-    mock::*, Buyer, DpoMemberInfo, DpoState, Error, MilestoneRewardInfo, Referrer, Target,
-    TargetCompare, TravelCabinInfo,
+    mock::*, Buyer, DpoAccessPolicy, DpoBaseFeeChangeInfo, DpoMemberInfo, DpoReferralPolicy, DpoRewardPoolInfo, DpoState,
+    Error, MilestoneRewardInfo, MilestoneRewardPayoutInfo, MilestoneRewardReleaseInfo,
+    PortfolioLegState, Referrer, Target, TargetCompare, TravelCabinInfo, YieldSchedule,
 };
 use frame_support::{assert_noop, assert_ok};
 use frame_system::{EventRecord, Phase};
 use orml_traits::MultiCurrency;
-use pallet_bullet_train_primitives::{DpoIndex, TravelCabinInventoryIndex};
-use sp_runtime::FixedPointNumber;
+use pallet_bullet_train_primitives::{DpoIndex, InvariantViolation, TravelCabinInventoryIndex};
+use sp_core::{sr25519, Pair, H256, H512};
+use sp_runtime::{FixedPointNumber, Permill};
 
 fn make_default_travel_cabin(
     token_id: crate::CurrencyId,
@@ -27,6 +29,7 @@ fn make_default_travel_cabin(
         1000 * mul.2,  //yield
         10 * mul.3,    //maturity
         1 * mul.4,     //stockpile
+        YieldSchedule::Linear,
     ));
 }
 
@@ -46,7 +49,8 @@ fn make_default_dpo(
         50,       //base fee, per thousand
         800,      //direct referral rate, per thousand
         end,      //end block
-        referrer  //referrer
+        referrer, //referrer
+        DpoAccessPolicy::Public
     ));
 }
 
@@ -73,6 +77,16 @@ fn fill_dpo_with_dummy_accounts(dpo_idx: DpoIndex, percent: u128) -> () {
     }
 }
 
+/// claim the released yield and bonus for every member of the dpo
+fn claim_all_dpo_rewards(dpo_idx: DpoIndex) -> () {
+    for member_idx in 0..BulletTrain::dpo_member_count(dpo_idx) {
+        let member = BulletTrain::dpo_member_by_index(dpo_idx, member_idx).unwrap();
+        if let Err(e) = BulletTrain::claim_dpo_rewards(Origin::signed(ALICE), dpo_idx, member) {
+            assert_eq!(e.error, Error::<Test>::ZeroBalanceToWithdraw.into());
+        }
+    }
+}
+
 fn dpo_buy_target(who: AccountId, dpo_idx: DpoIndex, percent: u128) -> () {
     let dpo = BulletTrain::dpos(dpo_idx).unwrap();
     let buy_amount = dpo.target_amount * percent / 100;
@@ -121,6 +135,7 @@ fn create_travel_cabin_works() {
                 bonus_total: 1000,
                 yield_total: 1000,
                 maturity: 10,
+                yield_schedule: YieldSchedule::Linear,
             })
         );
         //check inventory
@@ -158,6 +173,62 @@ fn create_travel_cabin_works() {
     });
 }
 
+#[test]
+fn genesis_config_creates_cabins_milestone_rewards_and_dpos() {
+    ExtBuilder::default()
+        .bullet_train_genesis(crate::GenesisConfig {
+            engineer_endowment: vec![(WUSD, 10_000)],
+            initial_travel_cabins: vec![(WUSD, b"genesis cabin".to_vec(), 1000, 10, 100, 10, 2, YieldSchedule::Linear)],
+            initial_milestone_rewards: vec![(WUSD, 1000, 100)],
+            initial_dpos: vec![(ALICE, b"genesis dpo".to_vec(), 0, 100, 10, 10)],
+        })
+        .build()
+        .execute_with(|| {
+            assert_eq!(BulletTrain::travel_cabin_count(), 1);
+            assert_eq!(
+                BulletTrain::travel_cabins(0),
+                Some(TravelCabinInfo {
+                    name: b"genesis cabin".to_vec(),
+                    creator: BulletTrain::eng_account_id(),
+                    token_id: WUSD,
+                    index: 0,
+                    deposit_amount: 1000,
+                    bonus_total: 10,
+                    yield_total: 100,
+                    maturity: 10,
+                    yield_schedule: YieldSchedule::Linear,
+                })
+            );
+            assert_eq!(BulletTrain::travel_cabin_inventory(0), Some((0, 2)));
+            assert_eq!(
+                BulletTrain::milestone_reward(WUSD),
+                Some(MilestoneRewardInfo {
+                    token_id: WUSD,
+                    deposited: 0,
+                    milestones: vec![(1000, 100)]
+                })
+            );
+            // rewards of the two cabins and the milestone reward funded by the engineer account
+            assert_eq!(
+                Currencies::free_balance(WUSD, &BulletTrain::eng_account_id()),
+                10_000 - 110 * 2 - 100
+            );
+
+            assert_eq!(BulletTrain::dpo_count(), 1);
+            let dpo = BulletTrain::dpos(0).unwrap();
+            assert_eq!(dpo.manager, ALICE);
+            assert_eq!(dpo.target, Target::TravelCabin(0));
+            assert_eq!(dpo.state, DpoState::CREATED);
+            assert_eq!(dpo.total_fund, 100);
+            assert_eq!(Currencies::free_balance(WUSD, &ALICE), 1_000_000 - 100);
+
+            // the seeded dpo is bought like any other
+            run_to_block(1);
+            assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(BOB), 0, 300, None));
+            assert_eq!(BulletTrain::dpos(0).unwrap().total_fund, 400);
+        });
+}
+
 #[test]
 fn issue_additional_travel_cabin_works() {
     ExtBuilder::default().build().execute_with(|| {
@@ -381,7 +452,7 @@ fn milestone_rewards_released_correctly() {
 
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_bullet_train(crate::Event::MilestoneRewardReleased(
-                ALICE, BOLT, 10000, 30,
+                ALICE, BOLT, 0, 10000, 30, 30,
             ))));
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_balances(BalancesEvent::Transfer(
@@ -404,13 +475,16 @@ fn milestone_rewards_released_correctly() {
             Origin::signed(CAROL), //buyer
             0                      //travel cabin index
         ));
+        //the cabins sold are counted on purchase, paid by the release
+        assert_eq!(BulletTrain::travel_cabins_sold(BOLT), 2);
+        assert_eq!(BulletTrain::travel_cabins_sold(WUSD), 0);
         assert_ok!(BulletTrain::release_milestone_reward(
             Origin::signed(ALICE),
             BOLT
         ));
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_bullet_train(crate::Event::MilestoneRewardReleased(
-                ALICE, BOLT, 20000, 30,
+                ALICE, BOLT, 1, 20000, 30, 30,
             ))));
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_balances(BalancesEvent::Transfer(
@@ -444,7 +518,7 @@ fn milestone_rewards_released_correctly() {
         ));
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_bullet_train(crate::Event::MilestoneRewardReleased(
-                ALICE, BOLT, 30000, 30,
+                ALICE, BOLT, 2, 30000, 30, 30,
             ))));
         assert!(System::events().iter().any(|a| a.event
             == Event::pallet_balances(BalancesEvent::Transfer(
@@ -468,6 +542,161 @@ fn milestone_rewards_released_correctly() {
     })
 }
 
+#[test]
+fn milestone_reward_release_records_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        make_default_travel_cabin(BOLT, (1, 1, 1, 1, 10));
+        assert_ok!(BulletTrain::create_milestone_reward(
+            Origin::signed(ALICE),
+            BOLT,
+            20000,
+            100
+        ));
+        assert_ok!(BulletTrain::passenger_buy_travel_cabin(
+            Origin::signed(BOB),
+            0
+        ));
+        make_default_dpo(DYLAN, Target::TravelCabin(0), 10, 10, None);
+        fill_dpo_with_dummy_accounts(0, 100);
+        dpo_buy_target(DYLAN, 0, 100);
+
+        run_to_block(5);
+        assert_ok!(BulletTrain::release_milestone_reward(
+            Origin::signed(ALICE),
+            BOLT
+        ));
+        assert_eq!(BulletTrain::milestone_reward_release_count(BOLT), 1);
+        assert_eq!(
+            BulletTrain::milestone_reward_releases(BOLT, 0),
+            Some(MilestoneRewardReleaseInfo {
+                milestone: 20000,
+                reward: 100,
+                deposited: 20000,
+                total_paid: 100,
+                recipients: 2,
+                released_by: ALICE,
+                released_blk: 5,
+            })
+        );
+        assert_eq!(
+            BulletTrain::milestone_reward_payouts((BOLT, 0), (0, 0)),
+            Some(MilestoneRewardPayoutInfo {
+                buyer: Buyer::Passenger(BOB),
+                amount: 50,
+            })
+        );
+        assert_eq!(
+            BulletTrain::milestone_reward_payouts((BOLT, 0), (0, 1)),
+            Some(MilestoneRewardPayoutInfo {
+                buyer: Buyer::Dpo(0),
+                amount: 50,
+            })
+        );
+
+        // rpc
+        assert_eq!(
+            BulletTrain::get_milestone_reward_releases(BOLT),
+            vec![(0, 5, 20000, 100, 100)]
+        );
+        assert_eq!(
+            BulletTrain::get_milestone_reward_payouts_of_account(&BOB),
+            vec![(BOLT, 0, 0, 0, 50)]
+        );
+        assert_eq!(BulletTrain::get_milestone_reward_payouts_of_account(&DYLAN), vec![]);
+    })
+}
+
+#[test]
+fn milestone_reward_release_is_permissionless_and_chunked() {
+    ExtBuilder::default().build().execute_with(|| {
+        // 10% of the reward goes to the callers, 2 cabins paid per call
+        set_milestone_reward_release_config(Permill::from_percent(10), 2);
+        run_to_block(1);
+        make_default_travel_cabin(BOLT, (1, 1, 1, 1, 10));
+        assert_ok!(BulletTrain::create_milestone_reward(
+            Origin::signed(ALICE),
+            BOLT,
+            50000,
+            1000
+        ));
+        for buyer in BOB..FRED {
+            assert_ok!(BulletTrain::passenger_buy_travel_cabin(
+                Origin::signed(buyer),
+                0
+            ));
+        }
+        // milestone not reached yet
+        assert_noop!(
+            BulletTrain::release_milestone_reward(Origin::signed(HUGH), BOLT),
+            Error::<Test>::NoMilestoneRewardWaiting
+        );
+        assert_ok!(BulletTrain::passenger_buy_travel_cabin(
+            Origin::signed(FRED),
+            0
+        ));
+
+        // anyone can release, 2 of 5 cabins paid
+        assert_ok!(BulletTrain::release_milestone_reward(
+            Origin::signed(HUGH),
+            BOLT
+        ));
+        assert!(BulletTrain::milestone_reward_release_in_progress(BOLT).is_some());
+        assert_eq!(BulletTrain::milestone_reward(BOLT).unwrap().milestones, vec![]);
+        assert_eq!(Balances::free_balance(HUGH), DEFAULT_BALANCE_USER + 40);
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER - 10000 + 180);
+        assert_eq!(Balances::free_balance(DYLAN), DEFAULT_BALANCE_USER - 10000);
+        let expected_event = Event::pallet_bullet_train(
+            crate::Event::MilestoneRewardReleaseProgressed(HUGH, BOLT, 0, 2, 40),
+        );
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+
+        // cabins bought after the release started are not paid
+        run_to_block(2);
+        assert_ok!(BulletTrain::passenger_buy_travel_cabin(
+            Origin::signed(GREG),
+            0
+        ));
+        assert_ok!(BulletTrain::release_milestone_reward(
+            Origin::signed(IVAN),
+            BOLT
+        ));
+        assert_eq!(Balances::free_balance(IVAN), DEFAULT_BALANCE_USER + 40);
+        assert_ok!(BulletTrain::release_milestone_reward(
+            Origin::signed(JILL),
+            BOLT
+        ));
+        // the last caller takes the rest of the incentive
+        assert_eq!(Balances::free_balance(JILL), DEFAULT_BALANCE_USER + 20);
+        assert_eq!(Balances::free_balance(FRED), DEFAULT_BALANCE_USER - 10000 + 180);
+        assert_eq!(Balances::free_balance(GREG), DEFAULT_BALANCE_USER - 10000);
+        assert!(BulletTrain::milestone_reward_release_in_progress(BOLT).is_none());
+        assert_eq!(BulletTrain::milestone_reward_payouts((BOLT, 0), (0, 5)), None);
+
+        let expected_event = Event::pallet_bullet_train(crate::Event::MilestoneRewardReleased(
+            JILL, BOLT, 0, 50000, 1000, 900,
+        ));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(
+            BulletTrain::milestone_reward_releases(BOLT, 0),
+            Some(MilestoneRewardReleaseInfo {
+                milestone: 50000,
+                reward: 1000,
+                deposited: 50000,
+                total_paid: 900,
+                recipients: 5,
+                released_by: JILL,
+                released_blk: 2,
+            })
+        );
+
+        assert_noop!(
+            BulletTrain::release_milestone_reward(Origin::signed(HUGH), BOLT),
+            Error::<Test>::NoMilestoneRewardWaiting
+        );
+    })
+}
+
 #[test]
 fn dpo_create_targeting_travel_cabin_works() {
     ExtBuilder::default().build().execute_with(|| {
@@ -482,7 +711,8 @@ fn dpo_create_targeting_travel_cabin_works() {
                 50,
                 800,
                 10,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::InvalidIndex
         );
@@ -496,7 +726,8 @@ fn dpo_create_targeting_travel_cabin_works() {
                 50,
                 800,
                 10,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::ExceededShareCap
         );
@@ -510,7 +741,8 @@ fn dpo_create_targeting_travel_cabin_works() {
                 51,  // >5%
                 800,
                 10,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::ExceededRateCap
         );
@@ -524,7 +756,8 @@ fn dpo_create_targeting_travel_cabin_works() {
                 50,
                 1001, // >100%
                 10,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::ExceededRateCap
         );
@@ -539,7 +772,8 @@ fn dpo_create_targeting_travel_cabin_works() {
                 50,
                 800,
                 0,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::InvalidEndTime
         );
@@ -606,7 +840,8 @@ fn dpo_create_targeting_dpo_works() {
                 50,
                 800,
                 9,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::ExceededShareCap
         );
@@ -620,7 +855,8 @@ fn dpo_create_targeting_dpo_works() {
                 50,
                 800,
                 9,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::PurchaseAtLeastThreePercentForDpo
         );
@@ -634,7 +870,8 @@ fn dpo_create_targeting_dpo_works() {
                 50,
                 800,
                 9,
-                None
+                None,
+                DpoAccessPolicy::Public
             ),
             Error::<Test>::TargetValueTooSmall
         );
@@ -1076,6 +1313,7 @@ fn dpo_buy_dpo_share_partially_works() {
             Origin::signed(ALICE),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::RUNNING);
         assert_eq!(BulletTrain::dpos(1).unwrap().blk_of_dpo_filled, None);
         assert_eq!(BulletTrain::dpos(1).unwrap().target_amount, 10000); // not 20000
@@ -1146,11 +1384,13 @@ fn dpo_state_transitions_correctly() {
         //dpo2 bonus released from dpo0
         assert_eq!(BulletTrain::dpos(2).unwrap().state, DpoState::ACTIVE);
         assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(BOB), 0));
+        claim_all_dpo_rewards(0);
         assert_eq!(BulletTrain::dpos(2).unwrap().state, DpoState::RUNNING);
 
         //dpo4 bonus released from dpo3
         assert_eq!(BulletTrain::dpos(4).unwrap().state, DpoState::ACTIVE);
         assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(BOB), 3));
+        claim_all_dpo_rewards(3);
         assert_eq!(BulletTrain::dpos(4).unwrap().state, DpoState::RUNNING);
 
         //CREATED -> FAILED
@@ -1321,6 +1561,104 @@ fn passenger_withdraw_yield_and_fare_works() {
     });
 }
 
+#[test]
+fn create_travel_cabin_with_invalid_yield_schedule_fails() {
+    ExtBuilder::default().build().execute_with(|| {
+        for yield_schedule in vec![
+            YieldSchedule::CliffLinear(11),
+            YieldSchedule::Periodic(0),
+            YieldSchedule::Periodic(11),
+        ] {
+            assert_noop!(
+                BulletTrain::create_travel_cabin(
+                    Origin::signed(ALICE),
+                    BOLT,
+                    String::from("test").into_bytes(),
+                    10000,
+                    1000,
+                    1000,
+                    10,
+                    1,
+                    yield_schedule,
+                ),
+                Error::<Test>::InvalidYieldSchedule
+            );
+        }
+    });
+}
+
+#[test]
+fn passenger_withdraw_yield_by_schedule_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        // cabin 0: cliff at block 4, cabin 1: coupon every 4 blocks, cabin 2: back-loaded
+        for yield_schedule in vec![
+            YieldSchedule::CliffLinear(4),
+            YieldSchedule::Periodic(4),
+            YieldSchedule::BackLoaded,
+        ] {
+            assert_ok!(BulletTrain::create_travel_cabin(
+                Origin::signed(ALICE),
+                BOLT,
+                String::from("test").into_bytes(),
+                10000,
+                0,
+                1000,
+                10,
+                1,
+                yield_schedule,
+            ));
+        }
+        for idx in 0..3 {
+            assert_ok!(BulletTrain::passenger_buy_travel_cabin(
+                Origin::signed(BOB),
+                idx
+            ));
+        }
+
+        // nothing accrued before the cliff or the first coupon
+        run_to_block(3);
+        assert_noop!(
+            BulletTrain::withdraw_yield_from_travel_cabin(Origin::signed(BOB), 0, 0),
+            Error::<Test>::NoYieldToRelease
+        );
+        assert_noop!(
+            BulletTrain::withdraw_yield_from_travel_cabin(Origin::signed(BOB), 1, 0),
+            Error::<Test>::NoYieldToRelease
+        );
+
+        run_to_block(5);
+        // cliff passed, catch up linearly
+        assert_eq!(BulletTrain::get_travel_cabin_withdrawable_yield(0, 0), 500);
+        // 1 of 3 coupons
+        assert_eq!(BulletTrain::get_travel_cabin_withdrawable_yield(1, 0), 333);
+        // (5/10)^2
+        assert_eq!(BulletTrain::get_travel_cabin_withdrawable_yield(2, 0), 250);
+        for idx in 0..3 {
+            assert_ok!(BulletTrain::withdraw_yield_from_travel_cabin(
+                Origin::signed(BOB),
+                idx,
+                0
+            ));
+        }
+        assert_eq!(
+            Balances::free_balance(BOB),
+            DEFAULT_BALANCE_USER - 30000 + 500 + 333 + 250
+        );
+
+        // everything is released on maturity
+        run_to_block(10);
+        for idx in 0..3 {
+            assert_ok!(BulletTrain::withdraw_yield_from_travel_cabin(
+                Origin::signed(BOB),
+                idx,
+                0
+            ));
+            assert_eq!(BulletTrain::travel_cabin_buyer(idx, 0).unwrap().yield_withdrawn, 1000);
+            assert_eq!(BulletTrain::get_travel_cabin_withdrawable_yield(idx, 0), 0);
+        }
+    });
+}
+
 #[test]
 fn dpo_release_yield_and_fare_works() {
     //this tests the matrix of manager/member/non-member + over grace period/in grace period for yield distribution
@@ -1353,6 +1691,7 @@ fn dpo_release_yield_and_fare_works() {
         assert_eq!(BulletTrain::dpos(0).unwrap().total_yield_received, 10000);
         assert_eq!(BulletTrain::dpos(0).unwrap().vault_yield, 10000);
         assert_ok!(BulletTrain::release_yield_from_dpo(Origin::signed(BOB), 0)); //10000
+        claim_all_dpo_rewards(0);
         assert!(System::events()
             .iter()
             .any(|a| a.event == Event::pallet_bullet_train(crate::Event::YieldReleased(BOB, 0))));
@@ -1383,6 +1722,7 @@ fn dpo_release_yield_and_fare_works() {
             Origin::signed(CAROL),
             0
         )); //10000
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * 2
@@ -1408,6 +1748,7 @@ fn dpo_release_yield_and_fare_works() {
             Origin::signed(ALICE),
             0
         )); //10000
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * 3
@@ -1430,6 +1771,7 @@ fn dpo_release_yield_and_fare_works() {
         //grace period over
         run_to_block(15);
         assert_ok!(BulletTrain::release_yield_from_dpo(Origin::signed(BOB), 0)); //10000
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * 4
@@ -1455,6 +1797,7 @@ fn dpo_release_yield_and_fare_works() {
             Origin::signed(CAROL),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * 4 + (500 + 475) * 11
@@ -1480,6 +1823,7 @@ fn dpo_release_yield_and_fare_works() {
             Origin::signed(DYLAN),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * 4 + (500 + 475) * (11 + 11)
@@ -1503,6 +1847,7 @@ fn dpo_release_yield_and_fare_works() {
             Origin::signed(ALICE),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(
             Balances::free_balance(BOB),
             DEFAULT_BALANCE_USER - 50000 + (1000 + 450) * (4 + 74) + (500 + 475) * (11 + 11)
@@ -1758,6 +2103,7 @@ fn dpo_release_to_nested_dpo_works() {
             Origin::signed(ALICE),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(BulletTrain::dpos(0).unwrap().vault_bonus, 0);
         let mut bonus_exp = 160000;
         for i in 1..6 {
@@ -1770,6 +2116,7 @@ fn dpo_release_to_nested_dpo_works() {
                 Origin::signed(ALICE),
                 i
             ));
+            claim_all_dpo_rewards(i);
             bonus_exp = bonus_exp / 5;
         }
     });
@@ -1801,6 +2148,7 @@ fn dpo_release_bonus_by_referral_works() {
         assert_eq!(BulletTrain::dpos(0).unwrap().total_bonus_received, 10000);
         assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER - 10000);
         assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(BOB), 0));
+        claim_all_dpo_rewards(0);
         assert!(System::events()
             .iter()
             .any(|a| a.event == Event::pallet_bullet_train(crate::Event::BonusReleased(BOB, 0))));
@@ -1815,6 +2163,7 @@ fn dpo_release_bonus_by_referral_works() {
             Origin::signed(CAROL),
             1
         ));
+        claim_all_dpo_rewards(1);
         //1000 * 0.7 for manager itself + 3000 from the first member, and 3000 * 20 % from the 2nd
         assert_eq!(
             Balances::free_balance(CAROL),
@@ -1828,6 +2177,7 @@ fn dpo_release_bonus_by_referral_works() {
             Origin::signed(CAROL),
             2
         ));
+        claim_all_dpo_rewards(2);
         //1000 * 0.7 for manager itself + 3000 from the first member, and 3000 * 20 % from the 2nd
         assert_eq!(
             Balances::free_balance(DYLAN),
@@ -1838,93 +2188,458 @@ fn dpo_release_bonus_by_referral_works() {
     });
 }
 
-/// this test case also test the correctness of the referral structure
 #[test]
-fn dpo_release_bonus_with_0_direct_referral_rate_works() {
+fn dpo_release_fare_in_chunks_works() {
     ExtBuilder::default().build().execute_with(|| {
-        //alice creates Cabins
-        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
-        assert_eq!(Balances::free_balance(ALICE), 1000000);
-        //alice creates dpo 0 and take 15% spending 15,000, referred by adam
-        //direct referral rate 0
-        assert_ok!(BulletTrain::create_dpo(
-            Origin::signed(ALICE),
-            String::from("test").into_bytes(),
-            Target::TravelCabin(0),
-            15000, // 15%
-            50,
-            0,
-            10,
-            Some(ADAM)
-        ));
-        assert_eq!(Balances::free_balance(ALICE), 1000000 - 15000);
-        //BCDE taking 10% each, spending 10,000
-        for i in BOB..FRED {
-            assert_ok!(BulletTrain::passenger_buy_dpo_share(
-                Origin::signed(i),
-                0,
-                10000, // 10%
-                None
-            ));
-        }
-        //F taking 15%, spending 15,000
-        assert_ok!(BulletTrain::passenger_buy_dpo_share(
-            Origin::signed(FRED),
-            0,
-            15000, // 15%
-            None
-        ));
-        // JILL takes 30% via DPO 1, taking 15% of DPO1, spending 30000 * 15% = 4500
-        make_default_dpo(JILL, Target::Dpo(0, 30000), 4500, 9, None);
-        //BCEDFGH taking 10 each, spending 3000
-        for i in BOB..IVAN {
-            assert_ok!(BulletTrain::passenger_buy_dpo_share(
-                Origin::signed(i),
-                1,
-                3000, // 10%
-                None
-            ));
-        }
-        //I taking 15%, spending 4500
-        assert_ok!(BulletTrain::passenger_buy_dpo_share(
-            Origin::signed(IVAN),
-            1,
-            4500, // 15%
-            None
-        ));
+        set_dpo_payout_members_per_call(2);
+        //deposit: 100000 bonus: 10000
+        make_default_travel_cabin(BOLT, (10, 10, 10, 1, 1));
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 10, None);
+        fill_dpo_with_dummy_accounts(0, 100); //adds 3 members 1000 to 1002
+        dpo_buy_target(BOB, 0, 100);
+        assert_eq!(BulletTrain::dpo_member_count(0), 4);
+        assert_eq!(BulletTrain::dpo_member_by_index(0, 0), Some(Buyer::Passenger(BOB)));
 
-        dpo_buy_target(JILL, 1, 100);
-        dpo_buy_target(ALICE, 0, 100);
-        run_to_block(1);
-        let dpo0 = BulletTrain::dpos(0).unwrap();
-        assert_eq!(dpo0.vault_bonus, 1000);
-        assert_eq!(dpo0.vault_yield, 0);
+        assert_noop!(
+            BulletTrain::continue_dpo_payout(Origin::signed(CAROL), 0),
+            Error::<Test>::NoDpoPayoutInProgress
+        );
 
-        assert_ok!(BulletTrain::release_bonus_from_dpo(
-            Origin::signed(ALICE),
+        run_to_block(20);
+        assert_ok!(BulletTrain::withdraw_fare_from_travel_cabin(
+            Origin::signed(BOB),
+            0,
             0
         ));
-        let expected_event = Event::pallet_bullet_train(crate::Event::BonusReleased(ALICE, 0));
+
+        // the first 2 of the 4 members are paid
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 0));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoPayoutProgressed(BOB, 0, 2));
         assert!(System::events().iter().any(|a| a.event == expected_event));
-        assert_eq!(BulletTrain::dpos(1).unwrap().vault_bonus, 255);
-        assert_eq!(BulletTrain::dpos(1).unwrap().total_bonus_received, 255);
-        assert_eq!(BulletTrain::dpos(0).unwrap().vault_bonus, 0);
+        assert!(BulletTrain::dpo_payout_in_progress(0).is_some());
+        assert_eq!(Balances::free_balance(1000), 30000);
+        assert_eq!(Balances::free_balance(1001), 0);
 
-        assert_eq!(
-            BulletTrain::dpo_members(0, Buyer::Dpo(1)).unwrap(),
-            DpoMemberInfo {
-                buyer: Buyer::Dpo(1),
-                share: 30000, // 30%
-                referrer: Referrer::MemberOfDpo(Buyer::Passenger(FRED)),
-            }
+        // no other release until the payout is finished
+        assert_noop!(
+            BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 0),
+            Error::<Test>::DpoPayoutInProgress
         );
 
-        //referral chain of dpo0 Alice <- Bob <- Carol <- Dylan <- Elsa <- Fred -< DPO1(JILL)/
-        assert_eq!(
-            Balances::free_balance(ALICE),
-            1000000 - 15000 + 105 + 100 + 100
-        ); // 150 * 70% (30% to ADAM as external referrer)
-           // + 100 from bob + 100 from Carol
+        // anyone can pay the rest
+        assert_ok!(BulletTrain::continue_dpo_payout(Origin::signed(CAROL), 0));
+        let expected_event = Event::pallet_bullet_train(crate::Event::WithdrewFareFromDpo(CAROL, 0));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpo_payout_in_progress(0), None);
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.vault_withdraw, 0);
+        assert!(dpo.fare_withdrawn);
+        //same as released in one call
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER);
+        for i in 1000..1003 {
+            assert_eq!(Balances::free_balance(i), 30000);
+        }
+    });
+}
+
+#[test]
+fn dpo_expiry_fails_dpo_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 10, None);
+        assert_eq!(BulletTrain::dpo_expiry_queue(11), vec![0]);
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None));
+
+        run_to_block_with_hooks(10);
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::CREATED);
+
+        run_to_block_with_hooks(11);
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::FAILED);
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoFailed(0));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpo_expiry_queue(11), vec![]);
+        assert_noop!(
+            BulletTrain::passenger_buy_dpo_share(Origin::signed(DYLAN), 0, 10000, None),
+            Error::<Test>::DpoWrongState
+        );
+
+        // no refund by default, the members withdraw by themselves
+        assert!(!BulletTrain::dpos(0).unwrap().fare_withdrawn);
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(CAROL), 0));
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER);
+        assert_eq!(Balances::free_balance(CAROL), DEFAULT_BALANCE_USER);
+    });
+}
+
+#[test]
+fn dpo_expiry_refunds_in_bounded_blocks_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        set_dpo_expiry_config(1, true);
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 10, None);
+        make_default_dpo(CAROL, Target::TravelCabin(0), 10000, 10, None);
+        make_default_dpo(DYLAN, Target::TravelCabin(0), 10000, 10, None);
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(ELSA), 0, 10000, None));
+        // dpo 2 is filled in time
+        fill_dpo_with_dummy_accounts(2, 100);
+        assert_eq!(BulletTrain::dpos(2).unwrap().state, DpoState::ACTIVE);
+        assert_eq!(BulletTrain::dpo_expiry_queue(11), vec![0, 1, 2]);
+
+        // one dpo per block, the rest are moved to the next block
+        run_to_block_with_hooks(11);
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.state, DpoState::FAILED);
+        assert!(dpo.fare_withdrawn);
+        let expected_event = Event::pallet_bullet_train(crate::Event::WithdrewFareFromDpo(
+            BulletTrain::account_id(),
+            0,
+        ));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER);
+        assert_eq!(Balances::free_balance(ELSA), DEFAULT_BALANCE_USER);
+        assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::CREATED);
+        assert_eq!(BulletTrain::dpo_expiry_queue(12), vec![1, 2]);
+
+        run_to_block_with_hooks(12);
+        assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::FAILED);
+        assert_eq!(Balances::free_balance(CAROL), DEFAULT_BALANCE_USER);
+        assert_eq!(BulletTrain::dpo_expiry_queue(13), vec![2]);
+
+        // a filled dpo is left as it is
+        run_to_block_with_hooks(13);
+        assert_eq!(BulletTrain::dpos(2).unwrap().state, DpoState::ACTIVE);
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoFailed(2));
+        assert!(!System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpo_expiry_queue(13), vec![]);
+    });
+}
+
+#[test]
+fn dpo_creation_deposit_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        set_dpo_creation_deposit(100);
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 2));
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 10, None);
+        make_default_dpo(CAROL, Target::TravelCabin(0), 10000, 10, None);
+        assert_eq!(Balances::reserved_balance(BOB), 100);
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER - 10000 - 100);
+        assert_eq!(BulletTrain::dpo_creation_deposits(0), Some((BOB, 100)));
+
+        // the deposit of a completed dpo goes back to the manager
+        fill_dpo_with_dummy_accounts(0, 100);
+        dpo_buy_target(BOB, 0, 100);
+        run_to_block(11);
+        assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::CREATED);
+        assert_eq!(Balances::reserved_balance(CAROL), 100);
+        run_to_block(30);
+        assert_ok!(BulletTrain::withdraw_fare_from_travel_cabin(Origin::signed(ALICE), 0, 0));
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::COMPLETED);
+        assert_eq!(Balances::reserved_balance(BOB), 0);
+        assert_eq!(BulletTrain::dpo_creation_deposits(0), None);
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoCreationDepositReturned(BOB, 0, 100));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+
+        // so does the deposit of a failed one
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(CAROL), 1));
+        assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::FAILED);
+        assert_eq!(Balances::reserved_balance(CAROL), 0);
+        assert_eq!(Balances::free_balance(CAROL), DEFAULT_BALANCE_USER);
+    });
+}
+
+#[test]
+fn name_too_long_fails() {
+    ExtBuilder::default().build().execute_with(|| {
+        assert_noop!(
+            BulletTrain::create_travel_cabin(
+                Origin::signed(ALICE),
+                BOLT,
+                vec![b'a'; 17],
+                10000,
+                1000,
+                1000,
+                10,
+                1,
+                YieldSchedule::Linear,
+            ),
+            Error::<Test>::NameTooLong
+        );
+        make_default_travel_cabin(BOLT, (1, 1, 1, 1, 1));
+        assert_noop!(
+            BulletTrain::create_dpo(
+                Origin::signed(BOB),
+                vec![b'a'; 17],
+                Target::TravelCabin(0),
+                1000,
+                50,
+                800,
+                10,
+                None,
+                DpoAccessPolicy::Public
+            ),
+            Error::<Test>::NameTooLong
+        );
+    });
+}
+
+#[test]
+fn set_dpo_base_fee_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block_with_hooks(1);
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+        // manager takes 10%, fee = 100 + 50
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 20, None);
+        assert_eq!(BulletTrain::dpos(0).unwrap().fee, 150);
+
+        assert_noop!(
+            BulletTrain::set_dpo_base_fee(Origin::signed(BOB), 0, 20),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            BulletTrain::set_dpo_base_fee(Origin::signed(ALICE), 0, 51),
+            Error::<Test>::ExceededRateCap
+        );
+        assert_noop!(
+            BulletTrain::set_dpo_base_fee(Origin::signed(ALICE), 0, 50),
+            Error::<Test>::DpoBaseFeeUnchanged
+        );
+
+        // a second change replaces the pending one
+        assert_ok!(BulletTrain::set_dpo_base_fee(Origin::signed(ALICE), 0, 20));
+        run_to_block_with_hooks(2);
+        assert_ok!(BulletTrain::set_dpo_base_fee(Origin::signed(ALICE), 0, 10));
+        assert_eq!(BulletTrain::pending_dpo_base_fees(0), Some((10, 2, 7)));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoBaseFeeChangeScheduled(0, 10, 7));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+
+        run_to_block_with_hooks(6);
+        assert_eq!(BulletTrain::dpos(0).unwrap().base_fee, 50);
+        assert_eq!(BulletTrain::dpo_base_fee_change_count(0), 0);
+
+        run_to_block_with_hooks(7);
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.base_fee, 10);
+        assert_eq!(dpo.fee, 110);
+        assert_eq!(BulletTrain::pending_dpo_base_fees(0), None);
+        assert_eq!(BulletTrain::dpo_base_fee_change_count(0), 1);
+        assert_eq!(
+            BulletTrain::dpo_base_fee_changes(0, 0),
+            Some(DpoBaseFeeChangeInfo {
+                old_base_fee: 50,
+                new_base_fee: 10,
+                old_fee: 150,
+                new_fee: 110,
+                scheduled_blk: 2,
+                changed_blk: 7,
+            })
+        );
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoBaseFeeChanged(0, 50, 10, 110));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+
+        // not for a failed dpo
+        run_to_block_with_hooks(21);
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::FAILED);
+        assert_noop!(
+            BulletTrain::set_dpo_base_fee(Origin::signed(ALICE), 0, 30),
+            Error::<Test>::DpoWrongState
+        );
+    });
+}
+
+#[test]
+fn claim_dpo_rewards_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        //deposit: 100000 bonus: 10000
+        make_default_travel_cabin(BOLT, (10, 10, 10, 1, 1));
+        make_default_dpo(BOB, Target::TravelCabin(0), 10000, 10, None);
+        fill_dpo_with_dummy_accounts(0, 100); //adds 3 members 1000 to 1002
+        dpo_buy_target(BOB, 0, 100);
+
+        assert_noop!(
+            BulletTrain::claim_dpo_rewards(Origin::signed(CAROL), 0, Buyer::Passenger(1000)),
+            Error::<Test>::ZeroBalanceToWithdraw
+        );
+
+        //the bonus is booked, nothing is paid yet
+        assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(BOB), 0));
+        assert_eq!(BulletTrain::dpos(0).unwrap().vault_bonus, 0);
+        assert_eq!(
+            BulletTrain::dpo_reward_pools(0),
+            DpoRewardPoolInfo {
+                total_yield: 0,
+                total_yield_withdrawn: 0,
+                total_bonus: 10000,
+                total_bonus_withdrawn: 0,
+                bonus_remainder: 0,
+            }
+        );
+        assert_eq!(Balances::free_balance(1000), 0);
+
+        //anyone can claim for a member, the bonus of 1000 goes to its referrer, the manager
+        assert_ok!(BulletTrain::claim_dpo_rewards(Origin::signed(CAROL), 0, Buyer::Passenger(1000)));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoRewardsClaimed(
+            CAROL,
+            0,
+            Buyer::Passenger(1000),
+            0,
+            3000,
+        ));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpo_member_rewards_withdrawn(0, Buyer::Passenger(1000)), (0, 3000));
+        assert_eq!(BulletTrain::dpo_reward_pools(0).total_bonus_withdrawn, 3000);
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER - 10000 + 3000);
+
+        //nothing more to claim until the next release
+        assert_noop!(
+            BulletTrain::claim_dpo_rewards(Origin::signed(CAROL), 0, Buyer::Passenger(1000)),
+            Error::<Test>::ZeroBalanceToWithdraw
+        );
+
+        //the other members are not affected by the claim of 1000
+        claim_all_dpo_rewards(0);
+        assert_eq!(BulletTrain::dpo_reward_pools(0).total_bonus_withdrawn, 10000);
+        assert_eq!(
+            Balances::free_balance(BOB),
+            DEFAULT_BALANCE_USER - 10000 + 1000 + 3000 + 600
+        );
+        assert_eq!(Balances::free_balance(1000), 2400 + 600);
+    });
+}
+
+#[test]
+fn claim_dpo_rewards_pays_bonus_remainder_to_manager_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        //deposit: 100000 bonus: 1000
+        make_default_travel_cabin(BOLT, (10, 1, 1, 1, 1));
+        make_default_dpo(ALICE, Target::TravelCabin(0), 10000, 10, None);
+        //dpo 1 takes 30% of dpo 0, its manager takes 10% of it
+        make_default_dpo(BOB, Target::Dpo(0, 30000), 3000, 9, None);
+        for (i, amount) in vec![(1000, 8127), (1001, 8127), (1002, 5427), (1003, 5319)] {
+            assert_ok!(Currencies::deposit(BOLT, &i, amount));
+            assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(i), 1, amount, None));
+        }
+        dpo_buy_target(BOB, 1, 100);
+        fill_dpo_with_dummy_accounts(0, 100);
+        dpo_buy_target(ALICE, 0, 100);
+
+        assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(ALICE), 0));
+        assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(ALICE), 1));
+        //the manager portion of the 300 bonus of dpo 1 goes up its referral chain in dpo 0
+        assert_eq!(BulletTrain::dpo_reward_pools(1).total_bonus, 270);
+        //the remainder of the shares is booked on release
+        assert_eq!(BulletTrain::dpo_reward_pools(1).bonus_remainder, 1);
+
+        //the members claim 81, 81, 54 and 53 by share, the manager the remainder
+        for i in 1000..1004 {
+            assert_ok!(BulletTrain::claim_dpo_rewards(Origin::signed(ALICE), 1, Buyer::Passenger(i)));
+        }
+        assert_eq!(BulletTrain::dpo_reward_pools(1).total_bonus_withdrawn, 269);
+        assert_ok!(BulletTrain::claim_dpo_rewards(Origin::signed(ALICE), 1, Buyer::Passenger(BOB)));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoRewardsClaimed(
+            ALICE,
+            1,
+            Buyer::Passenger(BOB),
+            0,
+            1,
+        ));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpo_reward_pools(1).total_bonus_withdrawn, 270);
+        assert_noop!(
+            BulletTrain::claim_dpo_rewards(Origin::signed(ALICE), 1, Buyer::Passenger(BOB)),
+            Error::<Test>::ZeroBalanceToWithdraw
+        );
+    });
+}
+
+/// this test case also test the correctness of the referral structure
+#[test]
+fn dpo_release_bonus_with_0_direct_referral_rate_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        //alice creates Cabins
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+        assert_eq!(Balances::free_balance(ALICE), 1000000);
+        //alice creates dpo 0 and take 15% spending 15,000, referred by adam
+        //direct referral rate 0
+        assert_ok!(BulletTrain::create_dpo(
+            Origin::signed(ALICE),
+            String::from("test").into_bytes(),
+            Target::TravelCabin(0),
+            15000, // 15%
+            50,
+            0,
+            10,
+            Some(ADAM),
+            DpoAccessPolicy::Public
+        ));
+        assert_eq!(Balances::free_balance(ALICE), 1000000 - 15000);
+        //BCDE taking 10% each, spending 10,000
+        for i in BOB..FRED {
+            assert_ok!(BulletTrain::passenger_buy_dpo_share(
+                Origin::signed(i),
+                0,
+                10000, // 10%
+                None
+            ));
+        }
+        //F taking 15%, spending 15,000
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(
+            Origin::signed(FRED),
+            0,
+            15000, // 15%
+            None
+        ));
+        // JILL takes 30% via DPO 1, taking 15% of DPO1, spending 30000 * 15% = 4500
+        make_default_dpo(JILL, Target::Dpo(0, 30000), 4500, 9, None);
+        //BCEDFGH taking 10 each, spending 3000
+        for i in BOB..IVAN {
+            assert_ok!(BulletTrain::passenger_buy_dpo_share(
+                Origin::signed(i),
+                1,
+                3000, // 10%
+                None
+            ));
+        }
+        //I taking 15%, spending 4500
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(
+            Origin::signed(IVAN),
+            1,
+            4500, // 15%
+            None
+        ));
+
+        dpo_buy_target(JILL, 1, 100);
+        dpo_buy_target(ALICE, 0, 100);
+        run_to_block(1);
+        let dpo0 = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo0.vault_bonus, 1000);
+        assert_eq!(dpo0.vault_yield, 0);
+
+        assert_ok!(BulletTrain::release_bonus_from_dpo(
+            Origin::signed(ALICE),
+            0
+        ));
+        claim_all_dpo_rewards(0);
+        let expected_event = Event::pallet_bullet_train(crate::Event::BonusReleased(ALICE, 0));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(BulletTrain::dpos(1).unwrap().vault_bonus, 255);
+        assert_eq!(BulletTrain::dpos(1).unwrap().total_bonus_received, 255);
+        assert_eq!(BulletTrain::dpos(0).unwrap().vault_bonus, 0);
+
+        assert_eq!(
+            BulletTrain::dpo_members(0, Buyer::Dpo(1)).unwrap(),
+            DpoMemberInfo {
+                buyer: Buyer::Dpo(1),
+                share: 30000, // 30%
+                referrer: Referrer::MemberOfDpo(Buyer::Passenger(FRED)),
+            }
+        );
+
+        //referral chain of dpo0 Alice <- Bob <- Carol <- Dylan <- Elsa <- Fred -< DPO1(JILL)/
+        assert_eq!(
+            Balances::free_balance(ALICE),
+            1000000 - 15000 + 105 + 100 + 100
+        ); // 150 * 70% (30% to ADAM as external referrer)
+           // + 100 from bob + 100 from Carol
         assert_eq!(Balances::free_balance(ADAM), 500000 + 45); // 150 * 30%
                                                                // base for everyone is BOB, CAROL, DYLAN and ELSA = 500000 - 10000 - 3000 = 487000 + x
         assert_eq!(Balances::free_balance(BOB), 487000 + 0 + 100); // carol 0 + dylan 100
@@ -1935,15 +2650,351 @@ fn dpo_release_bonus_with_0_direct_referral_rate_works() {
         assert_eq!(Balances::free_balance(FRED), 482000); // DPO1 0
                                                           // base for JILL = 500000 - 4500 = 495500, but got 30 + 6 from DPO 1
 
-        // release bonus of dpo1. 1% share worths 3 bonus.
-        assert_ok!(BulletTrain::release_bonus_from_dpo(
-            Origin::signed(ALICE),
-            1
+        // release bonus of dpo1. 1% share worths 3 bonus.
+        assert_ok!(BulletTrain::release_bonus_from_dpo(
+            Origin::signed(ALICE),
+            1
+        ));
+        claim_all_dpo_rewards(1);
+    });
+}
+
+/// this test case also test the correctness of the referral structure
+/// bob creates dpo 0 with the access policy, taking 10%
+fn make_private_dpo(access_policy: DpoAccessPolicy) -> () {
+    make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+    assert_ok!(BulletTrain::create_dpo(
+        Origin::signed(BOB),
+        String::from("test").into_bytes(),
+        Target::TravelCabin(0),
+        10000, // 10%
+        50,
+        800,
+        10,
+        None,
+        access_policy
+    ));
+}
+
+#[test]
+fn private_dpo_allow_list_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_private_dpo(DpoAccessPolicy::AllowList);
+        assert_eq!(BulletTrain::dpos(0).unwrap().access_policy, DpoAccessPolicy::AllowList);
+
+        assert_noop!(
+            BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None),
+            Error::<Test>::NotOnDpoAllowList
+        );
+        // the manager can always buy more
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(BOB), 0, 10000, None));
+
+        // only the manager updates the list
+        assert_noop!(
+            BulletTrain::update_dpo_allow_list(
+                Origin::signed(CAROL),
+                0,
+                vec![Buyer::Passenger(CAROL)],
+                true
+            ),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            BulletTrain::update_dpo_allow_list(Origin::signed(BOB), 0, vec![Buyer::InvalidBuyer], true),
+            Error::<Test>::InvalidBuyerType
+        );
+        assert_noop!(
+            BulletTrain::update_dpo_allow_list(
+                Origin::signed(BOB),
+                0,
+                (0..MaxAllowListLength::get() as u128 + 1).map(Buyer::Passenger).collect(),
+                true
+            ),
+            Error::<Test>::TooManyBuyers
+        );
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(CAROL), Buyer::Passenger(DYLAN)],
+            true
+        ));
+        let expected_event =
+            Event::pallet_bullet_train(crate::Event::DpoAllowListUpdated(BOB, 0, 2, true));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert!(BulletTrain::dpo_allow_list(0, Buyer::Passenger(CAROL)));
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None));
+
+        // removed from the list
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(CAROL)],
+            false
         ));
+        assert!(!BulletTrain::dpo_allow_list(0, Buyer::Passenger(CAROL)));
+        assert_noop!(
+            BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None),
+            Error::<Test>::NotOnDpoAllowList
+        );
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(DYLAN), 0, 10000, None));
+    });
+}
+
+#[test]
+fn private_dpo_allow_list_for_dpo_buyer_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_private_dpo(DpoAccessPolicy::AllowList);
+
+        // the manager of a new dpo must be allowed in the private target
+        assert_noop!(
+            BulletTrain::create_dpo(
+                Origin::signed(JILL),
+                String::from("test").into_bytes(),
+                Target::Dpo(0, 30000),
+                4500,
+                50,
+                800,
+                9,
+                None,
+                DpoAccessPolicy::Public
+            ),
+            Error::<Test>::NotOnDpoAllowList
+        );
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(JILL)],
+            true
+        ));
+        make_default_dpo(JILL, Target::Dpo(0, 30000), 4500, 9, None);
+        fill_dpo_with_dummy_accounts(1, 100);
+
+        // a dpo buyer is allowed while its manager is on the list
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(JILL)],
+            false
+        ));
+        assert_noop!(
+            BulletTrain::dpo_buy_dpo_share(Origin::signed(JILL), 1, 0, 30000),
+            Error::<Test>::NotOnDpoAllowList
+        );
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(JILL)],
+            true
+        ));
+        dpo_buy_target(JILL, 1, 100);
+        assert!(BulletTrain::dpo_members(0, Buyer::Dpo(1)).is_some());
+    });
+}
+
+#[test]
+fn private_dpo_invite_code_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        let invite_key = sr25519::Pair::from_seed(&[7; 32]);
+        let invite = |dpo_idx, invitee| H512(invite_key.sign(&BulletTrain::dpo_invitation(dpo_idx, &invitee)).0);
+        make_private_dpo(DpoAccessPolicy::InviteCode(H256(invite_key.public().0)));
+
+        assert_noop!(
+            BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None),
+            Error::<Test>::NotOnDpoAllowList
+        );
+        // signed by another key
+        let other_key = sr25519::Pair::from_seed(&[8; 32]);
+        assert_noop!(
+            BulletTrain::redeem_dpo_invite_code(
+                Origin::signed(CAROL),
+                0,
+                H512(other_key.sign(&BulletTrain::dpo_invitation(0, &CAROL)).0)
+            ),
+            Error::<Test>::InvalidInviteCode
+        );
+        // the invitation of carol can not be taken by anyone else
+        assert_noop!(
+            BulletTrain::redeem_dpo_invite_code(Origin::signed(DYLAN), 0, invite(0, CAROL)),
+            Error::<Test>::InvalidInviteCode
+        );
+        assert_ok!(BulletTrain::redeem_dpo_invite_code(Origin::signed(CAROL), 0, invite(0, CAROL)));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoInviteCodeRedeemed(CAROL, 0));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(CAROL), 0, 10000, None));
+        assert_noop!(
+            BulletTrain::redeem_dpo_invite_code(Origin::signed(CAROL), 0, invite(0, CAROL)),
+            Error::<Test>::InviteCodeAlreadyUsed
+        );
+
+        // a removed buyer can not join again with its invitation
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(CAROL)],
+            false
+        ));
+        assert_noop!(
+            BulletTrain::redeem_dpo_invite_code(Origin::signed(CAROL), 0, invite(0, CAROL)),
+            Error::<Test>::InviteCodeAlreadyUsed
+        );
+
+        // the manager can also add buyers to the list of a dpo with invite code
+        assert_ok!(BulletTrain::update_dpo_allow_list(
+            Origin::signed(BOB),
+            0,
+            vec![Buyer::Passenger(DYLAN)],
+            true
+        ));
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(Origin::signed(DYLAN), 0, 10000, None));
+
+        // public dpos have no invite code
+        make_default_dpo(ALICE, Target::TravelCabin(0), 10000, 10, None);
+        assert_noop!(
+            BulletTrain::redeem_dpo_invite_code(Origin::signed(ELSA), 1, invite(1, ELSA)),
+            Error::<Test>::InvalidInviteCode
+        );
+    });
+}
+
+/// alice manages dpo 0 with 10%, referred by adam. BOB to JILL take 10% each,
+/// making the referral chain Alice <- Bob <- Carol <- ... <- Ivan <- Jill.
+/// every 10% share receives 100 of the 1000 bonus
+fn make_referral_chain_dpo(external_referral_rate: u32, referral_chain_depth: u32) -> () {
+    make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+    assert_ok!(BulletTrain::create_dpo(
+        Origin::signed(ALICE),
+        String::from("test").into_bytes(),
+        Target::TravelCabin(0),
+        10000, // 10%
+        50,
+        500, // 50%
+        10,
+        Some(ADAM),
+        DpoAccessPolicy::Public
+    ));
+    assert_ok!(BulletTrain::set_dpo_referral_policy(
+        Origin::signed(ALICE),
+        0,
+        external_referral_rate,
+        referral_chain_depth
+    ));
+    for i in BOB..=JILL {
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(
+            Origin::signed(i),
+            0,
+            10000, // 10%
+            None
+        ));
+    }
+    dpo_buy_target(ALICE, 0, 100);
+    assert_eq!(BulletTrain::dpos(0).unwrap().vault_bonus, 1000);
+    assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(ALICE), 0));
+    claim_all_dpo_rewards(0);
+}
+
+#[test]
+fn set_dpo_referral_policy_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1));
+        make_default_dpo(ALICE, Target::TravelCabin(0), 10000, 10, None);
+        assert_eq!(BulletTrain::dpo_referral_policies(0), None);
+        assert_eq!(
+            BulletTrain::referral_policy_of_dpo(0),
+            DpoReferralPolicy {
+                external_referral_rate: 300,
+                referral_chain_depth: 2,
+            }
+        );
+
+        assert_noop!(
+            BulletTrain::set_dpo_referral_policy(Origin::signed(BOB), 0, 200, 3),
+            Error::<Test>::NoPermission
+        );
+        assert_noop!(
+            BulletTrain::set_dpo_referral_policy(Origin::signed(ALICE), 0, 501, 3),
+            Error::<Test>::ExceededRateCap
+        );
+        assert_noop!(
+            BulletTrain::set_dpo_referral_policy(Origin::signed(ALICE), 0, 200, 0),
+            Error::<Test>::InvalidReferralChainDepth
+        );
+        assert_noop!(
+            BulletTrain::set_dpo_referral_policy(Origin::signed(ALICE), 0, 200, 6),
+            Error::<Test>::InvalidReferralChainDepth
+        );
+
+        assert_ok!(BulletTrain::set_dpo_referral_policy(Origin::signed(ALICE), 0, 200, 3));
+        let expected_event =
+            Event::pallet_bullet_train(crate::Event::DpoReferralPolicySet(ALICE, 0, 200, 3));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(
+            BulletTrain::referral_policy_of_dpo(0),
+            DpoReferralPolicy {
+                external_referral_rate: 200,
+                referral_chain_depth: 3,
+            }
+        );
+
+        // not after other members join
+        assert_ok!(BulletTrain::passenger_buy_dpo_share(
+            Origin::signed(BOB),
+            0,
+            10000,
+            None
+        ));
+        assert_noop!(
+            BulletTrain::set_dpo_referral_policy(Origin::signed(ALICE), 0, 100, 2),
+            Error::<Test>::DpoWrongState
+        );
+    });
+}
+
+#[test]
+fn dpo_release_bonus_by_referral_chain_depth_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_referral_chain_dpo(200, 3);
+
+        // alice: 100 * 80% of her own (20% to adam) + 100 from bob + 50 from carol + 25 from dylan
+        assert_eq!(Balances::free_balance(ALICE), 1000000 - 10000 + 80 + 100 + 50 + 25);
+        assert_eq!(Balances::free_balance(ADAM), DEFAULT_BALANCE_USER + 20);
+        // the chain stops at the manager: carol pays bob 50 and alice 50
+        // dylan and later pay their parent 50, grandparent 25 and great-grandparent 25
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER - 10000 + 50 + 25 + 25);
+        for i in CAROL..=GREG {
+            assert_eq!(Balances::free_balance(i), DEFAULT_BALANCE_USER - 10000 + 50 + 25 + 25);
+        }
+        assert_eq!(Balances::free_balance(HUGH), DEFAULT_BALANCE_USER - 10000 + 50 + 25);
+        assert_eq!(Balances::free_balance(IVAN), DEFAULT_BALANCE_USER - 10000 + 50);
+        assert_eq!(Balances::free_balance(JILL), DEFAULT_BALANCE_USER - 10000);
+
+        // the own bonus of the manager is not a referral earning
+        assert_eq!(BulletTrain::referral_earnings(ALICE, BOLT), 100 + 50 + 25);
+        assert_eq!(BulletTrain::referral_earnings(ADAM, BOLT), 20);
+        assert_eq!(
+            BulletTrain::get_referral_earnings_of_account(&HUGH),
+            vec![(BOLT, 75)]
+        );
+        assert_eq!(BulletTrain::get_referral_earnings_of_account(&JILL), vec![]);
+    });
+}
+
+#[test]
+fn dpo_release_bonus_by_referral_chain_depth_one_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_referral_chain_dpo(300, 1);
+
+        // every referrer keeps all the bonus of its direct referral
+        assert_eq!(Balances::free_balance(ALICE), 1000000 - 10000 + 70 + 100);
+        assert_eq!(Balances::free_balance(ADAM), DEFAULT_BALANCE_USER + 30);
+        for i in BOB..=IVAN {
+            assert_eq!(Balances::free_balance(i), DEFAULT_BALANCE_USER - 10000 + 100);
+            assert_eq!(BulletTrain::referral_earnings(i, BOLT), 100);
+        }
+        assert_eq!(Balances::free_balance(JILL), DEFAULT_BALANCE_USER - 10000);
+        assert_eq!(BulletTrain::referral_earnings(ALICE, BOLT), 100);
     });
 }
 
-/// this test case also test the correctness of the referral structure
 #[test]
 fn dpo_release_bonus_internally_works() {
     ExtBuilder::default().build().execute_with(|| {
@@ -2000,6 +3051,7 @@ fn dpo_release_bonus_internally_works() {
             Origin::signed(ALICE),
             0
         ));
+        claim_all_dpo_rewards(0);
         let expected_event = Event::pallet_bullet_train(crate::Event::BonusReleased(ALICE, 0));
         assert!(System::events().iter().any(|a| a.event == expected_event));
         assert_eq!(BulletTrain::dpos(1).unwrap().vault_bonus, 255);
@@ -2043,6 +3095,7 @@ fn dpo_release_bonus_internally_works() {
             Origin::signed(ALICE),
             1
         ));
+        claim_all_dpo_rewards(1);
         //referral chain of dpo1 JILL <- Bob <- Carol <- Dylan <- Elsa <- Fred -< Greg -< Hugh -< Ivan
         assert_eq!(Balances::free_balance(JILL), 495500 + 30 + 6); //30 from Bob, 6 from Carol
         assert_eq!(Balances::free_balance(BOB), 487000 + 80 + 20 + 24 + 6); // Carol 24 + Dylan 6
@@ -2475,7 +3528,8 @@ fn dpo_change_smaller_cabin_and_activate() {
             1000,
             1100,
             10,
-            1
+            1,
+            YieldSchedule::Linear,
         ));
         // cabin 3
         assert_ok!(BulletTrain::create_travel_cabin(
@@ -2486,7 +3540,8 @@ fn dpo_change_smaller_cabin_and_activate() {
             0,
             1500,
             10,
-            1
+            1,
+            YieldSchedule::Linear,
         ));
         // dpo 0 takes 10% (10000)
         make_default_dpo(ALICE, Target::TravelCabin(0), 10000, 10, None);
@@ -2566,6 +3621,7 @@ fn dpo_change_smaller_cabin_and_activate() {
             Origin::signed(ALICE),
             0
         ));
+        claim_all_dpo_rewards(0);
         assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::RUNNING);
         assert_eq!(BulletTrain::dpos(1).unwrap().target_amount, 7500); // 15000 * (10000 / 20000)
         assert_eq!(BulletTrain::dpos(1).unwrap().total_fund, 10000); // target 7500 + unused 2500
@@ -2598,7 +3654,8 @@ fn dpo_change_target_to_non_default_dpo() {
             0,
             100,
             10,
-            1
+            1,
+            YieldSchedule::Linear,
         ));
         // dpo 0 manager takes 10%
         make_default_dpo(ALICE, Target::TravelCabin(0), 10000, 10, None);
@@ -2689,3 +3746,275 @@ fn compare_targets_works() {
         );
     });
 }
+
+fn make_portfolio_dpo(manager: AccountId, legs: Vec<Target<Balance>>, amount: Balance) -> () {
+    assert_ok!(BulletTrain::create_portfolio_dpo(
+        Origin::signed(manager),
+        String::from("test").into_bytes(),
+        legs,
+        amount,
+        50,  //base fee, per thousand
+        800, //direct referral rate, per thousand
+        10,  //end block
+        None,
+        DpoAccessPolicy::Public
+    ));
+}
+
+#[test]
+fn portfolio_dpo_completes_by_legs_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_default_travel_cabin(BOLT, (10, 1, 1, 2, 1)); // cabin 0 matures in 20 blocks
+        make_default_travel_cabin(BOLT, (10, 1, 1, 1, 1)); // cabin 1 in 10
+        make_portfolio_dpo(BOB, vec![Target::TravelCabin(0), Target::TravelCabin(1)], 20000);
+
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.target, Target::Portfolio(200000));
+        assert_eq!(dpo.target_amount, 200000);
+        assert_eq!(dpo.target_yield_estimate, 2000);
+        assert_eq!(dpo.target_bonus_estimate, 2000);
+        assert_eq!(dpo.target_maturity, 20);
+        let legs = BulletTrain::dpo_portfolio_legs(0);
+        assert_eq!(legs.len(), 2);
+        assert!(legs.iter().all(|leg| leg.state == PortfolioLegState::Pending));
+
+        // legs are bought once the dpo is filled
+        assert_noop!(
+            BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 0, 0),
+            Error::<Test>::DpoWrongState
+        );
+        fill_dpo_with_dummy_accounts(0, 100);
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::ACTIVE);
+        // not directly as a target
+        assert_noop!(
+            BulletTrain::dpo_buy_travel_cabin(Origin::signed(BOB), 0, 0),
+            Error::<Test>::NotAllowedToChangeTarget
+        );
+        // only by the manager within the grace period
+        assert_noop!(
+            BulletTrain::dpo_buy_portfolio_leg(Origin::signed(1000), 0, 0),
+            Error::<Test>::NoPermission
+        );
+
+        // the fund of the other leg is kept on buying a leg
+        assert_ok!(BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 0, 0));
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.state, DpoState::RUNNING);
+        assert_eq!(dpo.vault_deposit, 100000);
+        assert_eq!(dpo.vault_withdraw, 0);
+        assert_eq!(dpo.vault_bonus, 1000);
+        let leg = BulletTrain::dpo_portfolio_legs(0)[0].clone();
+        assert_eq!(leg.state, PortfolioLegState::Purchased);
+        assert_eq!(leg.cabin_number, Some(0));
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoPortfolioLegPurchased(BOB, 0, 0));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_noop!(
+            BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 0, 0),
+            Error::<Test>::PortfolioLegNotPending
+        );
+        assert_noop!(
+            BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 0, 2),
+            Error::<Test>::InvalidIndex
+        );
+
+        assert_ok!(BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 0, 1));
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.vault_deposit, 0);
+        assert_eq!(dpo.vault_bonus, 2000);
+        assert_eq!(BulletTrain::travel_cabin_buyer(1, 0).unwrap().buyer, Buyer::Dpo(0));
+
+        // partial completion, the fare of the first leg back is paid out while the other runs
+        run_to_block(12);
+        assert_ok!(BulletTrain::withdraw_fare_from_travel_cabin(Origin::signed(ALICE), 1, 0));
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.state, DpoState::RUNNING);
+        assert_eq!(dpo.vault_withdraw, 100000);
+        assert_eq!(BulletTrain::dpo_portfolio_legs(0)[1].state, PortfolioLegState::Completed);
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 0));
+        assert_eq!(BulletTrain::dpos(0).unwrap().vault_withdraw, 0);
+        assert_eq!(Currencies::free_balance(BOLT, &1000), 30000);
+
+        // completed once all the legs are back
+        run_to_block(22);
+        assert_ok!(BulletTrain::withdraw_fare_from_travel_cabin(Origin::signed(ALICE), 0, 0));
+        let dpo = BulletTrain::dpos(0).unwrap();
+        assert_eq!(dpo.state, DpoState::COMPLETED);
+        assert_eq!(dpo.vault_withdraw, 100000);
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 0));
+        assert!(BulletTrain::dpos(0).unwrap().fare_withdrawn);
+        assert_eq!(Currencies::free_balance(BOLT, &1000), 60000);
+    });
+}
+
+#[test]
+fn portfolio_dpo_abandons_unavailable_legs_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        set_dpo_creation_deposit(100);
+        make_default_travel_cabin(BOLT, (10, 1, 1, 1, 1));
+        make_default_travel_cabin(BOLT, (10, 1, 1, 1, 1));
+        make_default_dpo(CAROL, Target::TravelCabin(1), 10000, 10, None);
+
+        // 2 to MaxPortfolioLegs legs of distinct dpos
+        assert_noop!(
+            BulletTrain::create_portfolio_dpo(
+                Origin::signed(BOB),
+                String::from("test").into_bytes(),
+                vec![Target::TravelCabin(0)],
+                10000,
+                50,
+                800,
+                10,
+                None,
+                DpoAccessPolicy::Public
+            ),
+            Error::<Test>::InvalidPortfolio
+        );
+        assert_noop!(
+            BulletTrain::create_portfolio_dpo(
+                Origin::signed(BOB),
+                String::from("test").into_bytes(),
+                vec![Target::Dpo(0, 30000), Target::Dpo(0, 20000)],
+                10000,
+                50,
+                800,
+                10,
+                None,
+                DpoAccessPolicy::Public
+            ),
+            Error::<Test>::InvalidPortfolio
+        );
+        // a portfolio is not a target on its own
+        assert_noop!(
+            BulletTrain::create_dpo(
+                Origin::signed(BOB),
+                String::from("test").into_bytes(),
+                Target::Portfolio(100000),
+                10000,
+                50,
+                800,
+                10,
+                None,
+                DpoAccessPolicy::Public
+            ),
+            Error::<Test>::InvalidTargetForDpo
+        );
+
+        make_portfolio_dpo(BOB, vec![Target::TravelCabin(0), Target::Dpo(0, 30000)], 13000);
+        assert_eq!(BulletTrain::dpos(1).unwrap().target_amount, 130000);
+        assert_eq!(Balances::reserved_balance(BOB), 100);
+        fill_dpo_with_dummy_accounts(1, 100);
+
+        // a leg available is not abandoned
+        assert_noop!(
+            BulletTrain::dpo_abandon_portfolio_leg(Origin::signed(CAROL), 1, 0),
+            Error::<Test>::DefaultTargetAvailable
+        );
+        assert_ok!(BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 1, 1));
+        assert_eq!(
+            BulletTrain::dpo_members(0, Buyer::Dpo(1)).unwrap().share,
+            30000
+        );
+        assert_eq!(BulletTrain::dpos(1).unwrap().state, DpoState::RUNNING);
+        assert_noop!(
+            BulletTrain::dpo_change_target(Origin::signed(BOB), 1, Target::TravelCabin(1)),
+            Error::<Test>::InvalidTargetForDpo
+        );
+
+        // the cabin leg is sold out
+        assert_ok!(BulletTrain::passenger_buy_travel_cabin(Origin::signed(DYLAN), 0));
+        assert_noop!(
+            BulletTrain::dpo_buy_portfolio_leg(Origin::signed(BOB), 1, 0),
+            Error::<Test>::CabinNotAvailable
+        );
+        assert_ok!(BulletTrain::dpo_abandon_portfolio_leg(Origin::signed(CAROL), 1, 0));
+        let dpo = BulletTrain::dpos(1).unwrap();
+        assert_eq!(BulletTrain::dpo_portfolio_legs(1)[0].state, PortfolioLegState::Abandoned);
+        assert_eq!(dpo.vault_deposit, 0);
+        assert_eq!(dpo.vault_withdraw, 100000);
+        assert_eq!(dpo.state, DpoState::RUNNING);
+
+        // the target dpo of the other leg fails, the portfolio fails with all its legs abandoned
+        run_to_block(11);
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(CAROL), 0));
+        assert_eq!(BulletTrain::dpos(0).unwrap().state, DpoState::FAILED);
+        let dpo = BulletTrain::dpos(1).unwrap();
+        assert_eq!(BulletTrain::dpo_portfolio_legs(1)[1].state, PortfolioLegState::Abandoned);
+        assert_eq!(dpo.vault_deposit, 130000);
+        assert_eq!(dpo.vault_withdraw, 0);
+        assert_eq!(dpo.state, DpoState::FAILED);
+        let expected_event = Event::pallet_bullet_train(crate::Event::DpoFailed(1));
+        assert!(System::events().iter().any(|a| a.event == expected_event));
+        assert_eq!(Balances::reserved_balance(BOB), 0);
+
+        // the members are refunded by the fail path
+        assert_ok!(BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 1));
+        let dpo = BulletTrain::dpos(1).unwrap();
+        assert!(dpo.fare_withdrawn);
+        assert_eq!(dpo.vault_deposit, 0);
+        assert_eq!(Currencies::free_balance(BOLT, &1000), 39000);
+        assert_eq!(Balances::free_balance(BOB), DEFAULT_BALANCE_USER);
+        assert_noop!(
+            BulletTrain::release_fare_from_dpo(Origin::signed(BOB), 1),
+            Error::<Test>::ZeroBalanceToWithdraw
+        );
+    });
+}
+
+#[test]
+fn check_invariants_reports_deficits_and_broken_entities() {
+    ExtBuilder::default().build().execute_with(|| {
+        make_default_travel_cabin(BOLT, (1, 1, 1, 1, 10));
+        assert_ok!(BulletTrain::passenger_buy_travel_cabin(Origin::signed(BOB), 0));
+        make_default_dpo(ALICE, Target::TravelCabin(0), 1000, 10, None);
+        fill_dpo_with_dummy_accounts(0, 100);
+        dpo_buy_target(ALICE, 0, 100);
+
+        run_to_block(5);
+        assert_ok!(BulletTrain::withdraw_yield_from_travel_cabin(Origin::signed(BOB), 0, 0));
+        assert_ok!(BulletTrain::withdraw_yield_from_travel_cabin(Origin::signed(ALICE), 0, 1));
+        assert_ok!(BulletTrain::release_yield_from_dpo(Origin::signed(ALICE), 0));
+        assert_ok!(BulletTrain::release_bonus_from_dpo(Origin::signed(ALICE), 0));
+        claim_all_dpo_rewards(0);
+
+        let report = BulletTrain::check_invariants();
+        assert!(report.is_ok());
+        assert_eq!(report.currencies.len(), 1);
+        let bolt = report.currencies[0].clone();
+        assert_eq!(bolt.currency_id, BOLT);
+        assert_eq!(bolt.balance, Balances::free_balance(BulletTrain::account_id()));
+        assert_eq!(bolt.balance, bolt.obligations + bolt.surplus);
+
+        // a dpo booking more yield than the pallet holds, and shares issued to no member
+        crate::Dpos::<Test>::mutate(0, |dpo| {
+            let dpo = dpo.as_mut().unwrap();
+            dpo.vault_yield += bolt.surplus + 1;
+            dpo.issued_shares += 1;
+        });
+        let report = BulletTrain::check_invariants();
+        assert!(!report.is_ok());
+        assert_eq!(report.currencies[0].deficit, 1);
+        assert_eq!(report.currencies[0].surplus, 0);
+        assert_eq!(report.dpos, vec![(0, InvariantViolation::DpoSharesMismatch)]);
+        assert!(report.travel_cabins.is_empty());
+
+        // a cabin sold more than issued, without the buyer records
+        crate::TravelCabinInventory::<Test>::insert(0, (11, 10));
+        assert_eq!(
+            BulletTrain::check_invariants().travel_cabins,
+            vec![
+                (0, InvariantViolation::TravelCabinOversold),
+                (0, InvariantViolation::TravelCabinBuyerMissing),
+            ]
+        );
+    });
+}
+
+#[test]
+fn fuzzing_harness_holds_on_seeded_inputs() {
+    for seed in 0u64..200 {
+        let input: Vec<u8> = (0u64..8)
+            .flat_map(|chunk| sp_core::blake2_256(&[seed.to_le_bytes(), chunk.to_le_bytes()].concat()).to_vec())
+            .collect();
+        crate::fuzzing::run(&input);
+    }
+}
@@ -1,11 +1,11 @@
-
-//! Autogenerated weights for pallet_bullet_train
+//! Estimated weights for pallet_bullet_train
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 3.0.0
-//! DATE: 2021-07-16, STEPS: [50, ], REPEAT: 20, LOW RANGE: [], HIGH RANGE: []
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("spanner-dev"), DB CACHE: 128
+//! PARTLY NOT GENERATED BY THE BENCHMARK CLI. The calls added or changed since the benchmark
+//! run of 2021-07-16 on spanner-dev have weights estimated by hand from their storage reads and
+//! writes and from the benchmarked weights of similar calls. Regenerate this file with the
+//! command below before relying on them.
 
-// Executed Command:
+// Regenerate with:
 // ./target/release/substrate
 // benchmark
 // --chain=spanner-dev
@@ -29,7 +29,7 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for pallet_bullet_train.
 pub trait WeightInfo {
 	fn create_milestone_reward() -> Weight;
-	fn release_milestone_reward() -> Weight;
+	fn release_milestone_reward(p: u32, ) -> Weight;
 	fn create_travel_cabin() -> Weight;
 	fn issue_additional_travel_cabin() -> Weight;
 	fn withdraw_fare_from_travel_cabin() -> Weight;
@@ -40,9 +40,20 @@ pub trait WeightInfo {
 	fn dpo_change_target() -> Weight;
 	fn passenger_buy_dpo_share() -> Weight;
 	fn dpo_buy_dpo_share() -> Weight;
-	fn release_fare_from_dpo() -> Weight;
+	fn release_fare_from_dpo(m: u32, ) -> Weight;
 	fn release_yield_from_dpo() -> Weight;
-	fn release_bonus_from_dpo() -> Weight;
+	fn release_bonus_from_dpo(m: u32, ) -> Weight;
+	fn continue_dpo_payout(m: u32, ) -> Weight;
+	fn claim_dpo_rewards() -> Weight;
+	fn set_dpo_base_fee() -> Weight;
+	fn set_dpo_referral_policy() -> Weight;
+	fn update_dpo_allow_list(n: u32, ) -> Weight;
+	fn redeem_dpo_invite_code() -> Weight;
+	fn fail_expired_dpos(n: u32, ) -> Weight;
+	fn apply_dpo_base_fee_changes(n: u32, ) -> Weight;
+	fn create_portfolio_dpo(l: u32, ) -> Weight;
+	fn dpo_buy_portfolio_leg() -> Weight;
+	fn dpo_abandon_portfolio_leg() -> Weight;
 }
 
 /// Weight functions for pallet_bullet_train.
@@ -53,10 +64,13 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(3 as Weight))
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
-	fn release_milestone_reward() -> Weight {
-		(70_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(2 as Weight))
-			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	fn release_milestone_reward(p: u32, ) -> Weight {
+		(96_000_000 as Weight)
+			.saturating_add((58_000_000 as Weight).saturating_mul(p as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(p as Weight)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes((3 as Weight).saturating_mul(p as Weight)))
 	}
 	fn create_travel_cabin() -> Weight {
 		(166_000_000 as Weight)
@@ -79,19 +93,19 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(3 as Weight))
 	}
 	fn create_dpo() -> Weight {
-		(178_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(6 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+		(181_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
 	}
 	fn passenger_buy_travel_cabin() -> Weight {
 		(260_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(6 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
 	fn dpo_buy_travel_cabin() -> Weight {
 		(115_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
 	}
 	fn dpo_change_target() -> Weight {
 		(94_000_000 as Weight)
@@ -99,28 +113,100 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().writes(1 as Weight))
 	}
 	fn passenger_buy_dpo_share() -> Weight {
-		(166_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(4 as Weight))
-			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+		(169_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
 	fn dpo_buy_dpo_share() -> Weight {
-		(110_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(3 as Weight))
-			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+		(114_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
 	}
-	fn release_fare_from_dpo() -> Weight {
-		(475_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(11 as Weight))
-			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	fn release_fare_from_dpo(m: u32, ) -> Weight {
+		(118_000_000 as Weight)
+			.saturating_add((64_000_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(m as Weight)))
 	}
 	fn release_yield_from_dpo() -> Weight {
-		(524_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+		(143_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn release_bonus_from_dpo(m: u32, ) -> Weight {
+		(98_000_000 as Weight)
+			.saturating_add((11_000_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn continue_dpo_payout(m: u32, ) -> Weight {
+		(109_000_000 as Weight)
+			.saturating_add((64_000_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((3 as Weight).saturating_mul(m as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(m as Weight)))
+	}
+	fn claim_dpo_rewards() -> Weight {
+		(236_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	fn set_dpo_base_fee() -> Weight {
+		(34_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn set_dpo_referral_policy() -> Weight {
+		(28_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn update_dpo_allow_list(n: u32, ) -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add((3_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn redeem_dpo_invite_code() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn fail_expired_dpos(n: u32, ) -> Weight {
+		(6_000_000 as Weight)
+			.saturating_add((21_500_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(n as Weight)))
+	}
+	fn apply_dpo_base_fee_changes(n: u32, ) -> Weight {
+		(5_000_000 as Weight)
+			.saturating_add((32_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((4 as Weight).saturating_mul(n as Weight)))
+	}
+	fn create_portfolio_dpo(l: u32, ) -> Weight {
+		(164_000_000 as Weight)
+			.saturating_add((18_300_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	fn dpo_buy_portfolio_leg() -> Weight {
+		(121_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
-	fn release_bonus_from_dpo() -> Weight {
-		(611_000_000 as Weight)
-			.saturating_add(T::DbWeight::get().reads(10 as Weight))
-			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	fn dpo_abandon_portfolio_leg() -> Weight {
+		(58_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
 }
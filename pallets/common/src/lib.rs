@@ -37,6 +37,15 @@ pub trait DexManager<AccountId, CurrencyId, Balance> {
         max_supply_amount: Balance,
         gas_price_limit: Option<Ratio>,
     ) -> sp_std::result::Result<Balance, DispatchError>;
+
+    /// adds liquidity to an enabled pool, returning the lp share minted to `who`
+    fn add_liquidity(
+        who: &AccountId,
+        currency_id_a: CurrencyId,
+        currency_id_b: CurrencyId,
+        max_amount_a: Balance,
+        max_amount_b: Balance,
+    ) -> sp_std::result::Result<Balance, DispatchError>;
 }
 
 impl<AccountId, CurrencyId, Balance> DexManager<AccountId, CurrencyId, Balance> for ()
@@ -82,4 +91,14 @@ impl<AccountId, CurrencyId, Balance> DexManager<AccountId, CurrencyId, Balance>
     ) -> sp_std::result::Result<Balance, DispatchError> {
         Ok(Default::default())
     }
+
+    fn add_liquidity(
+        _who: &AccountId,
+        _currency_id_a: CurrencyId,
+        _currency_id_b: CurrencyId,
+        _max_amount_a: Balance,
+        _max_amount_b: Balance,
+    ) -> sp_std::result::Result<Balance, DispatchError> {
+        Ok(Default::default())
+    }
 }
@@ -0,0 +1,193 @@
+//! Fuzzing harness for the dex state machine.
+//!
+//! `run` decodes an arbitrary byte string into a sequence of swaps, liquidity and listing
+//! calls against the mock runtime and checks after every call that no tokens were created
+//! or destroyed, that the dex account backs every pool and provision, and that swaps never
+//! shrink the product of the pool reserves. It is driven by seeded inputs in the unit tests
+//! and by `cargo fuzz` (see `fuzz/`).
+
+use super::*;
+use mock::{Dex, ExtBuilder, ListingOrigin, Origin, System, Test, Tokens, ALICE, BOB, NCAT, PLKT, WUSD};
+use sp_std::collections::btree_map::BTreeMap;
+
+const ACCOUNTS: [u128; 2] = [ALICE, BOB];
+const TOKENS: [CurrencyId; 3] = [WUSD, NCAT, PLKT];
+const MAX_OPS: usize = 64;
+
+/// reads the fuzzer input byte by byte, yielding zeroes once it is exhausted
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((b, rest)) => {
+                self.data = rest;
+                *b
+            }
+            None => 0,
+        }
+    }
+
+    fn account(&mut self) -> u128 {
+        ACCOUNTS[self.byte() as usize % ACCOUNTS.len()]
+    }
+
+    fn token(&mut self) -> CurrencyId {
+        TOKENS[self.byte() as usize % TOKENS.len()]
+    }
+
+    /// an amount spanning from dust to more than an account holds
+    fn amount(&mut self) -> Balance {
+        let mantissa = self.byte() as Balance + 1;
+        mantissa * 10u128.pow(self.byte() as u32 % 20)
+    }
+
+    /// a path of two or three tokens, possibly repeating one
+    fn path(&mut self) -> Vec<CurrencyId> {
+        let len = 2 + self.byte() as usize % 2;
+        (0..len).map(|_| self.token()).collect()
+    }
+}
+
+/// the tokens the dex account must hold, for the pools and the provisions
+fn dex_obligations() -> BTreeMap<CurrencyId, Balance> {
+    let mut obligations = BTreeMap::new();
+    for (pair, (pool_0, pool_1)) in LiquidityPool::<Test>::iter() {
+        *obligations.entry(pair.0).or_insert(0) += pool_0;
+        *obligations.entry(pair.1).or_insert(0) += pool_1;
+    }
+    for (pair, _, (provision_0, provision_1)) in ProvisioningPool::<Test>::iter() {
+        *obligations.entry(pair.0).or_insert(0) += provision_0;
+        *obligations.entry(pair.1).or_insert(0) += provision_1;
+    }
+    obligations
+}
+
+fn pool_products() -> BTreeMap<TradingPair, U256> {
+    LiquidityPool::<Test>::iter()
+        .map(|(pair, (pool_0, pool_1))| (pair, U256::from(pool_0) * U256::from(pool_1)))
+        .collect()
+}
+
+fn check_properties(issuance_at_genesis: &BTreeMap<CurrencyId, Balance>) {
+    for currency_id in TOKENS.iter() {
+        assert_eq!(
+            Tokens::total_issuance(*currency_id),
+            issuance_at_genesis[currency_id],
+            "{:?} was created or destroyed",
+            currency_id
+        );
+    }
+
+    let mut totals: BTreeMap<CurrencyId, Balance> = BTreeMap::new();
+    for (_, currency_id, data) in orml_tokens::Accounts::<Test>::iter() {
+        *totals.entry(currency_id).or_insert(0) += data.free + data.reserved;
+    }
+    for (currency_id, issuance) in orml_tokens::TotalIssuance::<Test>::iter() {
+        assert_eq!(
+            totals.get(&currency_id).copied().unwrap_or_default(),
+            issuance,
+            "account balances of {:?} do not add up to the issuance",
+            currency_id
+        );
+    }
+
+    let dex_account = Dex::account_id();
+    for (currency_id, obligation) in dex_obligations() {
+        assert!(
+            Tokens::free_balance(currency_id, &dex_account) >= obligation,
+            "the dex holds less {:?} than its pools and provisions",
+            currency_id
+        );
+    }
+}
+
+/// applies the call encoded at the head of the input, ignoring dispatch errors
+fn apply_call(input: &mut Input) {
+    let op = input.byte() % 8;
+    let products_before = pool_products();
+    let _ = match op {
+        0 => Dex::swap_with_exact_supply(Origin::signed(input.account()), input.path(), input.amount(), 0),
+        1 => Dex::swap_with_exact_target(
+            Origin::signed(input.account()),
+            input.path(),
+            input.amount(),
+            Balance::MAX,
+        ),
+        2 => Dex::add_liquidity(
+            Origin::signed(input.account()),
+            input.token(),
+            input.token(),
+            input.amount(),
+            input.amount(),
+        ),
+        3 => {
+            let who = input.account();
+            let (currency_id_a, currency_id_b) = (input.token(), input.token());
+            let share = TradingPair::from_token_currency_ids(currency_id_a, currency_id_b)
+                .and_then(|pair| pair.get_dex_share_currency_id())
+                .map_or(0, |share_id| Tokens::free_balance(share_id, &who));
+            let percent = input.byte() as Balance % 100 + 1;
+            Dex::remove_liquidity(Origin::signed(who), currency_id_a, currency_id_b, share * percent / 100)
+        }
+        4 => Dex::list_trading_pair(
+            Origin::signed(ListingOrigin::get()),
+            input.token(),
+            input.token(),
+            input.amount(),
+            input.amount(),
+            input.amount(),
+            input.amount(),
+            System::block_number() + input.byte() as u64 % 8,
+        ),
+        5 => Dex::enable_trading_pair(Origin::signed(ListingOrigin::get()), input.token(), input.token()),
+        6 => Dex::disable_trading_pair(Origin::signed(ListingOrigin::get()), input.token(), input.token()),
+        _ => {
+            System::set_block_number(System::block_number() + input.byte() as u64 % 8 + 1);
+            Ok(().into())
+        }
+    };
+
+    // swaps pay a fee into the pools, so the product of the reserves may only grow
+    if op < 2 {
+        let products_after = pool_products();
+        for (pair, before) in products_before {
+            assert!(
+                products_after.get(&pair).copied().unwrap_or_default() >= before,
+                "a swap shrank the {:?} pool",
+                pair
+            );
+        }
+    }
+}
+
+/// runs the calls encoded by `data` against a fresh mock runtime, panicking on the first
+/// property that does not hold
+pub fn run(data: &[u8]) {
+    let mut input = Input { data };
+    let mut builder = ExtBuilder::default();
+    if input.byte() % 2 == 0 {
+        builder = builder.initialize_enabled_trading_pairs().initialize_added_liquidity_pools(ALICE);
+    } else {
+        builder = builder.initialize_listing_trading_pairs();
+    }
+
+    builder.build().execute_with(|| {
+        System::set_block_number(1);
+        let issuance_at_genesis: BTreeMap<CurrencyId, Balance> =
+            TOKENS.iter().map(|currency_id| (*currency_id, Tokens::total_issuance(*currency_id))).collect();
+        for _ in 0..MAX_OPS {
+            if input.is_empty() {
+                break;
+            }
+            apply_call(&mut input);
+            check_properties(&issuance_at_genesis);
+        }
+    });
+}
@@ -3,6 +3,8 @@
 use common::{DexManager, Price, Ratio};
 use frame_support::{pallet_prelude::*, transactional};
 use frame_system::pallet_prelude::*;
+#[cfg(feature = "std")]
+use node_type_registry::TypeInfo;
 use orml_traits::{MultiCurrency, MultiCurrencyExtended};
 use primitives::{Balance, CurrencyId};
 #[cfg(feature = "std")]
@@ -14,12 +16,15 @@ use sp_runtime::{
 };
 use sp_std::{convert::TryInto, prelude::*};
 
-#[cfg(test)]
-mod mock;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
@@ -27,7 +32,7 @@ pub mod weights;
 use weights::WeightInfo;
 
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, PartialOrd, Ord)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
 pub struct TradingPair(pub CurrencyId, pub CurrencyId);
 
 impl TradingPair {
@@ -61,6 +66,7 @@ impl TradingPair {
 
 /// Parameters of TradingPair in Provisioning status
 #[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub struct TradingPairProvisionParameters<Balance, BlockNumber> {
     /// limit contribution per time.
     min_contribution: (Balance, Balance),
@@ -74,6 +80,7 @@ pub struct TradingPairProvisionParameters<Balance, BlockNumber> {
 
 /// Status for TradingPair
 #[derive(Clone, Copy, Encode, Decode, RuntimeDebug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
 pub enum TradingPairStatus<Balance, BlockNumber> {
     /// Default status,
     /// can withdraw liquidity, re-enable and list this trading pair.
@@ -1193,4 +1200,19 @@ impl<T: Config> DexManager<T::AccountId, CurrencyId, Balance> for Pallet<T> {
             price_impact_limit,
         )
     }
+
+    fn add_liquidity(
+        who: &T::AccountId,
+        currency_id_a: CurrencyId,
+        currency_id_b: CurrencyId,
+        max_amount_a: Balance,
+        max_amount_b: Balance,
+    ) -> sp_std::result::Result<Balance, DispatchError> {
+        let lp_share_currency_id = TradingPair::new(currency_id_a, currency_id_b)
+            .get_dex_share_currency_id()
+            .ok_or(Error::<T>::InvalidCurrencyId)?;
+        let share = T::Currency::free_balance(lp_share_currency_id, who);
+        Self::do_add_liquidity(who, currency_id_a, currency_id_b, max_amount_a, max_amount_b)?;
+        Ok(T::Currency::free_balance(lp_share_currency_id, who).saturating_sub(share))
+    }
 }
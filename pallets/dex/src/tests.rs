@@ -1448,3 +1448,13 @@ fn sync_event_emits_correctly() {
                 .any(|record| record.event == remove_liquidity_sync_event));
         });
 }
+
+#[test]
+fn fuzzing_harness_holds_on_seeded_inputs() {
+    for seed in 0u64..200 {
+        let input: Vec<u8> = (0u64..8)
+            .flat_map(|chunk| sp_core::blake2_256(&[seed.to_le_bytes(), chunk.to_le_bytes()].concat()).to_vec())
+            .collect();
+        fuzzing::run(&input);
+    }
+}
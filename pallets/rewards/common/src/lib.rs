@@ -0,0 +1,49 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode, HasCompact};
+use primitives::{Balance, CurrencyId};
+use sp_runtime::RuntimeDebug;
+use sp_std::collections::btree_map::BTreeMap;
+
+#[cfg(feature = "std")]
+use node_type_registry::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub type YieldFarmingScheduleIndex = u32;
+
+/// PoolId for various rewards pools
+#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
+pub enum PoolId {
+    /// lp tokens deposited by deposit_dex_share
+    DexYieldFarming(CurrencyId),
+    /// tokens staked directly by stake
+    Staking(CurrencyId),
+}
+
+/// The Reward Pool Info.
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Default, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
+pub struct PoolInfo<Balance: HasCompact> {
+    /// Total shares amount
+    #[codec(compact)]
+    pub total_shares: Balance,
+    /// Total rewards and total withdrawn rewards, by reward currency
+    pub rewards: BTreeMap<CurrencyId, (Balance, Balance)>,
+}
+
+/// Rewards paid into a pool by a sponsor, `amount` every `interval` blocks from `start_blk`
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
+pub struct YieldFarmingSchedule<AccountId, BlockNumber> {
+    pub sponsor: AccountId,
+    pub pool_id: PoolId,
+    pub currency_id: CurrencyId,
+    pub amount: Balance,
+    pub start_blk: BlockNumber,
+    pub interval: BlockNumber,
+    pub repeats: u8,
+    /// repetitions already added to the pool
+    pub released: u8,
+}
@@ -0,0 +1,34 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+use codec::Codec;
+use primitives::{Balance, CurrencyId};
+use sp_runtime::FixedU128;
+use sp_std::vec::Vec;
+use pallet_rewards_primitives::*;
+
+sp_api::decl_runtime_apis! {
+    pub trait RewardsApi<AccountId, BlockNumber> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        fn get_yield_farming_schedules_of_account(
+            account: AccountId
+        ) -> Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>;
+
+        fn get_yield_farming_schedules_of_pool(
+            pool_id: PoolId
+        ) -> Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>;
+
+        fn get_pending_rewards(
+            account: AccountId,
+            pool_id: PoolId,
+        ) -> Vec<(CurrencyId, Balance)>;
+
+        fn get_pool_info(
+            pool_id: PoolId
+        ) -> PoolInfo<Balance>;
+
+        fn get_yield_farming_apr(
+            pool_id: PoolId
+        ) -> Vec<(CurrencyId, FixedU128)>;
+    }
+}
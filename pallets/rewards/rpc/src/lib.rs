@@ -0,0 +1,182 @@
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, FixedU128};
+use std::sync::Arc;
+
+use pallet_rewards_primitives::*;
+use primitives::{Balance, BlockNumber, CurrencyId};
+pub use pallet_rewards_rpc_runtime_api::RewardsApi as RewardsRuntimeApi;
+use sp_std::vec::Vec;
+
+pub enum Error {
+    RuntimeError,
+}
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+#[rpc]
+pub trait RewardsApi<BlockHash, AccountId> {
+    #[rpc(name = "rewards_getYieldFarmingSchedulesOfAccount")]
+    fn get_yield_farming_schedules_of_account(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>>;
+
+    #[rpc(name = "rewards_getYieldFarmingSchedulesOfPool")]
+    fn get_yield_farming_schedules_of_pool(
+        &self,
+        pool_id: PoolId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>>;
+
+    #[rpc(name = "rewards_getPendingRewards")]
+    fn get_pending_rewards(
+        &self,
+        account: AccountId,
+        pool_id: PoolId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(CurrencyId, Balance)>>;
+
+    #[rpc(name = "rewards_getPoolInfo")]
+    fn get_pool_info(
+        &self,
+        pool_id: PoolId,
+        at: Option<BlockHash>,
+    ) -> Result<PoolInfo<Balance>>;
+
+    #[rpc(name = "rewards_getYieldFarmingApr")]
+    fn get_yield_farming_apr(
+        &self,
+        pool_id: PoolId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(CurrencyId, FixedU128)>>;
+}
+
+/// An implementation of rewards specific RPC methods
+pub struct Rewards<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Rewards<C, B> {
+    /// Create new `Rewards` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Rewards {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, AccountId> RewardsApi<<Block as BlockT>::Hash, AccountId> for Rewards<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: RewardsRuntimeApi<Block, AccountId, BlockNumber>,
+    AccountId: Codec,
+{
+    fn get_yield_farming_schedules_of_account(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_yield_farming_schedules_of_account(&at, account)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get yield farming schedules from account.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_yield_farming_schedules_of_pool(
+        &self,
+        pool_id: PoolId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<AccountId, BlockNumber>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_yield_farming_schedules_of_pool(&at, pool_id)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get yield farming schedules of pool.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_pending_rewards(
+        &self,
+        account: AccountId,
+        pool_id: PoolId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(CurrencyId, Balance)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_pending_rewards(&at, account, pool_id)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get pending rewards.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_pool_info(
+        &self,
+        pool_id: PoolId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<PoolInfo<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_pool_info(&at, pool_id)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get pool info.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_yield_farming_apr(
+        &self,
+        pool_id: PoolId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(CurrencyId, FixedU128)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or(
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash,
+        ));
+
+        api.get_yield_farming_apr(&at, pool_id)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(Error::RuntimeError.into()),
+                message: "Unable to get yield farming apr of pool.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+}
@@ -0,0 +1,379 @@
+use super::*;
+use frame_benchmarking::{account, benchmarks};
+use frame_system::RawOrigin;
+use primitives::{Balance, CurrencyId, TokenSymbol};
+
+use crate::Module as Rewards;
+
+const SEED: u32 = 0;
+pub const BOLT: CurrencyId = CurrencyId::Token(TokenSymbol::BOLT);
+pub const WUSD: CurrencyId = CurrencyId::Token(TokenSymbol::WUSD);
+pub const BOLT_WUSD_LP: CurrencyId = CurrencyId::DexShare(TokenSymbol::BOLT, TokenSymbol::WUSD);
+pub const BOLT_WUSD_POOL: PoolId = PoolId::DexYieldFarming(BOLT_WUSD_LP);
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+    let caller: T::AccountId = account(name, index, SEED);
+    assert!(
+        T::Currency::update_balance(BOLT, &caller, Balance::MAX.unique_saturated_into()).is_ok()
+    );
+    assert!(
+        T::Currency::update_balance(BOLT_WUSD_LP, &caller, Balance::MAX.unique_saturated_into()).is_ok()
+    );
+    caller
+}
+
+/// the pool pays rewards in BOLT and WUSD
+fn funded_pool_rewards<T: Config>(pool_id: PoolId) -> Result<(), &'static str> {
+    let reward = T::MinimumYieldFarmingReward::get().saturating_mul(100);
+    for currency_id in vec![BOLT, WUSD] {
+        T::Currency::update_balance(currency_id, &Rewards::<T>::account_id(), reward.unique_saturated_into())?;
+        Rewards::<T>::add_reward(pool_id, currency_id, reward);
+    }
+    Ok(())
+}
+
+/// the staker holds a share of the pool, with rewards left to claim
+fn funded_deposit_with_rewards<T: Config>(staker: T::AccountId, share: Balance) -> Result<(), &'static str> {
+    Rewards::<T>::deposit_dex_share(RawOrigin::Signed(staker).into(), BOLT_WUSD_LP, share)?;
+    funded_pool_rewards::<T>(BOLT_WUSD_POOL)
+}
+
+/// a yield farming pool other than BOLT_WUSD_POOL, distinct for every index below 20
+fn emission_pool(i: u32) -> PoolId {
+    let symbols = [TokenSymbol::BOLT, TokenSymbol::WUSD, TokenSymbol::BBOT, TokenSymbol::NCAT, TokenSymbol::PLKT];
+    PoolId::DexYieldFarming(CurrencyId::DexShare(symbols[(1 + i / 5 % 4) as usize], symbols[(i % 5) as usize]))
+}
+
+benchmarks! {
+    deposit_dex_share {
+        let caller = funded_account::<T>("caller", 0);
+        let share: Balance = 1_000_000_000_000;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_LP, share)
+    verify{
+        assert_eq!(Rewards::<T>::share_and_withdrawn_reward(PoolId::DexYieldFarming(BOLT_WUSD_LP), caller).0, share);
+    }
+
+    withdraw_dex_share {
+        let caller = funded_account::<T>("caller", 0);
+        let share: Balance = 1_000_000_000_000;
+        funded_deposit_with_rewards::<T>(caller.clone(), share)?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_LP, share)
+    verify{
+        assert_eq!(Rewards::<T>::share_and_withdrawn_reward(PoolId::DexYieldFarming(BOLT_WUSD_LP), caller).0, 0);
+    }
+
+    claim_reward {
+        let caller = funded_account::<T>("caller", 0);
+        let share: Balance = 1_000_000_000_000;
+        funded_deposit_with_rewards::<T>(caller.clone(), share)?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_POOL)
+    verify{
+        assert_eq!(Rewards::<T>::share_and_withdrawn_reward(BOLT_WUSD_POOL, caller).1.len(), 2);
+    }
+
+    schedule_yield_farming_rewards {
+        let r in 1 .. 50;
+        let caller = funded_account::<T>("caller", 0);
+        let amount = T::MinimumYieldFarmingReward::get().saturating_add(1);
+        let start = <frame_system::Module<T>>::block_number() + T::StartDelay::get();
+        let rewards: Vec<(Balance, T::BlockNumber, T::BlockNumber, u8)> = (0..r)
+            .map(|i| (amount, start + i.into(), 10u32.into(), 10))
+            .collect();
+    }: _(RawOrigin::Signed(caller), BOLT_WUSD_POOL, BOLT, rewards)
+    verify{
+        assert_eq!(
+            T::Currency::free_balance(BOLT, &Rewards::<T>::account_id()),
+            amount.saturating_mul(10).saturating_mul(r.into())
+        );
+    }
+
+    add_yield_farming_reward {
+        let amount = T::MinimumYieldFarmingReward::get().saturating_add(1);
+    }: _(RawOrigin::Root, BOLT_WUSD_LP, amount)
+    verify{
+        assert_eq!(Rewards::<T>::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(amount, 0)));
+    }
+
+    stake {
+        let caller = funded_account::<T>("caller", 0);
+        let amount: Balance = 1_000_000_000_000;
+        funded_pool_rewards::<T>(PoolId::Staking(BOLT))?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT, amount)
+    verify{
+        assert_eq!(Rewards::<T>::share_and_withdrawn_reward(PoolId::Staking(BOLT), caller).0, amount);
+    }
+
+    unstake {
+        let caller = funded_account::<T>("caller", 0);
+        let amount: Balance = 1_000_000_000_000;
+        Rewards::<T>::stake(RawOrigin::Signed(caller.clone()).into(), BOLT, amount)?;
+        funded_pool_rewards::<T>(PoolId::Staking(BOLT))?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT, amount)
+    verify{
+        assert_eq!(Rewards::<T>::share_and_withdrawn_reward(PoolId::Staking(BOLT), caller).0, 0);
+    }
+
+    lock_dex_share {
+        let caller = funded_account::<T>("caller", 0);
+        let share: Balance = 1_000_000_000_000;
+        funded_deposit_with_rewards::<T>(caller.clone(), share)?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_LP, share, T::MaxLockPeriod::get())
+    verify{
+        assert!(Rewards::<T>::dex_share_locks(BOLT_WUSD_LP, caller).is_some());
+    }
+
+    unlock_dex_share {
+        let caller = funded_account::<T>("caller", 0);
+        let share: Balance = 1_000_000_000_000;
+        Rewards::<T>::deposit_dex_share(RawOrigin::Signed(caller.clone()).into(), BOLT_WUSD_LP, share)?;
+        Rewards::<T>::lock_dex_share(RawOrigin::Signed(caller.clone()).into(), BOLT_WUSD_LP, share, T::MaxLockPeriod::get())?;
+        funded_pool_rewards::<T>(BOLT_WUSD_POOL)?;
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_LP)
+    verify{
+        assert_eq!(
+            Rewards::<T>::share_and_withdrawn_reward(BOLT_WUSD_POOL, caller).0,
+            share - T::EarlyUnlockFee::get() * share
+        );
+    }
+
+    decay_dex_share_locks {
+        let n in 1 .. T::MaxLockDecaysPerBlock::get();
+        let share: Balance = 1_000_000_000_000;
+        let funder = funded_account::<T>("funder", 0);
+        for i in 0..n {
+            let caller: T::AccountId = account("caller", i, SEED);
+            T::Currency::transfer(BOLT_WUSD_LP, &funder, &caller, share)?;
+            Rewards::<T>::deposit_dex_share(RawOrigin::Signed(caller.clone()).into(), BOLT_WUSD_LP, share)?;
+            Rewards::<T>::lock_dex_share(RawOrigin::Signed(caller).into(), BOLT_WUSD_LP, share, T::MaxLockPeriod::get())?;
+        }
+        funded_pool_rewards::<T>(BOLT_WUSD_POOL)?;
+        let decay_blk = <frame_system::Module<T>>::block_number() + T::LockDecayPeriod::get();
+    }: { Rewards::<T>::decay_dex_share_locks(decay_blk); }
+    verify{
+        let caller: T::AccountId = account("caller", n - 1, SEED);
+        assert_eq!(Rewards::<T>::dex_share_locks(BOLT_WUSD_LP, caller).map(|lock| lock.next_decay_blk), Some(decay_blk + T::LockDecayPeriod::get()));
+    }
+
+    release_yield_farming_reward {
+        let caller = funded_account::<T>("caller", 0);
+        let amount = T::MinimumYieldFarmingReward::get().saturating_add(1);
+        let start = <frame_system::Module<T>>::block_number() + T::StartDelay::get();
+        Rewards::<T>::schedule_yield_farming_rewards(
+            RawOrigin::Signed(caller).into(),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(amount, start, 10u32.into(), 10)]
+        )?;
+    }: _(RawOrigin::Root, 0)
+    verify{
+        assert_eq!(Rewards::<T>::yield_farming_schedules(0).map(|schedule| schedule.released), Some(1));
+        assert_eq!(Rewards::<T>::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(amount, 0)));
+    }
+
+    set_auto_compound {
+        let caller = funded_account::<T>("caller", 0);
+    }: _(RawOrigin::Signed(caller.clone()), BOLT_WUSD_LP, true)
+    verify{
+        assert!(Rewards::<T>::auto_compounds(BOLT_WUSD_LP, caller).is_some());
+    }
+
+    auto_compound {
+        let n in 1 .. T::MaxAutoCompoundsPerBlock::get();
+        let share: Balance = 1_000_000_000_000;
+        let funder = funded_account::<T>("funder", 0);
+        for i in 0..n {
+            let caller: T::AccountId = account("caller", i, SEED);
+            T::Currency::transfer(BOLT_WUSD_LP, &funder, &caller, share)?;
+            Rewards::<T>::deposit_dex_share(RawOrigin::Signed(caller.clone()).into(), BOLT_WUSD_LP, share)?;
+            Rewards::<T>::set_auto_compound(RawOrigin::Signed(caller).into(), BOLT_WUSD_LP, true)?;
+        }
+        funded_pool_rewards::<T>(BOLT_WUSD_POOL)?;
+        let caller: T::AccountId = account("caller", n - 1, SEED);
+        let compound_blk = Rewards::<T>::auto_compounds(BOLT_WUSD_LP, &caller).unwrap_or_default();
+    }: { Rewards::<T>::auto_compound(compound_blk); }
+    verify{
+        assert_ne!(Rewards::<T>::auto_compounds(BOLT_WUSD_LP, caller), Some(compound_blk));
+    }
+
+    set_emission_schedule {
+        let schedule = EmissionSchedule {
+            source: EmissionSource::Mint,
+            initial_emission: 1_000_000_000_000,
+            curve: EmissionCurve::Halving(T::AccumulatePeriod::get()),
+            start_blk: T::AccumulatePeriod::get(),
+            cap: Balance::MAX,
+        };
+    }: _(RawOrigin::Root, Some(schedule.clone()))
+    verify{
+        assert_eq!(Rewards::<T>::emission(), Some(schedule));
+    }
+
+    set_emission_pool_weight {
+        for i in 1..T::MaxEmissionPools::get() {
+            Rewards::<T>::set_emission_pool_weight(RawOrigin::Root.into(), emission_pool(i), 1)?;
+        }
+    }: _(RawOrigin::Root, BOLT_WUSD_POOL, 1)
+    verify{
+        assert_eq!(Rewards::<T>::emission_pool_weights(BOLT_WUSD_POOL), 1);
+    }
+
+    emit {
+        let n in 1 .. T::MaxEmissionPools::get();
+        for i in 0..n {
+            Rewards::<T>::set_emission_pool_weight(RawOrigin::Root.into(), emission_pool(i), 1)?;
+        }
+        Rewards::<T>::set_emission_schedule(RawOrigin::Root.into(), Some(EmissionSchedule {
+            source: EmissionSource::Mint,
+            initial_emission: 1_000_000_000_000,
+            curve: EmissionCurve::Constant,
+            start_blk: Zero::zero(),
+            cap: Balance::MAX,
+        }))?;
+        let emission_blk = T::AccumulatePeriod::get();
+    }: { Rewards::<T>::emit(emission_blk); }
+    verify{
+        assert!(!Rewards::<T>::total_emitted().is_zero());
+    }
+
+    cancel_yield_farming_schedule {
+        let caller = funded_account::<T>("caller", 0);
+        let amount = T::MinimumYieldFarmingReward::get().saturating_add(1);
+        let start = <frame_system::Module<T>>::block_number() + T::StartDelay::get();
+        Rewards::<T>::schedule_yield_farming_rewards(
+            RawOrigin::Signed(caller.clone()).into(),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(amount, start, 10u32.into(), 10)]
+        )?;
+    }: _(RawOrigin::Signed(caller.clone()), 0)
+    verify{
+        assert!(Rewards::<T>::yield_farming_schedules(0).is_none());
+        assert_eq!(T::Currency::free_balance(BOLT, &Rewards::<T>::account_id()), 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{ExtBuilder, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn deposit_dex_share() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_deposit_dex_share::<Test>());
+        });
+    }
+
+    #[test]
+    fn withdraw_dex_share() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_withdraw_dex_share::<Test>());
+        });
+    }
+
+    #[test]
+    fn claim_reward() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_claim_reward::<Test>());
+        });
+    }
+
+    #[test]
+    fn schedule_yield_farming_rewards() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_schedule_yield_farming_rewards::<Test>());
+        });
+    }
+
+    #[test]
+    fn add_yield_farming_reward() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_add_yield_farming_reward::<Test>());
+        });
+    }
+
+    #[test]
+    fn stake() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_stake::<Test>());
+        });
+    }
+
+    #[test]
+    fn unstake() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_unstake::<Test>());
+        });
+    }
+
+    #[test]
+    fn lock_dex_share() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_lock_dex_share::<Test>());
+        });
+    }
+
+    #[test]
+    fn unlock_dex_share() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_unlock_dex_share::<Test>());
+        });
+    }
+
+    #[test]
+    fn decay_dex_share_locks() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_decay_dex_share_locks::<Test>());
+        });
+    }
+
+    #[test]
+    fn release_yield_farming_reward() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_release_yield_farming_reward::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_auto_compound() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_set_auto_compound::<Test>());
+        });
+    }
+
+    #[test]
+    fn auto_compound() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_auto_compound::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_emission_schedule() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_set_emission_schedule::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_emission_pool_weight() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_set_emission_pool_weight::<Test>());
+        });
+    }
+
+    #[test]
+    fn emit() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_emit::<Test>());
+        });
+    }
+
+    #[test]
+    fn cancel_yield_farming_schedule() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(test_benchmark_cancel_yield_farming_schedule::<Test>());
+        });
+    }
+}
@@ -13,12 +13,14 @@ use frame_support::{
 use frame_support::{pallet_prelude::*, transactional};
 use frame_system::pallet_prelude::*;
 use frame_system::{self as system, ensure_root, ensure_signed};
+#[cfg(feature = "std")]
+use node_type_registry::TypeInfo;
 use orml_traits::{MultiCurrency, MultiCurrencyExtended};
 use primitives::{Balance, CurrencyId, TokenSymbol};
-use sp_runtime::traits::{Dispatchable, UniqueSaturatedInto, Zero};
-use sp_runtime::{traits::AccountIdConversion, DispatchResult, RuntimeDebug};
-use sp_runtime::{FixedPointNumber, FixedU128, ModuleId};
-use sp_std::prelude::*;
+use sp_runtime::traits::{Dispatchable, One, Saturating, UniqueSaturatedInto, Zero};
+use sp_runtime::{traits::AccountIdConversion, DispatchError, DispatchResult, RuntimeDebug};
+use sp_runtime::{FixedPointNumber, FixedU128, ModuleId, Permill};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
 #[cfg(test)]
 mod mock;
@@ -26,24 +28,57 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
-/// PoolId for various rewards pools
-#[derive(Encode, Decode, Copy, Clone, PartialEq, Eq, RuntimeDebug)]
-pub enum PoolId {
-    DexYieldFarming(CurrencyId),
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migration;
+pub mod weights;
+use weights::WeightInfo;
+
+pub use pallet_rewards_primitives::{PoolId, PoolInfo, YieldFarmingSchedule, YieldFarmingScheduleIndex};
+
+/// LP shares locked in a yield farming pool. `boost` is the extra share counted for the lock,
+/// decaying every `LockDecayPeriod` until it is gone at `expiry_blk`
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct DexShareLock<BlockNumber> {
+    pub amount: Balance,
+    pub boost: Balance,
+    pub expiry_blk: BlockNumber,
+    pub next_decay_blk: BlockNumber,
 }
 
-/// The Reward Pool Info.
-#[derive(Clone, Encode, Decode, PartialEq, Eq, Default, RuntimeDebug)]
-pub struct PoolInfo<Balance: HasCompact> {
-    /// Total shares amount
-    #[codec(compact)]
-    pub total_shares: Balance,
-    /// Total rewards amount
-    #[codec(compact)]
-    pub total_rewards: Balance,
-    /// Total withdrawn rewards amount
-    #[codec(compact)]
-    pub total_withdrawn_rewards: Balance,
+/// where the emissions are taken from
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub enum EmissionSource {
+    /// newly issued
+    Mint,
+    /// transferred from the EmissionTreasury account
+    Treasury,
+}
+
+/// how the emission of an AccumulatePeriod drops over time
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub enum EmissionCurve<BlockNumber> {
+    Constant,
+    /// halves every period
+    Halving(BlockNumber),
+    /// drops by the part every period
+    Decay(BlockNumber, Permill),
+}
+
+/// emission of `initial_emission` every AccumulatePeriod from `start_blk`, following the curve,
+/// until `cap` is emitted in total, by this schedule and the previous ones
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(TypeInfo))]
+pub struct EmissionSchedule<BlockNumber> {
+    pub source: EmissionSource,
+    pub initial_emission: Balance,
+    pub curve: EmissionCurve<BlockNumber>,
+    pub start_blk: BlockNumber,
+    pub cap: Balance,
 }
 
 pub use module::*;
@@ -68,6 +103,7 @@ pub mod module {
         #[pallet::constant]
         type StartDelay: Get<Self::BlockNumber>;
 
+        /// how often the rewards of auto-compounding accounts are compounded
         #[pallet::constant]
         type AccumulatePeriod: Get<Self::BlockNumber>;
 
@@ -79,6 +115,60 @@ pub mod module {
         type Scheduler: ScheduleNamed<Self::BlockNumber, Self::ReleaseReward, Self::PalletsOrigin>;
 
         type PalletsOrigin: From<system::RawOrigin<Self::AccountId>>;
+
+        /// longest lock of dex shares, earning the full `MaxLockBoost`
+        #[pallet::constant]
+        type MaxLockPeriod: Get<Self::BlockNumber>;
+
+        /// shortest lock of dex shares, and how often the boost of a lock decays
+        #[pallet::constant]
+        type LockDecayPeriod: Get<Self::BlockNumber>;
+
+        /// most locks decayed in a block, the rest wait for the next block
+        #[pallet::constant]
+        type MaxLockDecaysPerBlock: Get<u32>;
+
+        /// extra share of a lock for `MaxLockPeriod`, as a part of the locked amount
+        #[pallet::constant]
+        type MaxLockBoost: Get<Permill>;
+
+        /// part of the locked dex shares taken on unlocking them before the lock expires,
+        /// transferred to the EmissionTreasury account
+        #[pallet::constant]
+        type EarlyUnlockFee: Get<Permill>;
+
+        /// blocks in a year, to estimate the apr of the pools
+        #[pallet::constant]
+        type BlocksPerYear: Get<Self::BlockNumber>;
+
+        /// part of the auto-compounded rewards taken as performance fee
+        #[pallet::constant]
+        type AutoCompoundFee: Get<Permill>;
+
+        /// account receiving the auto-compound performance fee
+        type AutoCompoundFeeReceiver: Get<Self::AccountId>;
+
+        /// most accounts compounded in a block, the rest wait for the next block
+        #[pallet::constant]
+        type MaxAutoCompoundsPerBlock: Get<u32>;
+
+        /// most a swap of auto-compound can get below the pool price. the compounding of an
+        /// account is skipped until the next AccumulatePeriod beyond it
+        #[pallet::constant]
+        type AutoCompoundMaxSlippage: Get<Permill>;
+
+        /// currency of the emissions funding the pools
+        #[pallet::constant]
+        type EmissionCurrencyId: Get<CurrencyId>;
+
+        /// account the emissions are transferred from under EmissionSource::Treasury
+        type EmissionTreasury: Get<Self::AccountId>;
+
+        /// most pools sharing the emissions
+        #[pallet::constant]
+        type MaxEmissionPools: Get<u32>;
+
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::error]
@@ -93,6 +183,30 @@ pub mod module {
         RewardTooSmall,
         /// empty rewards in vector
         EmptyRewards,
+        /// the pool does not take deposits or rewards by this call
+        InvalidPoolId,
+        /// no unfinished yield farming schedule under this index
+        InvalidScheduleIndex,
+        /// only the sponsor of a schedule can cancel it
+        NotScheduleSponsor,
+        /// the scheduled task could not be cancelled
+        CancelScheduleFailed,
+        /// lock period shorter than LockDecayPeriod or longer than MaxLockPeriod
+        InvalidLockPeriod,
+        /// the dex shares of the account are already locked
+        DexShareAlreadyLocked,
+        /// the account has no locked dex shares
+        DexShareNotLocked,
+        /// emission curve with a zero period, or zero emission
+        InvalidEmissionSchedule,
+        /// more than MaxEmissionPools pools with an emission weight
+        TooManyEmissionPools,
+        /// zero repetitions, or several repetitions with a zero interval
+        InvalidRepetitions,
+        /// every repetition of the schedule has been released
+        ScheduleFinished,
+        /// the swap would get more than AutoCompoundMaxSlippage below the pool price
+        SlippageTooHigh,
     }
 
     #[pallet::event]
@@ -102,16 +216,46 @@ pub mod module {
         DepositDexShare(T::AccountId, CurrencyId, Balance),
         /// Withdraw Dex share. \[who, dex_share_type, withdraw_amount\]
         WithdrawDexShare(T::AccountId, CurrencyId, Balance),
+        /// Stake token. \[who, currency_id, amount\]
+        Staked(T::AccountId, CurrencyId, Balance),
+        /// Unstake token. \[who, currency_id, amount\]
+        Unstaked(T::AccountId, CurrencyId, Balance),
         // Schedule Failed
         ScheduleFailed(T::BlockNumber),
-        // New Yield Farming Reward
-        YieldFarmingReward(PoolId, Balance),
+        // New Yield Farming Reward. \[pool_id, reward_currency_id, amount\]
+        YieldFarmingReward(PoolId, CurrencyId, Balance),
+        /// Yield farming rewards scheduled. \[sponsor, schedule_idx, pool_id, reward_currency_id\]
+        YieldFarmingScheduled(T::AccountId, YieldFarmingScheduleIndex, PoolId, CurrencyId),
+        /// Yield farming schedule cancelled. \[sponsor, schedule_idx, refund\]
+        YieldFarmingScheduleCancelled(T::AccountId, YieldFarmingScheduleIndex, Balance),
+        /// Dex share locked. \[who, dex_share_type, amount, boost, expiry_blk\]
+        DexShareLocked(T::AccountId, CurrencyId, Balance, Balance, T::BlockNumber),
+        /// Dex share lock expired. \[who, dex_share_type, amount\]
+        DexShareUnlocked(T::AccountId, CurrencyId, Balance),
+        /// Dex share unlocked before expiry, forfeiting the pending rewards and paying the fee.
+        /// \[who, dex_share_type, amount, fee\]
+        DexShareUnlockedEarly(T::AccountId, CurrencyId, Balance, Balance),
+        /// Auto-compound switched on or off. \[who, dex_share_type, enabled\]
+        AutoCompoundSet(T::AccountId, CurrencyId, bool),
+        /// Rewards compounded into the pool. \[who, dex_share_type, share\]
+        AutoCompounded(T::AccountId, CurrencyId, Balance),
+        /// Rewards could not be compounded and were left pending. \[who, dex_share_type\]
+        AutoCompoundFailed(T::AccountId, CurrencyId),
+        /// Emission schedule set, or removed if None. \[schedule\]
+        EmissionScheduleSet(Option<EmissionSchedule<T::BlockNumber>>),
+        /// Emission weight of a pool set. \[pool_id, weight\]
+        EmissionPoolWeightSet(PoolId, u32),
+        /// Emissions added to the pools. \[block, amount, total_emitted\]
+        Emitted(T::BlockNumber, Balance, Balance),
+        /// Emissions could not be funded from the source. \[block, amount\]
+        EmissionFailed(T::BlockNumber, Balance),
     }
 
     #[pallet::storage]
     #[pallet::getter(fn pools)]
     pub type Pools<T: Config> = StorageMap<_, Twox64Concat, PoolId, PoolInfo<Balance>, ValueQuery>;
 
+    /// share of an account in a pool, and its withdrawn rewards by reward currency
     #[pallet::storage]
     #[pallet::getter(fn share_and_withdrawn_reward)]
     pub type ShareAndWithdrawnReward<T: Config> = StorageDoubleMap<
@@ -120,19 +264,100 @@ pub mod module {
         PoolId,
         Twox64Concat,
         T::AccountId,
-        (Balance, Balance),
+        (Balance, BTreeMap<CurrencyId, Balance>),
         ValueQuery,
     >;
 
+    #[pallet::storage]
+    #[pallet::getter(fn yield_farming_schedule_count)]
+    pub type YieldFarmingScheduleCount<T: Config> = StorageValue<_, YieldFarmingScheduleIndex, ValueQuery>;
+
+    /// unfinished yield farming schedules, removed once every repetition is released or cancelled
+    #[pallet::storage]
+    #[pallet::getter(fn yield_farming_schedules)]
+    pub type YieldFarmingSchedules<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        YieldFarmingScheduleIndex,
+        YieldFarmingSchedule<T::AccountId, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn sponsor_yield_farming_schedules)]
+    pub type SponsorYieldFarmingSchedules<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, Vec<YieldFarmingScheduleIndex>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dex_share_locks)]
+    pub type DexShareLocks<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        CurrencyId,
+        Twox64Concat,
+        T::AccountId,
+        DexShareLock<T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    /// locks whose boost decays at the block
+    #[pallet::storage]
+    #[pallet::getter(fn dex_share_lock_queue)]
+    pub type DexShareLockQueue<T: Config> =
+        StorageMap<_, Twox64Concat, T::BlockNumber, Vec<(CurrencyId, T::AccountId)>, ValueQuery>;
+
+    /// next block the rewards of an auto-compounding account are compounded
+    #[pallet::storage]
+    #[pallet::getter(fn auto_compounds)]
+    pub type AutoCompounds<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        CurrencyId,
+        Twox64Concat,
+        T::AccountId,
+        T::BlockNumber,
+        OptionQuery,
+    >;
+
+    /// accounts whose rewards are compounded at the block
+    #[pallet::storage]
+    #[pallet::getter(fn auto_compound_queue)]
+    pub type AutoCompoundQueue<T: Config> =
+        StorageMap<_, Twox64Concat, T::BlockNumber, Vec<(CurrencyId, T::AccountId)>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn emission)]
+    pub type Emission<T: Config> = StorageValue<_, EmissionSchedule<T::BlockNumber>, OptionQuery>;
+
+    /// emitted by all the schedules, up to the cap of the current one
+    #[pallet::storage]
+    #[pallet::getter(fn total_emitted)]
+    pub type TotalEmitted<T: Config> = StorageValue<_, Balance, ValueQuery>;
+
+    /// share of the emissions going to a pool, out of the sum of the weights
+    #[pallet::storage]
+    #[pallet::getter(fn emission_pool_weights)]
+    pub type EmissionPoolWeights<T: Config> = StorageMap<_, Twox64Concat, PoolId, u32, ValueQuery>;
+
     #[pallet::pallet]
     pub struct Pallet<T>(PhantomData<T>);
 
     #[pallet::hooks]
-    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            Self::decay_dex_share_locks(now)
+                .saturating_add(Self::auto_compound(now))
+                .saturating_add(Self::emit(now))
+        }
+
+        fn on_runtime_upgrade() -> frame_support::weights::Weight {
+            migration::migrate_to_v2_1::<T>()
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::deposit_dex_share())]
         #[transactional]
         pub fn deposit_dex_share(
             origin: OriginFor<T>,
@@ -144,7 +369,7 @@ pub mod module {
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
+        #[pallet::weight(<T as Config>::WeightInfo::withdraw_dex_share())]
         #[transactional]
         pub fn withdraw_dex_share(
             origin: OriginFor<T>,
@@ -156,26 +381,25 @@ pub mod module {
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
+        /// claims the rewards of the pool in every reward currency
+        #[pallet::weight(<T as Config>::WeightInfo::claim_reward())]
         #[transactional]
         pub fn claim_reward(
             origin: OriginFor<T>,
-            lp_token: CurrencyId,
+            pool_id: PoolId,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
-            ensure!(
-                lp_token.is_dex_share_currency_id(),
-                Error::<T>::InvalidCurrencyId
-            );
-            Self::claim_rewards(&who, PoolId::DexYieldFarming(lp_token))?;
+            Self::claim_rewards(&who, pool_id)?;
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
+        /// rewards of the pool paid in currency_id. each reward is (amount, start block, interval, repeats)
+        #[pallet::weight(<T as Config>::WeightInfo::schedule_yield_farming_rewards(rewards.len() as u32))]
         #[transactional]
         pub fn schedule_yield_farming_rewards(
             origin: OriginFor<T>,
-            lp_token: CurrencyId,
+            pool_id: PoolId,
+            currency_id: CurrencyId,
             rewards: Vec<(Balance, T::BlockNumber, T::BlockNumber, u8)>,
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
@@ -183,10 +407,10 @@ pub mod module {
             ensure!(rewards.len() > 0, Error::<T>::EmptyRewards);
 
             let now = <system::Module<T>>::block_number();
-            let currency_id = CurrencyId::Token(TokenSymbol::BOLT);
 
+            Self::ensure_valid_pool(pool_id)?;
             ensure!(
-                lp_token.is_dex_share_currency_id(),
+                matches!(currency_id, CurrencyId::Token(_)),
                 Error::<T>::InvalidCurrencyId
             );
 
@@ -199,31 +423,61 @@ pub mod module {
                     start_blk >= now + T::StartDelay::get(),
                     Error::<T>::StartTooSoon
                 );
+                // the scheduler runs a task once for zero repetitions or a zero interval
+                ensure!(
+                    rep == 1 || (rep > 1 && !interval.is_zero()),
+                    Error::<T>::InvalidRepetitions
+                );
 
-                T::Currency::transfer(
-                    currency_id,
-                    &who,
-                    &Self::account_id(),
-                    amount.saturating_mul(rep.into()),
-                )?;
-
+                let schedule_idx = Self::yield_farming_schedule_count();
                 if T::Scheduler::schedule_named(
-                    (b"yield_farming_reward", lp_token, who.clone()).encode(),
+                    Self::schedule_name(schedule_idx),
                     DispatchTime::At(start_blk),
-                    Some((interval.into(), rep.into())),
+                    Some((interval, rep.into())),
                     0,
                     frame_system::RawOrigin::Root.into(),
-                    Call::add_yield_farming_reward(lp_token, amount).into(),
+                    Call::release_yield_farming_reward(schedule_idx).into(),
                 )
                 .is_err()
                 {
                     Self::deposit_event(Event::ScheduleFailed(now));
+                    continue;
                 }
+
+                T::Currency::transfer(
+                    currency_id,
+                    &who,
+                    &Self::account_id(),
+                    amount.saturating_mul(rep.into()),
+                )?;
+
+                YieldFarmingSchedules::<T>::insert(
+                    schedule_idx,
+                    YieldFarmingSchedule {
+                        sponsor: who.clone(),
+                        pool_id,
+                        currency_id,
+                        amount,
+                        start_blk,
+                        interval,
+                        repeats: rep,
+                        released: 0,
+                    },
+                );
+                SponsorYieldFarmingSchedules::<T>::append(&who, schedule_idx);
+                YieldFarmingScheduleCount::<T>::put(schedule_idx.saturating_add(1));
+                Self::deposit_event(Event::YieldFarmingScheduled(
+                    who.clone(),
+                    schedule_idx,
+                    pool_id,
+                    currency_id,
+                ));
             }
             Ok(().into())
         }
 
-        #[pallet::weight(0)]
+        /// kept for the rewards scheduled in BOLT before pools paid several currencies
+        #[pallet::weight(<T as Config>::WeightInfo::add_yield_farming_reward())]
         #[transactional]
         pub fn add_yield_farming_reward(
             origin: OriginFor<T>,
@@ -231,31 +485,422 @@ pub mod module {
             amount: Balance,
         ) -> DispatchResultWithPostInfo {
             ensure_root(origin)?;
-            let pool_id = PoolId::DexYieldFarming(lp_token);
-            Pools::<T>::mutate(pool_id, |pool_info| {
-                pool_info.total_rewards = pool_info.total_rewards.saturating_add(amount);
-            });
-            Self::deposit_event(Event::YieldFarmingReward(pool_id, amount));
+            Self::add_reward(
+                PoolId::DexYieldFarming(lp_token),
+                CurrencyId::Token(TokenSymbol::BOLT),
+                amount,
+            );
+            Ok(().into())
+        }
+
+        #[pallet::weight(<T as Config>::WeightInfo::stake())]
+        #[transactional]
+        pub fn stake(
+            origin: OriginFor<T>,
+            currency_id: CurrencyId,
+            amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::do_stake(&who, currency_id, amount)?;
+            Ok(().into())
+        }
+
+        #[pallet::weight(<T as Config>::WeightInfo::unstake())]
+        #[transactional]
+        pub fn unstake(
+            origin: OriginFor<T>,
+            currency_id: CurrencyId,
+            amount: Balance,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::do_unstake(&who, currency_id, amount)?;
+            Ok(().into())
+        }
+
+        /// locks deposited dex shares for lock_period blocks. the lock adds a boost to the share,
+        /// up to MaxLockBoost of the amount for MaxLockPeriod, decaying until the lock expires
+        #[pallet::weight(<T as Config>::WeightInfo::lock_dex_share())]
+        #[transactional]
+        pub fn lock_dex_share(
+            origin: OriginFor<T>,
+            lp_token: CurrencyId,
+            amount: Balance,
+            lock_period: T::BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                lp_token.is_dex_share_currency_id(),
+                Error::<T>::InvalidCurrencyId
+            );
+            ensure!(
+                lock_period >= T::LockDecayPeriod::get() && lock_period <= T::MaxLockPeriod::get(),
+                Error::<T>::InvalidLockPeriod
+            );
+            ensure!(
+                !DexShareLocks::<T>::contains_key(lp_token, &who),
+                Error::<T>::DexShareAlreadyLocked
+            );
+            let pool = PoolId::DexYieldFarming(lp_token);
+            ensure!(
+                !amount.is_zero() && Self::share_and_withdrawn_reward(pool, &who).0 >= amount,
+                Error::<T>::NotEnough
+            );
+
+            let now = <system::Module<T>>::block_number();
+            let expiry_blk = now.saturating_add(lock_period);
+            let next_decay_blk = now.saturating_add(T::LockDecayPeriod::get()).min(expiry_blk);
+            let boost = Self::calc_lock_boost(amount, lock_period);
+            Self::add_share(&who, pool, boost);
+
+            DexShareLocks::<T>::insert(
+                lp_token,
+                &who,
+                DexShareLock {
+                    amount,
+                    boost,
+                    expiry_blk,
+                    next_decay_blk,
+                },
+            );
+            DexShareLockQueue::<T>::append(next_decay_blk, (lp_token, who.clone()));
+
+            Self::deposit_event(Event::DexShareLocked(who, lp_token, amount, boost, expiry_blk));
+            Ok(().into())
+        }
+
+        /// unlocks dex shares before the lock expires. the boost is removed, the pending rewards
+        /// of the pool are forfeited, added back to the pool rewards, and EarlyUnlockFee of the
+        /// locked dex shares is taken
+        #[pallet::weight(<T as Config>::WeightInfo::unlock_dex_share())]
+        #[transactional]
+        pub fn unlock_dex_share(
+            origin: OriginFor<T>,
+            lp_token: CurrencyId,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let lock = Self::dex_share_locks(lp_token, &who).ok_or(Error::<T>::DexShareNotLocked)?;
+            let pool = PoolId::DexYieldFarming(lp_token);
+
+            // the fee keeps an early unlock costly once the pending rewards have been claimed
+            let fee = T::EarlyUnlockFee::get() * lock.amount;
+            T::Currency::transfer(lp_token, &Self::account_id(), &T::EmissionTreasury::get(), fee)?;
+
+            let forfeited = Self::withdraw_rewards(&who, pool);
+            Self::remove_share(&who, pool, lock.boost.saturating_add(fee));
+            DexShareLocks::<T>::remove(lp_token, &who);
+            for (currency_id, amount) in forfeited {
+                Self::add_reward(pool, currency_id, amount);
+            }
+
+            Self::deposit_event(Event::DexShareUnlockedEarly(who, lp_token, lock.amount, fee));
+            Ok(().into())
+        }
+
+        /// adds one repetition of a yield farming schedule to its pool. dispatched by the scheduler
+        #[pallet::weight(<T as Config>::WeightInfo::release_yield_farming_reward())]
+        #[transactional]
+        pub fn release_yield_farming_reward(
+            origin: OriginFor<T>,
+            schedule_idx: YieldFarmingScheduleIndex,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let mut schedule = Self::yield_farming_schedules(schedule_idx)
+                .ok_or(Error::<T>::InvalidScheduleIndex)?;
+            ensure!(schedule.released < schedule.repeats, Error::<T>::ScheduleFinished);
+
+            Self::add_reward(schedule.pool_id, schedule.currency_id, schedule.amount);
+
+            schedule.released = schedule.released.saturating_add(1);
+            if schedule.released < schedule.repeats {
+                YieldFarmingSchedules::<T>::insert(schedule_idx, schedule);
+            } else {
+                Self::remove_schedule(&schedule.sponsor, schedule_idx);
+            }
+            Ok(().into())
+        }
+
+        /// cancels the repetitions not released yet and refunds them to the sponsor
+        #[pallet::weight(<T as Config>::WeightInfo::cancel_yield_farming_schedule())]
+        #[transactional]
+        pub fn cancel_yield_farming_schedule(
+            origin: OriginFor<T>,
+            schedule_idx: YieldFarmingScheduleIndex,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let schedule = Self::yield_farming_schedules(schedule_idx)
+                .ok_or(Error::<T>::InvalidScheduleIndex)?;
+            ensure!(schedule.sponsor == who, Error::<T>::NotScheduleSponsor);
+
+            T::Scheduler::cancel_named(Self::schedule_name(schedule_idx))
+                .map_err(|_| Error::<T>::CancelScheduleFailed)?;
+
+            let refund = schedule
+                .amount
+                .saturating_mul(schedule.repeats.saturating_sub(schedule.released).into());
+            T::Currency::transfer(schedule.currency_id, &Self::account_id(), &who, refund)?;
+            Self::remove_schedule(&who, schedule_idx);
+
+            Self::deposit_event(Event::YieldFarmingScheduleCancelled(who, schedule_idx, refund));
+            Ok(().into())
+        }
+
+        /// switches auto-compound of the yield farming pool on or off. every AccumulatePeriod
+        /// the rewards are swapped into the lp token, less AutoCompoundFee, and deposited again
+        #[pallet::weight(<T as Config>::WeightInfo::set_auto_compound())]
+        #[transactional]
+        pub fn set_auto_compound(
+            origin: OriginFor<T>,
+            lp_token: CurrencyId,
+            enabled: bool,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                lp_token.is_dex_share_currency_id(),
+                Error::<T>::InvalidCurrencyId
+            );
+
+            if !enabled {
+                AutoCompounds::<T>::remove(lp_token, &who);
+            } else if !AutoCompounds::<T>::contains_key(lp_token, &who) {
+                // compounded at the next period boundary, together with the other accounts
+                let period = T::AccumulatePeriod::get().max(One::one());
+                let now = <system::Module<T>>::block_number();
+                let next_compound_blk = (now / period).saturating_add(One::one()).saturating_mul(period);
+                AutoCompounds::<T>::insert(lp_token, &who, next_compound_blk);
+                AutoCompoundQueue::<T>::append(next_compound_blk, (lp_token, who.clone()));
+            }
+
+            Self::deposit_event(Event::AutoCompoundSet(who, lp_token, enabled));
+            Ok(().into())
+        }
+
+        /// replaces the emission schedule, or stops the emissions if None.
+        /// the cap of the new schedule counts what the previous ones have emitted
+        #[pallet::weight(<T as Config>::WeightInfo::set_emission_schedule())]
+        #[transactional]
+        pub fn set_emission_schedule(
+            origin: OriginFor<T>,
+            schedule: Option<EmissionSchedule<T::BlockNumber>>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            if let Some(schedule) = &schedule {
+                let valid_curve = match schedule.curve {
+                    EmissionCurve::Constant => true,
+                    EmissionCurve::Halving(period) | EmissionCurve::Decay(period, _) => !period.is_zero(),
+                };
+                ensure!(
+                    valid_curve
+                        && !schedule.initial_emission.is_zero()
+                        && schedule.cap > Self::total_emitted(),
+                    Error::<T>::InvalidEmissionSchedule
+                );
+            }
+
+            Emission::<T>::set(schedule.clone());
+            Self::deposit_event(Event::EmissionScheduleSet(schedule));
+            Ok(().into())
+        }
+
+        /// sets the share of the emissions going to the pool. a zero weight removes the pool
+        #[pallet::weight(<T as Config>::WeightInfo::set_emission_pool_weight())]
+        #[transactional]
+        pub fn set_emission_pool_weight(
+            origin: OriginFor<T>,
+            pool_id: PoolId,
+            weight: u32,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::ensure_valid_pool(pool_id)?;
+
+            if weight.is_zero() {
+                EmissionPoolWeights::<T>::remove(pool_id);
+            } else {
+                ensure!(
+                    EmissionPoolWeights::<T>::contains_key(pool_id)
+                        || (EmissionPoolWeights::<T>::iter().count() as u32) < T::MaxEmissionPools::get(),
+                    Error::<T>::TooManyEmissionPools
+                );
+                EmissionPoolWeights::<T>::insert(pool_id, weight);
+            }
+
+            Self::deposit_event(Event::EmissionPoolWeightSet(pool_id, weight));
             Ok(().into())
         }
     }
 }
 
+#[cfg(feature = "try-runtime")]
+impl<T: Config> Pallet<T> {
+    /// Checks the storage before `on_runtime_upgrade` runs the migration
+    pub fn pre_upgrade() -> Result<(), &'static str> {
+        migration::pre_upgrade::<T>()
+    }
+
+    /// Checks the storage migrated by `on_runtime_upgrade`
+    pub fn post_upgrade() -> Result<(), &'static str> {
+        migration::post_upgrade::<T>()
+    }
+}
+
 impl<T: Config> Pallet<T> {
     fn account_id() -> T::AccountId {
         T::ModuleId::get().into_account()
     }
 
-    pub fn payout(who: &T::AccountId, _pool_id: PoolId, amount: Balance) {
-        // hard coded reward currency
-        let currency_id = CurrencyId::Token(TokenSymbol::BOLT);
-
+    pub fn payout(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) {
         // payout the reward to user from the pool. it should not affect the
         // process, ignore the result to continue. if it fails, just the user will not
         // be rewarded, there will not increase user balance.
         let _ = T::Currency::transfer(currency_id, &Self::account_id(), &who, amount);
     }
 
+    fn schedule_name(schedule_idx: YieldFarmingScheduleIndex) -> Vec<u8> {
+        (b"yield_farming_reward", schedule_idx).encode()
+    }
+
+    fn remove_schedule(sponsor: &T::AccountId, schedule_idx: YieldFarmingScheduleIndex) {
+        YieldFarmingSchedules::<T>::remove(schedule_idx);
+        SponsorYieldFarmingSchedules::<T>::mutate(sponsor, |schedules| {
+            schedules.retain(|idx| *idx != schedule_idx);
+        });
+    }
+
+    pub fn get_yield_farming_schedules_of_account(
+        sponsor: &T::AccountId,
+    ) -> Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<T::AccountId, T::BlockNumber>)> {
+        Self::sponsor_yield_farming_schedules(sponsor)
+            .into_iter()
+            .filter_map(|idx| Self::yield_farming_schedules(idx).map(|schedule| (idx, schedule)))
+            .collect()
+    }
+
+    pub fn get_yield_farming_schedules_of_pool(
+        pool: PoolId,
+    ) -> Vec<(YieldFarmingScheduleIndex, YieldFarmingSchedule<T::AccountId, T::BlockNumber>)> {
+        YieldFarmingSchedules::<T>::iter()
+            .filter(|(_, schedule)| schedule.pool_id == pool)
+            .collect()
+    }
+
+    /// rewards `who` would get by claim_reward, by reward currency
+    pub fn get_pending_rewards(who: &T::AccountId, pool: PoolId) -> Vec<(CurrencyId, Balance)> {
+        let (share, withdrawn_rewards) = Self::share_and_withdrawn_reward(pool, who);
+        if share.is_zero() {
+            return Vec::new();
+        }
+
+        let pool_info = Self::pools(pool);
+        let proportion = FixedU128::checked_from_rational(share, pool_info.total_shares)
+            .unwrap_or_default();
+        pool_info
+            .rewards
+            .iter()
+            .filter_map(|(currency_id, (total_rewards, total_withdrawn_rewards))| {
+                let pending = Self::reward_to_withdraw(
+                    proportion,
+                    *total_rewards,
+                    *total_withdrawn_rewards,
+                    withdrawn_rewards.get(currency_id).copied().unwrap_or_default(),
+                );
+                if pending.is_zero() {
+                    None
+                } else {
+                    Some((*currency_id, pending))
+                }
+            })
+            .collect()
+    }
+
+    /// yearly return of the pool by reward currency: the scheduled rewards released in the
+    /// next BlocksPerYear, over the value of the pool deposits. both are valued by the dex prices
+    pub fn get_yield_farming_apr(pool: PoolId) -> Vec<(CurrencyId, FixedU128)> {
+        let (base_currency_id, pool_value) = match Self::pool_value(pool) {
+            Some((base_currency_id, pool_value)) if !pool_value.is_zero() => (base_currency_id, pool_value),
+            _ => return Vec::new(),
+        };
+
+        let year_end = <system::Module<T>>::block_number().saturating_add(T::BlocksPerYear::get());
+        let mut yearly_rewards: BTreeMap<CurrencyId, Balance> = BTreeMap::new();
+        for (_, schedule) in Self::get_yield_farming_schedules_of_pool(pool) {
+            let releases = Self::releases_until(&schedule, year_end);
+            let yearly_reward = yearly_rewards.entry(schedule.currency_id).or_default();
+            *yearly_reward = yearly_reward.saturating_add(schedule.amount.saturating_mul(releases.into()));
+        }
+
+        yearly_rewards
+            .into_iter()
+            .filter_map(|(currency_id, amount)| {
+                let value = Self::value_in(currency_id, amount, base_currency_id)?;
+                FixedU128::checked_from_rational(value, pool_value).map(|apr| (currency_id, apr))
+            })
+            .collect()
+    }
+
+    /// releases of the schedule left by the end block
+    fn releases_until(
+        schedule: &YieldFarmingSchedule<T::AccountId, T::BlockNumber>,
+        end: T::BlockNumber,
+    ) -> u32 {
+        let remaining: u32 = schedule.repeats.saturating_sub(schedule.released).into();
+        let next_blk = schedule
+            .start_blk
+            .saturating_add(schedule.interval.saturating_mul(schedule.released.into()));
+        if remaining == 0 || next_blk > end {
+            return 0;
+        }
+        if schedule.interval.is_zero() {
+            return remaining;
+        }
+        let later: u32 = ((end - next_blk) / schedule.interval).unique_saturated_into();
+        later.saturating_add(1).min(remaining)
+    }
+
+    /// the currency the deposits of the pool are valued in, and their value.
+    /// both sides of a liquidity pool are worth the same
+    fn pool_value(pool: PoolId) -> Option<(CurrencyId, Balance)> {
+        match pool {
+            PoolId::DexYieldFarming(lp_token) => {
+                let (currency_id_a, currency_id_b) = lp_token.split_dex_share_currency_id()?;
+                let (_, reserve_b) = T::Dex::get_liquidity_pool(currency_id_a, currency_id_b);
+                let deposited = T::Currency::free_balance(lp_token, &Self::account_id());
+                let value = FixedU128::checked_from_rational(deposited, T::Currency::total_issuance(lp_token))?
+                    .saturating_mul_int(reserve_b.saturating_mul(2));
+                Some((currency_id_b, value))
+            }
+            PoolId::Staking(currency_id) => Some((currency_id, Self::pools(pool).total_shares)),
+        }
+    }
+
+    /// `amount` of currency_id valued in base_currency_id by the dex price
+    fn value_in(currency_id: CurrencyId, amount: Balance, base_currency_id: CurrencyId) -> Option<Balance> {
+        if currency_id == base_currency_id {
+            return Some(amount);
+        }
+        let (reserve, base_reserve) = T::Dex::get_liquidity_pool(currency_id, base_currency_id);
+        FixedU128::checked_from_rational(base_reserve, reserve).map(|price| price.saturating_mul_int(amount))
+    }
+
+    /// the pools taking deposits or scheduled rewards
+    fn ensure_valid_pool(pool_id: PoolId) -> DispatchResult {
+        match pool_id {
+            PoolId::DexYieldFarming(lp_token) => ensure!(
+                lp_token.is_dex_share_currency_id(),
+                Error::<T>::InvalidPoolId
+            ),
+            PoolId::Staking(CurrencyId::Token(_)) => (),
+            PoolId::Staking(_) => Err(Error::<T>::InvalidPoolId)?,
+        }
+        Ok(())
+    }
+
+    pub fn add_reward(pool: PoolId, currency_id: CurrencyId, amount: Balance) {
+        Pools::<T>::mutate(pool, |pool_info| {
+            let (total_rewards, _) = pool_info.rewards.entry(currency_id).or_default();
+            *total_rewards = total_rewards.saturating_add(amount);
+        });
+        Self::deposit_event(Event::YieldFarmingReward(pool, currency_id, amount));
+    }
+
     fn do_deposit_dex_share(
         who: &T::AccountId,
         lp_token: CurrencyId,
@@ -286,8 +931,13 @@ impl<T: Config> Pallet<T> {
             lp_token.is_dex_share_currency_id(),
             Error::<T>::InvalidCurrencyId
         );
+        let locked = Self::dex_share_locks(lp_token, &who)
+            .map(|lock| lock.amount.saturating_add(lock.boost))
+            .unwrap_or_default();
         ensure!(
-            Self::share_and_withdrawn_reward(PoolId::DexYieldFarming(lp_token), &who).0
+            Self::share_and_withdrawn_reward(PoolId::DexYieldFarming(lp_token), &who)
+                .0
+                .saturating_sub(locked)
                 >= amount,
             Error::<T>::NotEnough
         );
@@ -299,6 +949,290 @@ impl<T: Config> Pallet<T> {
         Ok(())
     }
 
+    /// the boost of `amount` locked for `remaining` more blocks
+    fn calc_lock_boost(amount: Balance, remaining: T::BlockNumber) -> Balance {
+        let remaining: Balance = remaining.min(T::MaxLockPeriod::get()).unique_saturated_into();
+        let max_lock_period: Balance = T::MaxLockPeriod::get().unique_saturated_into();
+        FixedU128::checked_from_rational(remaining, max_lock_period)
+            .unwrap_or_default()
+            .saturating_mul_int(T::MaxLockBoost::get() * amount)
+    }
+
+    /// decays the locks queued at the block, at most MaxLockDecaysPerBlock.
+    /// the rest are moved to the next block
+    fn decay_dex_share_locks(now: T::BlockNumber) -> Weight {
+        let mut locks = DexShareLockQueue::<T>::take(now);
+        if locks.is_empty() {
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+
+        let deferred = locks.split_off(locks.len().min(T::MaxLockDecaysPerBlock::get() as usize));
+        let next_blk = now.saturating_add(One::one());
+        let weight = T::WeightInfo::decay_dex_share_locks(locks.len() as u32)
+            .saturating_add(T::DbWeight::get().reads_writes(deferred.len() as Weight, 2 * deferred.len() as Weight));
+        for (lp_token, who) in deferred {
+            if let Some(mut lock) = Self::dex_share_locks(lp_token, &who) {
+                if lock.next_decay_blk == now {
+                    lock.next_decay_blk = next_blk;
+                    DexShareLocks::<T>::insert(lp_token, &who, lock);
+                    DexShareLockQueue::<T>::append(next_blk, (lp_token, who));
+                }
+            }
+        }
+
+        for (lp_token, who) in locks {
+            match Self::dex_share_locks(lp_token, &who) {
+                Some(lock) if lock.next_decay_blk == now => Self::decay_dex_share_lock(lp_token, who, lock, now),
+                _ => (),
+            }
+        }
+        weight
+    }
+
+    /// the share loses the part of the boost decayed since the last decay. the expired lock is removed
+    fn decay_dex_share_lock(
+        lp_token: CurrencyId,
+        who: T::AccountId,
+        mut lock: DexShareLock<T::BlockNumber>,
+        now: T::BlockNumber,
+    ) {
+        let pool = PoolId::DexYieldFarming(lp_token);
+        let boost = Self::calc_lock_boost(lock.amount, lock.expiry_blk.saturating_sub(now));
+        Self::remove_share(&who, pool, lock.boost.saturating_sub(boost));
+
+        if now >= lock.expiry_blk {
+            DexShareLocks::<T>::remove(lp_token, &who);
+            Self::deposit_event(Event::DexShareUnlocked(who, lp_token, lock.amount));
+            return;
+        }
+
+        lock.boost = boost;
+        lock.next_decay_blk = now.saturating_add(T::LockDecayPeriod::get()).min(lock.expiry_blk);
+        DexShareLockQueue::<T>::append(lock.next_decay_blk, (lp_token, who.clone()));
+        DexShareLocks::<T>::insert(lp_token, &who, lock);
+    }
+
+    /// emission of the AccumulatePeriod starting at the block, before the cap
+    fn emission_at(schedule: &EmissionSchedule<T::BlockNumber>, now: T::BlockNumber) -> Balance {
+        let elapsed = now.saturating_sub(schedule.start_blk);
+        match schedule.curve {
+            EmissionCurve::Constant => schedule.initial_emission,
+            EmissionCurve::Halving(period) => {
+                let halvings: u32 = (elapsed / period.max(One::one())).unique_saturated_into();
+                schedule.initial_emission.checked_shr(halvings).unwrap_or_default()
+            }
+            EmissionCurve::Decay(period, rate) => {
+                let decays: u32 = (elapsed / period.max(One::one())).unique_saturated_into();
+                Permill::one()
+                    .saturating_sub(rate)
+                    .saturating_pow(decays as usize)
+                    * schedule.initial_emission
+            }
+        }
+    }
+
+    /// every AccumulatePeriod, funds the emission of the schedule and adds it to the pools
+    /// by their weights
+    fn emit(now: T::BlockNumber) -> Weight {
+        if !(now % T::AccumulatePeriod::get().max(One::one())).is_zero() {
+            return 0;
+        }
+        let schedule = match Self::emission() {
+            Some(schedule) if now >= schedule.start_blk => schedule,
+            _ => return T::DbWeight::get().reads(1),
+        };
+
+        let total_emitted = Self::total_emitted();
+        let emission = Self::emission_at(&schedule, now).min(schedule.cap.saturating_sub(total_emitted));
+        let pools: Vec<(PoolId, u32)> = EmissionPoolWeights::<T>::iter().collect();
+        let weight = T::WeightInfo::emit(pools.len() as u32);
+        let total_weight = pools.iter().fold(0u32, |total, (_, pool_weight)| total.saturating_add(*pool_weight));
+        if emission.is_zero() || total_weight.is_zero() {
+            return weight;
+        }
+
+        // the rounding leftover is not emitted
+        let pool_emissions: Vec<(PoolId, Balance)> = pools
+            .into_iter()
+            .map(|(pool, pool_weight)| {
+                let pool_emission = FixedU128::checked_from_rational(pool_weight, total_weight)
+                    .unwrap_or_default()
+                    .saturating_mul_int(emission);
+                (pool, pool_emission)
+            })
+            .collect();
+        let amount = pool_emissions.iter().fold(0, |total: Balance, (_, pool_emission)| total.saturating_add(*pool_emission));
+
+        let currency_id = T::EmissionCurrencyId::get();
+        let funded = match schedule.source {
+            EmissionSource::Mint => T::Currency::deposit(currency_id, &Self::account_id(), amount),
+            EmissionSource::Treasury => T::Currency::transfer(
+                currency_id,
+                &T::EmissionTreasury::get(),
+                &Self::account_id(),
+                amount,
+            ),
+        };
+        if funded.is_err() {
+            Self::deposit_event(Event::EmissionFailed(now, amount));
+            return weight;
+        }
+
+        for (pool, pool_emission) in pool_emissions {
+            if !pool_emission.is_zero() {
+                Self::add_reward(pool, currency_id, pool_emission);
+            }
+        }
+        let total_emitted = total_emitted.saturating_add(amount);
+        TotalEmitted::<T>::put(total_emitted);
+        Self::deposit_event(Event::Emitted(now, amount, total_emitted));
+        weight
+    }
+
+    /// compounds the accounts queued at the block, at most MaxAutoCompoundsPerBlock. accounts
+    /// without share left in the pool are no longer compounded.
+    /// the rest are moved to the next block
+    fn auto_compound(now: T::BlockNumber) -> Weight {
+        let mut accounts = AutoCompoundQueue::<T>::take(now);
+        if accounts.is_empty() {
+            return T::DbWeight::get().reads_writes(1, 1);
+        }
+
+        let deferred = accounts.split_off(accounts.len().min(T::MaxAutoCompoundsPerBlock::get() as usize));
+        let next_blk = now.saturating_add(One::one());
+        let weight = T::WeightInfo::auto_compound(accounts.len() as u32)
+            .saturating_add(T::DbWeight::get().reads_writes(deferred.len() as Weight, 2 * deferred.len() as Weight));
+        for (lp_token, who) in deferred {
+            if Self::auto_compounds(lp_token, &who) == Some(now) {
+                AutoCompounds::<T>::insert(lp_token, &who, next_blk);
+                AutoCompoundQueue::<T>::append(next_blk, (lp_token, who));
+            }
+        }
+
+        let next_compound_blk = now.saturating_add(T::AccumulatePeriod::get().max(One::one()));
+        for (lp_token, who) in accounts {
+            if Self::auto_compounds(lp_token, &who) != Some(now) {
+                continue;
+            }
+            if Self::share_and_withdrawn_reward(PoolId::DexYieldFarming(lp_token), &who).0.is_zero() {
+                AutoCompounds::<T>::remove(lp_token, &who);
+                Self::deposit_event(Event::AutoCompoundSet(who, lp_token, false));
+                continue;
+            }
+            match Self::compound(&who, lp_token) {
+                Ok(share) if share.is_zero() => (),
+                Ok(share) => Self::deposit_event(Event::AutoCompounded(who.clone(), lp_token, share)),
+                Err(_) => Self::deposit_event(Event::AutoCompoundFailed(who.clone(), lp_token)),
+            }
+            AutoCompounds::<T>::insert(lp_token, &who, next_compound_blk);
+            AutoCompoundQueue::<T>::append(next_compound_blk, (lp_token, who));
+        }
+        weight
+    }
+
+    /// claims the rewards of the pool less AutoCompoundFee, swaps them into both sides of the
+    /// lp token in equal value, adds the liquidity and deposits the new lp share.
+    /// rewards without a dex price are left with `who`. fails if a swap gets more than
+    /// AutoCompoundMaxSlippage below the pool price
+    #[transactional]
+    fn compound(who: &T::AccountId, lp_token: CurrencyId) -> Result<Balance, DispatchError> {
+        let (currency_id_a, currency_id_b) = lp_token
+            .split_dex_share_currency_id()
+            .ok_or(Error::<T>::InvalidCurrencyId)?;
+
+        let (mut amount_a, mut amount_b): (Balance, Balance) = (Zero::zero(), Zero::zero());
+        for (currency_id, amount) in Self::withdraw_rewards(who, PoolId::DexYieldFarming(lp_token)) {
+            let fee = T::AutoCompoundFee::get() * amount;
+            let amount = amount.saturating_sub(fee);
+            T::Currency::transfer(currency_id, &Self::account_id(), &T::AutoCompoundFeeReceiver::get(), fee)?;
+            T::Currency::transfer(currency_id, &Self::account_id(), who, amount)?;
+
+            if currency_id == currency_id_a {
+                amount_a = amount_a.saturating_add(amount);
+            } else if currency_id == currency_id_b {
+                amount_b = amount_b.saturating_add(amount);
+            } else if Self::value_in(currency_id, amount, currency_id_b).is_some() {
+                let target = Self::swap_for_compound(who, currency_id, currency_id_b, amount)?;
+                amount_b = amount_b.saturating_add(target);
+            } else if Self::value_in(currency_id, amount, currency_id_a).is_some() {
+                let target = Self::swap_for_compound(who, currency_id, currency_id_a, amount)?;
+                amount_a = amount_a.saturating_add(target);
+            }
+        }
+
+        // swap half of the excess value of one side into the other
+        if let Some(value_a) = Self::value_in(currency_id_a, amount_a, currency_id_b) {
+            if value_a > amount_b {
+                let supply = FixedU128::checked_from_rational((value_a - amount_b) / 2, value_a)
+                    .unwrap_or_default()
+                    .saturating_mul_int(amount_a);
+                if !supply.is_zero() {
+                    let target = Self::swap_for_compound(who, currency_id_a, currency_id_b, supply)?;
+                    amount_a = amount_a.saturating_sub(supply);
+                    amount_b = amount_b.saturating_add(target);
+                }
+            } else {
+                let supply = (amount_b - value_a) / 2;
+                if !supply.is_zero() {
+                    let target = Self::swap_for_compound(who, currency_id_b, currency_id_a, supply)?;
+                    amount_b = amount_b.saturating_sub(supply);
+                    amount_a = amount_a.saturating_add(target);
+                }
+            }
+        }
+
+        if amount_a.is_zero() || amount_b.is_zero() {
+            return Ok(Zero::zero());
+        }
+        let share = T::Dex::add_liquidity(who, currency_id_a, currency_id_b, amount_a, amount_b)?;
+        Self::do_deposit_dex_share(who, lp_token, share)?;
+        Ok(share)
+    }
+
+    /// swaps `supply` into `target_currency_id`, getting at most AutoCompoundMaxSlippage below
+    /// the pool price
+    fn swap_for_compound(
+        who: &T::AccountId,
+        supply_currency_id: CurrencyId,
+        target_currency_id: CurrencyId,
+        supply: Balance,
+    ) -> Result<Balance, DispatchError> {
+        let path = [supply_currency_id, target_currency_id];
+        let value = Self::value_in(supply_currency_id, supply, target_currency_id).unwrap_or_default();
+        let min_target = value.saturating_sub(T::AutoCompoundMaxSlippage::get() * value);
+        ensure!(
+            T::Dex::get_swap_target_amount(&path, supply, None).unwrap_or_default() >= min_target,
+            Error::<T>::SlippageTooHigh
+        );
+        T::Dex::swap_with_exact_supply(who, &path, supply, min_target, None)
+    }
+
+    fn do_stake(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+        let pool = PoolId::Staking(currency_id);
+        Self::ensure_valid_pool(pool)?;
+
+        T::Currency::transfer(currency_id, who, &Self::account_id(), amount)?;
+        Self::add_share(&who, pool, amount);
+
+        Self::deposit_event(Event::Staked(who.clone(), currency_id, amount));
+        Ok(())
+    }
+
+    fn do_unstake(who: &T::AccountId, currency_id: CurrencyId, amount: Balance) -> DispatchResult {
+        let pool = PoolId::Staking(currency_id);
+        Self::ensure_valid_pool(pool)?;
+        ensure!(
+            Self::share_and_withdrawn_reward(pool, &who).0 >= amount,
+            Error::<T>::NotEnough
+        );
+
+        T::Currency::transfer(currency_id, &Self::account_id(), &who, amount)?;
+        Self::remove_share(&who, pool, amount);
+
+        Self::deposit_event(Event::Unstaked(who.clone(), currency_id, amount));
+        Ok(())
+    }
+
     fn add_share(who: &T::AccountId, pool: PoolId, add_amount: Balance) {
         if add_amount.is_zero() {
             return;
@@ -307,17 +1241,22 @@ impl<T: Config> Pallet<T> {
         Pools::<T>::mutate(pool, |pool_info| {
             let proportion = FixedU128::checked_from_rational(add_amount, pool_info.total_shares)
                 .unwrap_or_default();
-            let reward_inflation = proportion.saturating_mul_int(pool_info.total_rewards);
-
             pool_info.total_shares = pool_info.total_shares.saturating_add(add_amount);
-            pool_info.total_rewards = pool_info.total_rewards.saturating_add(reward_inflation);
-            pool_info.total_withdrawn_rewards = pool_info
-                .total_withdrawn_rewards
-                .saturating_add(reward_inflation);
 
             ShareAndWithdrawnReward::<T>::mutate(pool, who, |(share, withdrawn_rewards)| {
                 *share = share.saturating_add(add_amount);
-                *withdrawn_rewards = withdrawn_rewards.saturating_add(reward_inflation);
+                // the new share is booked as if its rewards so far were withdrawn
+                for (currency_id, (total_rewards, total_withdrawn_rewards)) in pool_info.rewards.iter_mut() {
+                    let reward_inflation = proportion.saturating_mul_int(*total_rewards);
+                    if reward_inflation.is_zero() {
+                        continue;
+                    }
+                    *total_rewards = total_rewards.saturating_add(reward_inflation);
+                    *total_withdrawn_rewards = total_withdrawn_rewards.saturating_add(reward_inflation);
+
+                    let withdrawn_reward = withdrawn_rewards.entry(*currency_id).or_default();
+                    *withdrawn_reward = withdrawn_reward.saturating_add(reward_inflation);
+                }
             });
         });
     }
@@ -339,17 +1278,23 @@ impl<T: Config> Pallet<T> {
             Pools::<T>::mutate(pool, |pool_info| {
                 let proportion =
                     FixedU128::checked_from_rational(remove_amount, *share).unwrap_or_default();
-                let withdrawn_rewards_to_remove = proportion.saturating_mul_int(*withdrawn_rewards);
 
                 pool_info.total_shares = pool_info.total_shares.saturating_sub(remove_amount);
-                pool_info.total_rewards = pool_info
-                    .total_rewards
-                    .saturating_sub(withdrawn_rewards_to_remove);
-                pool_info.total_withdrawn_rewards = pool_info
-                    .total_withdrawn_rewards
-                    .saturating_sub(withdrawn_rewards_to_remove);
-
-                *withdrawn_rewards = withdrawn_rewards.saturating_sub(withdrawn_rewards_to_remove);
+                for (currency_id, withdrawn_reward) in withdrawn_rewards.iter_mut() {
+                    let withdrawn_rewards_to_remove = proportion.saturating_mul_int(*withdrawn_reward);
+                    if let Some((total_rewards, total_withdrawn_rewards)) = pool_info.rewards.get_mut(currency_id) {
+                        *total_rewards = total_rewards.saturating_sub(withdrawn_rewards_to_remove);
+                        *total_withdrawn_rewards = total_withdrawn_rewards.saturating_sub(withdrawn_rewards_to_remove);
+                    }
+                    *withdrawn_reward = withdrawn_reward.saturating_sub(withdrawn_rewards_to_remove);
+                }
+                let emptied: Vec<CurrencyId> = withdrawn_rewards.iter()
+                    .filter(|(_, withdrawn_reward)| withdrawn_reward.is_zero())
+                    .map(|(currency_id, _)| *currency_id)
+                    .collect();
+                for currency_id in emptied {
+                    withdrawn_rewards.remove(&currency_id);
+                }
             });
 
             *share = share.saturating_sub(remove_amount);
@@ -367,7 +1312,22 @@ impl<T: Config> Pallet<T> {
         }
     }
 
-    pub fn claim_rewards(who: &T::AccountId, pool: PoolId) -> DispatchResult {
+    /// the part of the rewards due to a share of `proportion` not withdrawn yet
+    fn reward_to_withdraw(
+        proportion: FixedU128,
+        total_rewards: Balance,
+        total_withdrawn_rewards: Balance,
+        withdrawn_reward: Balance,
+    ) -> Balance {
+        proportion
+            .saturating_mul_int(total_rewards)
+            .saturating_sub(withdrawn_reward)
+            .min(total_rewards.saturating_sub(total_withdrawn_rewards))
+    }
+
+    /// books the pending rewards of `who` in the pool as withdrawn, returning them unpaid
+    fn withdraw_rewards(who: &T::AccountId, pool: PoolId) -> Vec<(CurrencyId, Balance)> {
+        let mut rewards = Vec::new();
         ShareAndWithdrawnReward::<T>::mutate(pool, who, |(share, withdrawn_rewards)| {
             if share.is_zero() {
                 return;
@@ -376,28 +1336,34 @@ impl<T: Config> Pallet<T> {
             Pools::<T>::mutate(pool, |pool_info| {
                 let proportion = FixedU128::checked_from_rational(*share, pool_info.total_shares)
                     .unwrap_or_default();
-                let reward_to_withdraw = proportion
-                    .saturating_mul_int(pool_info.total_rewards)
-                    .saturating_sub(*withdrawn_rewards)
-                    .min(
-                        pool_info
-                            .total_rewards
-                            .saturating_sub(pool_info.total_withdrawn_rewards),
+                for (currency_id, (total_rewards, total_withdrawn_rewards)) in pool_info.rewards.iter_mut() {
+                    let reward_to_withdraw = Self::reward_to_withdraw(
+                        proportion,
+                        *total_rewards,
+                        *total_withdrawn_rewards,
+                        withdrawn_rewards.get(currency_id).copied().unwrap_or_default(),
                     );
 
-                if reward_to_withdraw.is_zero() {
-                    return;
-                }
-
-                pool_info.total_withdrawn_rewards = pool_info
-                    .total_withdrawn_rewards
-                    .saturating_add(reward_to_withdraw);
-                *withdrawn_rewards = withdrawn_rewards.saturating_add(reward_to_withdraw);
+                    if reward_to_withdraw.is_zero() {
+                        continue;
+                    }
 
-                // pay reward to `who`
-                Self::payout(who, pool, reward_to_withdraw);
+                    *total_withdrawn_rewards = total_withdrawn_rewards.saturating_add(reward_to_withdraw);
+                    let withdrawn_reward = withdrawn_rewards.entry(*currency_id).or_default();
+                    *withdrawn_reward = withdrawn_reward.saturating_add(reward_to_withdraw);
+                    rewards.push((*currency_id, reward_to_withdraw));
+                }
             });
         });
+        rewards
+    }
+
+    /// pays the rewards of `who` in the pool, in every reward currency
+    pub fn claim_rewards(who: &T::AccountId, pool: PoolId) -> DispatchResult {
+        for (currency_id, reward) in Self::withdraw_rewards(who, pool) {
+            // pay reward to `who`
+            Self::payout(who, currency_id, reward);
+        }
         Ok(())
     }
 }
@@ -0,0 +1,189 @@
+use super::*;
+use frame_support::traits::PalletVersion;
+use frame_support::weights::Weight;
+
+/// deprecated types and storage
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Default, RuntimeDebug)]
+pub struct DeprecatedPoolInfo<Balance: HasCompact> {
+    #[codec(compact)]
+    total_shares: Balance,
+    #[codec(compact)]
+    total_rewards: Balance,
+    #[codec(compact)]
+    total_withdrawn_rewards: Balance,
+}
+
+/// Apply the migration from 2_0_0 to 2_1_0, the pools paying rewards in BOLT only before.
+/// Returning the weight of the entries read and written.
+pub fn migrate_to_v2_1<T: Config>() -> Weight {
+    let maybe_storage_version = <Pallet<T>>::storage_version();
+    if let Some(storage_version) = maybe_storage_version {
+        if storage_version < PalletVersion::new(2, 1, 0) {
+            let weight = migrate_pool_rewards::<T>();
+            frame_support::debug::info!("successful migration of rewards to 2.1.0");
+            return weight;
+        }
+    }
+    0
+}
+
+pub fn migrate_pool_rewards<T: Config>() -> Weight {
+    let reward_currency_id = CurrencyId::Token(TokenSymbol::BOLT);
+    let mut translated: Weight = 0;
+    Pools::<T>::translate_values::<DeprecatedPoolInfo<Balance>, _>(|pool_info| {
+        translated += 1;
+        let mut rewards = BTreeMap::new();
+        if !pool_info.total_rewards.is_zero() {
+            rewards.insert(
+                reward_currency_id,
+                (pool_info.total_rewards, pool_info.total_withdrawn_rewards),
+            );
+        }
+        Some(PoolInfo {
+            total_shares: pool_info.total_shares,
+            rewards,
+        })
+    });
+    ShareAndWithdrawnReward::<T>::translate_values::<(Balance, Balance), _>(
+        |(share, withdrawn_rewards)| {
+            translated += 1;
+            let mut withdrawn = BTreeMap::new();
+            if !withdrawn_rewards.is_zero() {
+                withdrawn.insert(reward_currency_id, withdrawn_rewards);
+            }
+            Some((share, withdrawn))
+        },
+    );
+    T::DbWeight::get().reads_writes(translated, translated)
+}
+
+/// storage key holding the totals recorded by `pre_upgrade`, for `post_upgrade` to compare against
+#[cfg(feature = "try-runtime")]
+const TRY_RUNTIME_TOTALS_KEY: &[u8] = b":rewards:try-runtime:totals";
+
+/// totals of the pools and of the accounts in them, in BOLT
+#[cfg(feature = "try-runtime")]
+#[derive(Encode, Decode, PartialEq, Eq, Default, RuntimeDebug)]
+struct PoolTotals {
+    pools: u32,
+    total_shares: Balance,
+    total_rewards: Balance,
+    total_withdrawn_rewards: Balance,
+    accounts: u32,
+    account_withdrawn_rewards: Balance,
+}
+
+/// Records the totals of the pools before `migrate_to_v2_1` translates them, if it is to run.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config>() -> Result<(), &'static str> {
+    use frame_support::storage::migration::storage_iter;
+    use frame_support::traits::PalletInfo;
+
+    let storage_version = <Pallet<T>>::storage_version().ok_or("rewards storage version is missing")?;
+    let mut maybe_totals = None;
+    if storage_version < PalletVersion::new(2, 1, 0) {
+        let module = <T as frame_system::Config>::PalletInfo::name::<Pallet<T>>()
+            .ok_or("rewards pallet is missing from the runtime")?
+            .as_bytes();
+        let mut totals = PoolTotals::default();
+        for (_, pool_info) in storage_iter::<DeprecatedPoolInfo<Balance>>(module, b"Pools") {
+            totals.pools += 1;
+            totals.total_shares = totals.total_shares.saturating_add(pool_info.total_shares);
+            totals.total_rewards = totals.total_rewards.saturating_add(pool_info.total_rewards);
+            totals.total_withdrawn_rewards =
+                totals.total_withdrawn_rewards.saturating_add(pool_info.total_withdrawn_rewards);
+        }
+        for (_, (_, withdrawn_rewards)) in
+            storage_iter::<(Balance, Balance)>(module, b"ShareAndWithdrawnReward")
+        {
+            totals.accounts += 1;
+            totals.account_withdrawn_rewards =
+                totals.account_withdrawn_rewards.saturating_add(withdrawn_rewards);
+        }
+        maybe_totals = Some(totals);
+    }
+    frame_support::storage::unhashed::put(TRY_RUNTIME_TOTALS_KEY, &maybe_totals);
+    Ok(())
+}
+
+/// Checks that `migrate_to_v2_1` kept every pool and account, with their rewards in BOLT.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config>() -> Result<(), &'static str> {
+    let maybe_totals: Option<PoolTotals> =
+        frame_support::storage::unhashed::take(TRY_RUNTIME_TOTALS_KEY)
+            .ok_or("rewards pre_upgrade did not run")?;
+    let expected = match maybe_totals {
+        Some(totals) => totals,
+        None => return Ok(()),
+    };
+    let reward_currency_id = CurrencyId::Token(TokenSymbol::BOLT);
+    let mut totals = PoolTotals::default();
+    for pool_info in Pools::<T>::iter_values() {
+        ensure!(
+            pool_info.rewards.keys().all(|currency_id| *currency_id == reward_currency_id),
+            "rewards pool migrated with a reward currency other than BOLT"
+        );
+        let (total_rewards, total_withdrawn_rewards) =
+            pool_info.rewards.get(&reward_currency_id).copied().unwrap_or_default();
+        totals.pools += 1;
+        totals.total_shares = totals.total_shares.saturating_add(pool_info.total_shares);
+        totals.total_rewards = totals.total_rewards.saturating_add(total_rewards);
+        totals.total_withdrawn_rewards = totals.total_withdrawn_rewards.saturating_add(total_withdrawn_rewards);
+    }
+    for (_, withdrawn) in ShareAndWithdrawnReward::<T>::iter_values() {
+        totals.accounts += 1;
+        totals.account_withdrawn_rewards = totals
+            .account_withdrawn_rewards
+            .saturating_add(withdrawn.get(&reward_currency_id).copied().unwrap_or_default());
+    }
+    if totals != expected {
+        frame_support::debug::error!("rewards totals {:?} migrated to {:?}", expected, totals);
+        return Err("rewards pools or accounts changed by the migration to 2.1.0");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::*;
+
+    #[test]
+    fn migrate_pool_rewards_test() {
+        ExtBuilder::default().build().execute_with(|| {
+            frame_support::storage::unhashed::put(
+                &Pools::<Test>::hashed_key_for(BOLT_WUSD_POOL),
+                &DeprecatedPoolInfo::<Balance> {
+                    total_shares: 200,
+                    total_rewards: 10000,
+                    total_withdrawn_rewards: 5000,
+                },
+            );
+            frame_support::storage::unhashed::put(
+                &ShareAndWithdrawnReward::<Test>::hashed_key_for(BOLT_WUSD_POOL, ALICE),
+                &(100 as Balance, 5000 as Balance),
+            );
+            frame_support::storage::unhashed::put(
+                &ShareAndWithdrawnReward::<Test>::hashed_key_for(BOLT_WUSD_POOL, BOB),
+                &(100 as Balance, 0 as Balance),
+            );
+
+            migrate_pool_rewards::<Test>();
+            assert_eq!(
+                RewardsModule::pools(BOLT_WUSD_POOL),
+                PoolInfo {
+                    total_shares: 200,
+                    rewards: vec![(BOLT, (10000, 5000))].into_iter().collect(),
+                }
+            );
+            assert_eq!(
+                RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE),
+                (100, vec![(BOLT, 5000)].into_iter().collect())
+            );
+            assert_eq!(
+                RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB),
+                (100, BTreeMap::new())
+            );
+        });
+    }
+}
@@ -8,6 +8,7 @@ pub use common::{DexManager, Price, Ratio};
 use frame_system::EnsureRoot;
 use sp_runtime::traits::BlakeTwo256;
 use orml_currencies::BasicCurrencyAdapter;
+use std::cell::RefCell;
 use orml_traits::parameter_type_with_key;
 use frame_support::traits::{OnFinalize, OnInitialize};
 
@@ -17,6 +18,7 @@ pub type BlockNumber = u64;
 pub const ALICE: AccountId = 1;
 pub const BOB: AccountId = 2;
 pub const CAROL: AccountId = 3;
+pub const TREASURY: AccountId = 10;
 pub const BOLT: CurrencyId = CurrencyId::Token(TokenSymbol::BOLT);
 pub const WUSD: CurrencyId = CurrencyId::Token(TokenSymbol::WUSD);
 pub const PLKT: CurrencyId = CurrencyId::Token(TokenSymbol::PLKT);
@@ -111,7 +113,27 @@ impl orml_tokens::Config for Test {
     type OnDust = ();
 }
 
+thread_local! {
+    static SWAP_SLIPPAGE: RefCell<Permill> = RefCell::new(Permill::zero());
+}
+
+/// part of the target amount lost by the swaps of the mock dex
+pub fn set_swap_slippage(slippage: Permill) {
+    SWAP_SLIPPAGE.with(|v| *v.borrow_mut() = slippage);
+}
+
 pub struct MockDEX;
+impl MockDEX {
+    fn target_amount(path: &[CurrencyId], supply_amount: Balance) -> Option<Balance> {
+        let (supply_pool, target_pool) = Self::get_liquidity_pool(path[0], path[path.len() - 1]);
+        if supply_pool.is_zero() {
+            return None;
+        }
+        let target_amount = supply_amount * target_pool / supply_pool;
+        Some(target_amount - SWAP_SLIPPAGE.with(|v| *v.borrow()) * target_amount)
+    }
+}
+
 impl DexManager<AccountId, CurrencyId, Balance> for MockDEX {
     fn get_liquidity_pool(currency_id_a: CurrencyId, currency_id_b: CurrencyId) -> (Balance, Balance) {
         match (currency_id_a, currency_id_b) {
@@ -123,8 +145,8 @@ impl DexManager<AccountId, CurrencyId, Balance> for MockDEX {
         }
     }
 
-    fn get_swap_target_amount(_: &[CurrencyId], _: Balance, _: Option<Ratio>) -> Option<Balance> {
-        unimplemented!()
+    fn get_swap_target_amount(path: &[CurrencyId], supply_amount: Balance, _: Option<Ratio>) -> Option<Balance> {
+        Self::target_amount(path, supply_amount)
     }
 
     fn get_swap_supply_amount(_: &[CurrencyId], _: Balance, _: Option<Ratio>) -> Option<Balance> {
@@ -132,13 +154,17 @@ impl DexManager<AccountId, CurrencyId, Balance> for MockDEX {
     }
 
     fn swap_with_exact_supply(
-        _: &AccountId,
-        _: &[CurrencyId],
-        _: Balance,
-        _: Balance,
+        who: &AccountId,
+        path: &[CurrencyId],
+        supply_amount: Balance,
+        min_target_amount: Balance,
         _: Option<Ratio>,
     ) -> sp_std::result::Result<Balance, DispatchError> {
-        unimplemented!()
+        let target_amount = Self::target_amount(path, supply_amount).ok_or(DispatchError::Other("no liquidity"))?;
+        ensure!(target_amount >= min_target_amount, DispatchError::Other("below min target"));
+        Currencies::withdraw(path[0], who, supply_amount)?;
+        Currencies::deposit(path[path.len() - 1], who, target_amount)?;
+        Ok(target_amount)
     }
 
     fn swap_with_exact_target(
@@ -150,6 +176,25 @@ impl DexManager<AccountId, CurrencyId, Balance> for MockDEX {
     ) -> sp_std::result::Result<Balance, DispatchError> {
         unimplemented!()
     }
+
+    fn add_liquidity(
+        who: &AccountId,
+        currency_id_a: CurrencyId,
+        currency_id_b: CurrencyId,
+        max_amount_a: Balance,
+        max_amount_b: Balance,
+    ) -> sp_std::result::Result<Balance, DispatchError> {
+        let lp_token = match (currency_id_a, currency_id_b) {
+            (CurrencyId::Token(a), CurrencyId::Token(b)) if a < b => CurrencyId::DexShare(a, b),
+            (CurrencyId::Token(a), CurrencyId::Token(b)) => CurrencyId::DexShare(b, a),
+            _ => return Err(DispatchError::Other("invalid pair")),
+        };
+        Currencies::withdraw(currency_id_a, who, max_amount_a)?;
+        Currencies::withdraw(currency_id_b, who, max_amount_b)?;
+        let share = max_amount_a + max_amount_b;
+        Currencies::deposit(lp_token, who, share)?;
+        Ok(share)
+    }
 }
 
 parameter_types! {
@@ -157,6 +202,19 @@ parameter_types! {
 	pub const StartDelay: BlockNumber = 2;
 	pub const RewardsModuleId: ModuleId = ModuleId(*b"span/rwd");
 	pub const MinimumYieldFarmingReward: Balance = 10;
+	pub const MaxLockPeriod: BlockNumber = 100;
+	pub const LockDecayPeriod: BlockNumber = 10;
+	pub const MaxLockDecaysPerBlock: u32 = 2;
+	pub const MaxLockBoost: Permill = Permill::from_percent(100);
+	pub const EarlyUnlockFee: Permill = Permill::from_percent(10);
+	pub const BlocksPerYear: BlockNumber = 1000;
+	pub const AutoCompoundFee: Permill = Permill::from_percent(10);
+	pub const AutoCompoundFeeReceiver: AccountId = TREASURY;
+	pub const MaxAutoCompoundsPerBlock: u32 = 2;
+	pub const AutoCompoundMaxSlippage: Permill = Permill::from_percent(5);
+	pub const EmissionCurrencyId: CurrencyId = BOLT;
+	pub const EmissionTreasury: AccountId = TREASURY;
+	pub const MaxEmissionPools: u32 = 3;
 }
 impl Config for Test {
     type Event = Event;
@@ -169,6 +227,20 @@ impl Config for Test {
     type AccumulatePeriod = AccumulatePeriod;
     type Scheduler = Scheduler;
     type PalletsOrigin = OriginCaller;
+    type MaxLockPeriod = MaxLockPeriod;
+    type LockDecayPeriod = LockDecayPeriod;
+    type MaxLockDecaysPerBlock = MaxLockDecaysPerBlock;
+    type MaxLockBoost = MaxLockBoost;
+    type EarlyUnlockFee = EarlyUnlockFee;
+    type BlocksPerYear = BlocksPerYear;
+    type AutoCompoundFee = AutoCompoundFee;
+    type AutoCompoundFeeReceiver = AutoCompoundFeeReceiver;
+    type MaxAutoCompoundsPerBlock = MaxAutoCompoundsPerBlock;
+    type AutoCompoundMaxSlippage = AutoCompoundMaxSlippage;
+    type EmissionCurrencyId = EmissionCurrencyId;
+    type EmissionTreasury = EmissionTreasury;
+    type MaxEmissionPools = MaxEmissionPools;
+    type WeightInfo = weights::SubstrateWeight<Test>;
 }
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -206,5 +278,6 @@ pub fn run_to_block(n: u64) {
         Scheduler::on_finalize(System::block_number());
         System::set_block_number(System::block_number() + 1);
         Scheduler::on_initialize(System::block_number());
+        RewardsModule::on_initialize(System::block_number());
     }
 }
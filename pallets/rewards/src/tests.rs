@@ -1,79 +1,74 @@
 use frame_support::{assert_noop, assert_ok};
 use crate::{
-    mock::*, Error, PoolId, PoolInfo
+    mock::*, EmissionCurve, EmissionSchedule, EmissionSource, Error, PoolId, PoolInfo, YieldFarmingSchedule
 };
 use orml_traits::MultiCurrency;
+use primitives::{Balance, CurrencyId};
+use sp_runtime::{traits::BadOrigin, FixedPointNumber, FixedU128, Permill};
+use sp_std::collections::btree_map::BTreeMap;
+
+fn pool_info(total_shares: Balance, total_rewards: Balance, total_withdrawn_rewards: Balance) -> PoolInfo<Balance> {
+    let mut rewards = BTreeMap::new();
+    if total_rewards > 0 || total_withdrawn_rewards > 0 {
+        rewards.insert(BOLT, (total_rewards, total_withdrawn_rewards));
+    }
+    PoolInfo { total_shares, rewards }
+}
+
+fn withdrawn(amount: Balance) -> BTreeMap<CurrencyId, Balance> {
+    let mut withdrawn_rewards = BTreeMap::new();
+    if amount > 0 {
+        withdrawn_rewards.insert(BOLT, amount);
+    }
+    withdrawn_rewards
+}
 
 #[test]
 fn add_share_should_work() {
     ExtBuilder::default().build().execute_with(|| {
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 0,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(0, 0, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, withdrawn(0)));
 
         RewardsModule::add_share(&ALICE, BOLT_WUSD_POOL, 0);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 0,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(0, 0, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, withdrawn(0)));
 
         RewardsModule::add_share(&ALICE, BOLT_WUSD_POOL, 100);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(100, 0, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(0)));
 
         crate::Pools::<Test>::mutate(BOLT_WUSD_POOL, |pool_info| {
-            pool_info.total_rewards += 5000;
-            pool_info.total_withdrawn_rewards += 2000;
+            pool_info.rewards.entry(BOLT).or_default().0 += 5000;
+            pool_info.rewards.entry(BOLT).or_default().1 += 2000;
         });
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 5000,
-                total_withdrawn_rewards: 2000,
-            }
+            pool_info(100, 5000, 2000)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (0, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (0, withdrawn(0)));
 
         RewardsModule::add_share(&BOB, BOLT_WUSD_POOL, 50);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 150,
-                total_rewards: 7500,
-                total_withdrawn_rewards: 4500,
-            }
+            pool_info(150, 7500, 4500)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (50, 2500));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (50, withdrawn(2500)));
 
         RewardsModule::add_share(&ALICE, BOLT_WUSD_POOL, 150);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 300,
-                total_rewards: 15000,
-                total_withdrawn_rewards: 12000,
-            }
+            pool_info(300, 15000, 12000)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (250, 7500));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (250, withdrawn(7500)));
     });
 }
 
@@ -85,21 +80,17 @@ fn claim_rewards_should_work() {
 
         assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 5000));
         crate::Pools::<Test>::mutate(BOLT_WUSD_POOL, |pool_info| {
-            pool_info.total_rewards += 5000;
+            pool_info.rewards.entry(BOLT).or_default().0 += 5000;
         });
         RewardsModule::add_share(&CAROL, BOLT_WUSD_POOL, 200);
 
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 400,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 5000,
-            }
-        );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 0));
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, 0));
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, CAROL), (200, 5000));
+            pool_info(400, 10000, 5000)
+        );
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(0)));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, withdrawn(0)));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, CAROL), (200, withdrawn(5000)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 0);
         assert_eq!(Currencies::free_balance(BOLT, &CAROL), 0);
@@ -107,37 +98,25 @@ fn claim_rewards_should_work() {
         assert_ok!(RewardsModule::claim_rewards(&ALICE, BOLT_WUSD_POOL));
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 400,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 7500,
-            }
+            pool_info(400, 10000, 7500)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 2500));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(2500)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 2500);
 
         assert_ok!(RewardsModule::claim_rewards(&CAROL, BOLT_WUSD_POOL));
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 400,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 7500,
-            }
+            pool_info(400, 10000, 7500)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, CAROL), (200, 5000));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, CAROL), (200, withdrawn(5000)));
         assert_eq!(Currencies::free_balance(BOLT, &CAROL), 0);
 
         assert_ok!(RewardsModule::claim_rewards(&BOB, BOLT_WUSD_POOL));
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 400,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 10000,
-            }
+            pool_info(400, 10000, 10000)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, 2500));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, withdrawn(2500)));
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 2500);
     });
 }
@@ -150,19 +129,15 @@ fn remove_share_should_work() {
 
         assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 10000));
         crate::Pools::<Test>::mutate(BOLT_WUSD_POOL, |pool_info| {
-            pool_info.total_rewards += 10000;
+            pool_info.rewards.entry(BOLT).or_default().0 += 10000;
         });
 
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 200,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 0,
-            }
-        );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 0));
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, 0));
+            pool_info(200, 10000, 0)
+        );
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(0)));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (100, withdrawn(0)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 0);
 
@@ -170,37 +145,25 @@ fn remove_share_should_work() {
         RewardsModule::remove_share(&ALICE, BOLT_WUSD_POOL, 0);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 200,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(200, 10000, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(0)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
 
         RewardsModule::remove_share(&BOB, BOLT_WUSD_POOL, 50);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 150,
-                total_rewards: 7500,
-                total_withdrawn_rewards: 2500,
-            }
+            pool_info(150, 7500, 2500)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (50, 2500));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (50, withdrawn(2500)));
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 5000);
 
         RewardsModule::remove_share(&ALICE, BOLT_WUSD_POOL, 101);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 50,
-                total_rewards: 2501,
-                total_withdrawn_rewards: 2500,
-            }
+            pool_info(50, 2501, 2500)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, withdrawn(0)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 4999);
     });
 }
@@ -210,60 +173,40 @@ fn set_share_should_work() {
     ExtBuilder::default().build().execute_with(|| {
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 0,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(0, 0, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (0, withdrawn(0)));
 
         RewardsModule::set_share(&ALICE, BOLT_WUSD_POOL, 100);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(100, 0, 0)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 0));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(0)));
 
         assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 10000));
         crate::Pools::<Test>::mutate(BOLT_WUSD_POOL, |pool_info| {
-            pool_info.total_rewards += 10000;
+            pool_info.rewards.entry(BOLT).or_default().0 += 10000;
         });
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 0,
-            }
+            pool_info(100, 10000, 0)
         );
 
         RewardsModule::set_share(&ALICE, BOLT_WUSD_POOL, 500);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 500,
-                total_rewards: 50000,
-                total_withdrawn_rewards: 40000,
-            }
+            pool_info(500, 50000, 40000)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (500, 40000));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (500, withdrawn(40000)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
 
         RewardsModule::set_share(&ALICE, BOLT_WUSD_POOL, 100);
         assert_eq!(
             RewardsModule::pools(BOLT_WUSD_POOL),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 10000,
-                total_withdrawn_rewards: 10000,
-            }
+            pool_info(100, 10000, 10000)
         );
-        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, 10000));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE), (100, withdrawn(10000)));
         assert_eq!(Currencies::free_balance(BOLT, &ALICE), 10000);
     });
 }
@@ -275,25 +218,284 @@ fn scheduling_yield_farming_rewards() {
         assert_ok!(Currencies::deposit(BOLT, &ALICE, 500));
         assert_ok!(RewardsModule::schedule_yield_farming_rewards(
             Origin::signed(ALICE),
-            BOLT_WUSD_LP,
+            BOLT_WUSD_POOL,
+            BOLT,
             vec![(100, 3, 10, 5)]
         ));
+        let total_rewards = || RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT).map(|r| r.0).unwrap_or_default();
         assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 500);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 0);
+        assert_eq!(total_rewards(), 0);
         run_to_block(3);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 100);
+        assert_eq!(total_rewards(), 100);
         assert!(System::events().iter().any(|record| record.event ==
-            Event::pallet_rewards(crate::Event::YieldFarmingReward(BOLT_WUSD_POOL, 100))));
+            Event::pallet_rewards(crate::Event::YieldFarmingReward(BOLT_WUSD_POOL, BOLT, 100))));
         run_to_block(13);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 200);
+        assert_eq!(total_rewards(), 200);
         run_to_block(23);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 300);
+        assert_eq!(total_rewards(), 300);
         run_to_block(33);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 400);
+        assert_eq!(total_rewards(), 400);
         run_to_block(43);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 500);
+        assert_eq!(total_rewards(), 500);
         run_to_block(50);
-        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_rewards, 500);
+        assert_eq!(total_rewards(), 500);
+    })
+}
+
+#[test]
+fn yield_farming_schedules_are_tracked() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(BOLT, &ALICE, 1000));
+        // two schedules of the same pool and currency do not collide
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(ALICE),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(100, 3, 10, 2), (200, 5, 10, 2)]
+        ));
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 600);
+        assert_eq!(RewardsModule::yield_farming_schedule_count(), 2);
+        assert_eq!(RewardsModule::sponsor_yield_farming_schedules(ALICE), vec![0, 1]);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::YieldFarmingScheduled(ALICE, 1, BOLT_WUSD_POOL, BOLT))));
+        assert_eq!(
+            RewardsModule::get_yield_farming_schedules_of_account(&ALICE),
+            vec![
+                (0, YieldFarmingSchedule {
+                    sponsor: ALICE,
+                    pool_id: BOLT_WUSD_POOL,
+                    currency_id: BOLT,
+                    amount: 100,
+                    start_blk: 3,
+                    interval: 10,
+                    repeats: 2,
+                    released: 0,
+                }),
+                (1, YieldFarmingSchedule {
+                    sponsor: ALICE,
+                    pool_id: BOLT_WUSD_POOL,
+                    currency_id: BOLT,
+                    amount: 200,
+                    start_blk: 5,
+                    interval: 10,
+                    repeats: 2,
+                    released: 0,
+                }),
+            ]
+        );
+
+        run_to_block(5);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(300, 0)));
+        assert_eq!(RewardsModule::yield_farming_schedules(0).map(|schedule| schedule.released), Some(1));
+        assert_eq!(RewardsModule::yield_farming_schedules(1).map(|schedule| schedule.released), Some(1));
+
+        run_to_block(13);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(400, 0)));
+        assert_eq!(RewardsModule::yield_farming_schedules(0), None);
+        assert_eq!(RewardsModule::sponsor_yield_farming_schedules(ALICE), vec![1]);
+
+        run_to_block(15);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(600, 0)));
+        assert!(RewardsModule::get_yield_farming_schedules_of_account(&ALICE).is_empty());
+    })
+}
+
+#[test]
+fn cancel_yield_farming_schedule_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(BOLT, &ALICE, 500));
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(ALICE),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(100, 3, 10, 5)]
+        ));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
+
+        assert_noop!(
+            RewardsModule::cancel_yield_farming_schedule(Origin::signed(ALICE), 1),
+            Error::<Test>::InvalidScheduleIndex
+        );
+        assert_noop!(
+            RewardsModule::cancel_yield_farming_schedule(Origin::signed(BOB), 0),
+            Error::<Test>::NotScheduleSponsor
+        );
+
+        run_to_block(13);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(200, 0)));
+
+        // the three repetitions left are refunded
+        assert_ok!(RewardsModule::cancel_yield_farming_schedule(Origin::signed(ALICE), 0));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::YieldFarmingScheduleCancelled(ALICE, 0, 300))));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 300);
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 200);
+        assert_eq!(RewardsModule::yield_farming_schedules(0), None);
+        assert!(RewardsModule::sponsor_yield_farming_schedules(ALICE).is_empty());
+
+        run_to_block(50);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(200, 0)));
+        assert_noop!(
+            RewardsModule::cancel_yield_farming_schedule(Origin::signed(ALICE), 0),
+            Error::<Test>::InvalidScheduleIndex
+        );
+    })
+}
+
+#[test]
+fn scheduling_rewards_with_invalid_pool_or_currency_fails() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(BOLT, &ALICE, 500));
+        assert_noop!(
+            RewardsModule::schedule_yield_farming_rewards(
+                Origin::signed(ALICE),
+                PoolId::DexYieldFarming(NCAT),
+                BOLT,
+                vec![(100, 3, 10, 5)]
+            ),
+            Error::<Test>::InvalidPoolId
+        );
+        assert_noop!(
+            RewardsModule::schedule_yield_farming_rewards(
+                Origin::signed(ALICE),
+                PoolId::Staking(WUSD_NCAT_LP),
+                BOLT,
+                vec![(100, 3, 10, 5)]
+            ),
+            Error::<Test>::InvalidPoolId
+        );
+        assert_noop!(
+            RewardsModule::schedule_yield_farming_rewards(
+                Origin::signed(ALICE),
+                BOLT_WUSD_POOL,
+                WUSD_NCAT_LP,
+                vec![(100, 3, 10, 5)]
+            ),
+            Error::<Test>::InvalidCurrencyId
+        );
+    })
+}
+
+#[test]
+fn scheduling_rewards_without_repetitions_fails() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(BOLT, &ALICE, 500));
+        assert_noop!(
+            RewardsModule::schedule_yield_farming_rewards(
+                Origin::signed(ALICE),
+                BOLT_WUSD_POOL,
+                BOLT,
+                vec![(100, 3, 10, 0)]
+            ),
+            Error::<Test>::InvalidRepetitions
+        );
+        assert_noop!(
+            RewardsModule::schedule_yield_farming_rewards(
+                Origin::signed(ALICE),
+                BOLT_WUSD_POOL,
+                BOLT,
+                vec![(100, 3, 0, 5)]
+            ),
+            Error::<Test>::InvalidRepetitions
+        );
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(ALICE),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(100, 3, 0, 1)]
+        ));
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 100);
+
+        // the single repetition is released once, however often the release is dispatched
+        assert_ok!(RewardsModule::release_yield_farming_reward(Origin::root(), 0));
+        assert_noop!(
+            RewardsModule::release_yield_farming_reward(Origin::root(), 0),
+            Error::<Test>::InvalidScheduleIndex
+        );
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(100, 0)));
+    })
+}
+
+#[test]
+fn rewards_in_several_currencies_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(BOLT, &CAROL, 500));
+        assert_ok!(Currencies::deposit(WUSD, &CAROL, 1000));
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(CAROL),
+            BOLT_WUSD_POOL,
+            BOLT,
+            vec![(500, 3, 10, 1)]
+        ));
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(CAROL),
+            BOLT_WUSD_POOL,
+            WUSD,
+            vec![(1000, 3, 10, 1)]
+        ));
+
+        RewardsModule::add_share(&ALICE, BOLT_WUSD_POOL, 100);
+        RewardsModule::add_share(&BOB, BOLT_WUSD_POOL, 300);
+        run_to_block(3);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(500, 0)));
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&WUSD), Some(&(1000, 0)));
+
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(ALICE), BOLT_WUSD_POOL));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 125);
+        assert_eq!(Currencies::free_balance(WUSD, &ALICE), 250);
+        let (share, withdrawn_rewards) = RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, ALICE);
+        assert_eq!(share, 100);
+        assert_eq!(withdrawn_rewards.get(&BOLT), Some(&125));
+        assert_eq!(withdrawn_rewards.get(&WUSD), Some(&250));
+
+        // bob leaves with his rewards in both currencies
+        RewardsModule::remove_share(&BOB, BOLT_WUSD_POOL, 300);
+        assert_eq!(Currencies::free_balance(BOLT, &BOB), 375);
+        assert_eq!(Currencies::free_balance(WUSD, &BOB), 750);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(BOLT_WUSD_POOL, BOB), (0, withdrawn(0)));
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).total_shares, 100);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(125, 125)));
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&WUSD), Some(&(250, 250)));
+    })
+}
+
+#[test]
+fn stake_and_unstake_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        System::set_block_number(1);
+        let pool = PoolId::Staking(BOLT);
+        assert_ok!(Currencies::deposit(BOLT, &ALICE, 1000));
+
+        assert_noop!(
+            RewardsModule::stake(Origin::signed(ALICE), WUSD_NCAT_LP, 100),
+            Error::<Test>::InvalidPoolId
+        );
+        assert_noop!(
+            RewardsModule::unstake(Origin::signed(ALICE), BOLT, 100),
+            Error::<Test>::NotEnough
+        );
+
+        assert_ok!(RewardsModule::stake(Origin::signed(ALICE), BOLT, 400));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::Staked(ALICE, BOLT, 400))));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 600);
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 400);
+        assert_eq!(RewardsModule::pools(pool), pool_info(400, 0, 0));
+
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 200));
+        RewardsModule::add_reward(pool, WUSD, 200);
+
+        assert_ok!(RewardsModule::unstake(Origin::signed(ALICE), BOLT, 400));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::Unstaked(ALICE, BOLT, 400))));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 1000);
+        assert_eq!(Currencies::free_balance(WUSD, &ALICE), 200);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (0, withdrawn(0)));
     })
 }
 
@@ -309,15 +511,11 @@ fn deposit_dex_share_works() {
         );
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(WUSD_NCAT_LP)),
-            PoolInfo {
-                total_shares: 0,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(0, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(WUSD_NCAT_LP), ALICE),
-            (0, 0)
+            (0, withdrawn(0))
         );
         assert_ok!(RewardsModule::deposit_dex_share(
 			Origin::signed(ALICE),
@@ -334,15 +532,11 @@ fn deposit_dex_share_works() {
         );
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(WUSD_NCAT_LP)),
-            PoolInfo {
-                total_shares: 10000,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(10000, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(WUSD_NCAT_LP), ALICE),
-            (10000, 0)
+            (10000, withdrawn(0))
         );
     });
 }
@@ -370,15 +564,11 @@ fn withdraw_dex_share_works() {
         );
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(WUSD_NCAT_LP)),
-            PoolInfo {
-                total_shares: 10000,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(10000, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(WUSD_NCAT_LP), ALICE),
-            (10000, 0)
+            (10000, withdrawn(0))
         );
 
         assert_ok!(RewardsModule::withdraw_dex_share(
@@ -398,15 +588,11 @@ fn withdraw_dex_share_works() {
         );
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(WUSD_NCAT_LP)),
-            PoolInfo {
-                total_shares: 2000,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(2000, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(WUSD_NCAT_LP), ALICE),
-            (2000, 0)
+            (2000, withdrawn(0))
         );
     });
 }
@@ -416,42 +602,30 @@ fn on_add_liquidity_works() {
     ExtBuilder::default().build().execute_with(|| {
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(NCAT)),
-            PoolInfo {
-                total_shares: 0,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(0, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), ALICE),
-            (0, 0)
+            (0, withdrawn(0))
         );
         RewardsModule::add_share(&ALICE, PoolId::DexYieldFarming(NCAT), 100);
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(NCAT)),
-            PoolInfo {
-                total_shares: 100,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(100, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), ALICE),
-            (100, 0)
+            (100, withdrawn(0))
         );
 
         RewardsModule::add_share(&BOB, PoolId::DexYieldFarming(NCAT), 100);
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(NCAT)),
-            PoolInfo {
-                total_shares: 200,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(200, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), BOB),
-            (100, 0)
+            (100, withdrawn(0))
         );
     });
 }
@@ -463,38 +637,30 @@ fn on_remove_liquidity_works() {
         RewardsModule::add_share(&BOB, PoolId::DexYieldFarming(NCAT), 100);
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(NCAT)),
-            PoolInfo {
-                total_shares: 200,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(200, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), ALICE),
-            (100, 0)
+            (100, withdrawn(0))
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), BOB),
-            (100, 0)
+            (100, withdrawn(0))
         );
 
         RewardsModule::remove_share(&ALICE, PoolId::DexYieldFarming(NCAT), 40);
         RewardsModule::remove_share(&BOB, PoolId::DexYieldFarming(NCAT), 70);
         assert_eq!(
             RewardsModule::pools(PoolId::DexYieldFarming(NCAT)),
-            PoolInfo {
-                total_shares: 90,
-                total_rewards: 0,
-                total_withdrawn_rewards: 0
-            }
+            pool_info(90, 0, 0)
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), ALICE),
-            (60, 0)
+            (60, withdrawn(0))
         );
         assert_eq!(
             RewardsModule::share_and_withdrawn_reward(PoolId::DexYieldFarming(NCAT), BOB),
-            (30, 0)
+            (30, withdrawn(0))
         );
     });
 }
@@ -507,8 +673,540 @@ fn payout_works() {
 
         assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 10000);
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 0);
-        RewardsModule::payout(&BOB, PoolId::DexYieldFarming(NCAT), 1000);
+        RewardsModule::payout(&BOB, BOLT, 1000);
         assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 9000);
         assert_eq!(Currencies::free_balance(BOLT, &BOB), 1000);
+
+        RewardsModule::payout(&BOB, WUSD, 2000);
+        assert_eq!(Currencies::free_balance(WUSD, &RewardsModule::account_id()), 8000);
+        assert_eq!(Currencies::free_balance(WUSD, &BOB), 2000);
+    });
+}
+
+#[test]
+fn lock_dex_share_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 1000));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+
+        assert_noop!(
+            RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000, 5),
+            Error::<Test>::InvalidLockPeriod
+        );
+        assert_noop!(
+            RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000, 101),
+            Error::<Test>::InvalidLockPeriod
+        );
+        assert_noop!(
+            RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1001, 100),
+            Error::<Test>::NotEnough
+        );
+
+        // locked for MaxLockPeriod, the share is doubled
+        assert_ok!(RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000, 100));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::DexShareLocked(ALICE, WUSD_NCAT_LP, 1000, 1000, 101))));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (2000, withdrawn(0)));
+        assert_eq!(RewardsModule::pools(pool).total_shares, 2000);
+        assert_noop!(
+            RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000, 100),
+            Error::<Test>::DexShareAlreadyLocked
+        );
+        assert_noop!(
+            RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1),
+            Error::<Test>::NotEnough
+        );
+
+        // the boost decays every LockDecayPeriod
+        run_to_block(11);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (1900, withdrawn(0)));
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, ALICE).map(|lock| lock.boost), Some(900));
+        run_to_block(91);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (1100, withdrawn(0)));
+
+        run_to_block(101);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::DexShareUnlocked(ALICE, WUSD_NCAT_LP, 1000))));
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, ALICE), None);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (1000, withdrawn(0)));
+        assert_eq!(RewardsModule::pools(pool).total_shares, 1000);
+        assert_ok!(RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+        assert_eq!(Currencies::free_balance(WUSD_NCAT_LP, &ALICE), 1000);
+    });
+}
+
+#[test]
+fn decay_dex_share_locks_is_bounded_per_block() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        for who in vec![ALICE, BOB, CAROL] {
+            assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &who, 1000));
+            assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(who), WUSD_NCAT_LP, 1000));
+            assert_ok!(RewardsModule::lock_dex_share(Origin::signed(who), WUSD_NCAT_LP, 1000, 100));
+        }
+
+        // MaxLockDecaysPerBlock is 2, carol waits for the next block
+        run_to_block(11);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 1900);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, BOB).0, 1900);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, CAROL).0, 2000);
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, CAROL).map(|lock| lock.next_decay_blk), Some(12));
+
+        run_to_block(12);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, CAROL).0, 1890);
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, ALICE).map(|lock| lock.next_decay_blk), Some(21));
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, CAROL).map(|lock| lock.next_decay_blk), Some(22));
+        assert_eq!(RewardsModule::pools(pool).total_shares, 5690);
+    });
+}
+
+#[test]
+fn locked_dex_share_earns_boosted_rewards() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 100));
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &BOB, 100));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(BOB), WUSD_NCAT_LP, 100));
+
+        // half of MaxLockPeriod, half of the boost
+        assert_ok!(RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100, 50));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (150, withdrawn(0)));
+
+        assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 250));
+        RewardsModule::add_reward(pool, BOLT, 250);
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(ALICE), pool));
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(BOB), pool));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 150);
+        assert_eq!(Currencies::free_balance(BOLT, &BOB), 100);
+    });
+}
+
+#[test]
+fn unlock_dex_share_early_forfeits_pending_rewards() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 100));
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &BOB, 200));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(BOB), WUSD_NCAT_LP, 200));
+
+        assert_noop!(
+            RewardsModule::unlock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP),
+            Error::<Test>::DexShareNotLocked
+        );
+        assert_ok!(RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100, 100));
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (200, withdrawn(0)));
+
+        assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 400));
+        RewardsModule::add_reward(pool, BOLT, 400);
+
+        // the 200 pending for alice go back to the pool rewards, and 10 of her 100 dex shares
+        // to the treasury
+        assert_ok!(RewardsModule::unlock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::DexShareUnlockedEarly(ALICE, WUSD_NCAT_LP, 100, 10))));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::YieldFarmingReward(pool, BOLT, 200))));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 0);
+        assert_eq!(Currencies::free_balance(WUSD_NCAT_LP, &TREASURY), 10);
+        assert_eq!(RewardsModule::dex_share_locks(WUSD_NCAT_LP, ALICE), None);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE), (90, withdrawn(90)));
+        assert_eq!(RewardsModule::pools(pool), pool_info(290, 490, 90));
+
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(ALICE), pool));
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(BOB), pool));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 62);
+        assert_eq!(Currencies::free_balance(BOLT, &BOB), 337);
+
+        // the lock no longer holds the shares back, nor decays them
+        run_to_block(11);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 90);
+        assert_ok!(RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 90));
+        assert_eq!(Currencies::free_balance(WUSD_NCAT_LP, &ALICE), 90);
+    });
+}
+
+#[test]
+fn unlock_dex_share_early_after_claiming_pays_the_fee() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 100));
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &BOB, 200));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(BOB), WUSD_NCAT_LP, 200));
+        assert_ok!(RewardsModule::lock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100, 100));
+
+        assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 400));
+        RewardsModule::add_reward(pool, BOLT, 400);
+
+        // alice claims the rewards of her boost first, nothing is left to forfeit
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(ALICE), pool));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 200);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, pool), vec![]);
+
+        // the early unlock still costs her the fee on the locked dex shares
+        assert_ok!(RewardsModule::unlock_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::DexShareUnlockedEarly(ALICE, WUSD_NCAT_LP, 100, 10))));
+        assert_eq!(Currencies::free_balance(WUSD_NCAT_LP, &TREASURY), 10);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 90);
+        assert_eq!(RewardsModule::pools(pool).total_shares, 290);
+        assert_noop!(
+            RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 100),
+            Error::<Test>::NotEnough
+        );
+        assert_ok!(RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 90));
+        assert_eq!(Currencies::free_balance(WUSD_NCAT_LP, &ALICE), 90);
+    });
+}
+
+#[test]
+fn get_pending_rewards_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        RewardsModule::add_share(&ALICE, BOLT_WUSD_POOL, 100);
+        RewardsModule::add_share(&BOB, BOLT_WUSD_POOL, 300);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, BOLT_WUSD_POOL), vec![]);
+
+        assert_ok!(Currencies::deposit(BOLT, &RewardsModule::account_id(), 400));
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 800));
+        RewardsModule::add_reward(BOLT_WUSD_POOL, BOLT, 400);
+        RewardsModule::add_reward(BOLT_WUSD_POOL, WUSD, 800);
+        assert_eq!(
+            RewardsModule::get_pending_rewards(&ALICE, BOLT_WUSD_POOL),
+            vec![(BOLT, 100), (WUSD, 200)]
+        );
+        assert_eq!(RewardsModule::get_pending_rewards(&CAROL, BOLT_WUSD_POOL), vec![]);
+
+        // the pending rewards are what claim_reward pays
+        assert_ok!(RewardsModule::claim_reward(Origin::signed(ALICE), BOLT_WUSD_POOL));
+        assert_eq!(Currencies::free_balance(BOLT, &ALICE), 100);
+        assert_eq!(Currencies::free_balance(WUSD, &ALICE), 200);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, BOLT_WUSD_POOL), vec![]);
+        assert_eq!(
+            RewardsModule::get_pending_rewards(&BOB, BOLT_WUSD_POOL),
+            vec![(BOLT, 300), (WUSD, 600)]
+        );
+    });
+}
+
+#[test]
+fn get_yield_farming_apr_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_eq!(RewardsModule::get_yield_farming_apr(pool), vec![]);
+
+        // all the lp tokens are deposited, worth 2 * 100 NCAT
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 1000));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+
+        assert_ok!(Currencies::deposit(NCAT, &CAROL, 500));
+        assert_ok!(Currencies::deposit(WUSD, &CAROL, 250));
+        assert_ok!(Currencies::deposit(BOLT, &CAROL, 500));
+        // 500 NCAT released within the year
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(CAROL),
+            pool,
+            NCAT,
+            vec![(100, 3, 100, 5)]
+        ));
+        // 100 of the 250 WUSD released within the year, worth 20 NCAT
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(CAROL),
+            pool,
+            WUSD,
+            vec![(50, 3, 500, 5)]
+        ));
+        // no dex price for BOLT
+        assert_ok!(RewardsModule::schedule_yield_farming_rewards(
+            Origin::signed(CAROL),
+            pool,
+            BOLT,
+            vec![(100, 3, 100, 5)]
+        ));
+        assert_eq!(RewardsModule::get_yield_farming_schedules_of_pool(pool).len(), 3);
+        assert_eq!(RewardsModule::get_yield_farming_schedules_of_pool(BOLT_WUSD_POOL), vec![]);
+
+        assert_eq!(
+            RewardsModule::get_yield_farming_apr(pool),
+            vec![
+                (WUSD, FixedU128::saturating_from_rational(1, 10)),
+                (NCAT, FixedU128::saturating_from_rational(5, 2)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn auto_compound_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 1000));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+
+        assert_noop!(
+            RewardsModule::set_auto_compound(Origin::signed(ALICE), WUSD, true),
+            Error::<Test>::InvalidCurrencyId
+        );
+        // compounded at the next AccumulatePeriod
+        assert_ok!(RewardsModule::set_auto_compound(Origin::signed(ALICE), WUSD_NCAT_LP, true));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::AutoCompoundSet(ALICE, WUSD_NCAT_LP, true))));
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), Some(10));
+
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 1000));
+        assert_ok!(Currencies::deposit(NCAT, &RewardsModule::account_id(), 100));
+        RewardsModule::add_reward(pool, WUSD, 1000);
+        RewardsModule::add_reward(pool, NCAT, 100);
+
+        // 900 WUSD and 90 NCAT after the fee, 225 WUSD swapped into 45 NCAT to even the value
+        run_to_block(10);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::AutoCompounded(ALICE, WUSD_NCAT_LP, 810))));
+        assert_eq!(Currencies::free_balance(WUSD, &TREASURY), 100);
+        assert_eq!(Currencies::free_balance(NCAT, &TREASURY), 10);
+        assert_eq!(Currencies::free_balance(WUSD, &ALICE), 0);
+        assert_eq!(Currencies::free_balance(NCAT, &ALICE), 0);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 1810);
+        assert_eq!(RewardsModule::pools(pool).total_shares, 1810);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, pool), vec![]);
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), Some(20));
+
+        // switched off, the rewards are left pending
+        assert_ok!(RewardsModule::set_auto_compound(Origin::signed(ALICE), WUSD_NCAT_LP, false));
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), None);
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 1000));
+        RewardsModule::add_reward(pool, WUSD, 1000);
+        run_to_block(20);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 1810);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, pool), vec![(WUSD, 1000)]);
+    });
+}
+
+#[test]
+fn auto_compound_is_bounded_per_block() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        for who in vec![ALICE, BOB, CAROL] {
+            assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &who, 100));
+            assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(who), WUSD_NCAT_LP, 100));
+            assert_ok!(RewardsModule::set_auto_compound(Origin::signed(who), WUSD_NCAT_LP, true));
+        }
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 300));
+        assert_ok!(Currencies::deposit(NCAT, &RewardsModule::account_id(), 60));
+        RewardsModule::add_reward(pool, WUSD, 300);
+        RewardsModule::add_reward(pool, NCAT, 60);
+
+        // MaxAutoCompoundsPerBlock is 2, carol waits for the next block
+        run_to_block(10);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 208);
+        assert!(RewardsModule::share_and_withdrawn_reward(pool, BOB).0 > 200);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, CAROL).0, 100);
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, CAROL), Some(11));
+
+        run_to_block(11);
+        assert!(RewardsModule::share_and_withdrawn_reward(pool, CAROL).0 > 200);
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), Some(20));
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, CAROL), Some(21));
+    });
+}
+
+#[test]
+fn auto_compound_is_skipped_beyond_max_slippage() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let pool = PoolId::DexYieldFarming(WUSD_NCAT_LP);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 1000));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+        assert_ok!(RewardsModule::set_auto_compound(Origin::signed(ALICE), WUSD_NCAT_LP, true));
+        assert_ok!(Currencies::deposit(WUSD, &RewardsModule::account_id(), 1000));
+        RewardsModule::add_reward(pool, WUSD, 1000);
+
+        // the swap gets 10% below the pool price, AutoCompoundMaxSlippage is 5%
+        set_swap_slippage(Permill::from_percent(10));
+        run_to_block(10);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::AutoCompoundFailed(ALICE, WUSD_NCAT_LP))));
+        assert_eq!(Currencies::free_balance(WUSD, &TREASURY), 0);
+        assert_eq!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0, 1000);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, pool), vec![(WUSD, 1000)]);
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), Some(20));
+
+        // compounded at the next period within the slippage
+        set_swap_slippage(Permill::from_percent(1));
+        run_to_block(20);
+        assert!(RewardsModule::share_and_withdrawn_reward(pool, ALICE).0 > 1000);
+        assert_eq!(RewardsModule::get_pending_rewards(&ALICE, pool), vec![]);
+    });
+}
+
+#[test]
+fn auto_compound_drops_accounts_without_share() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(Currencies::deposit(WUSD_NCAT_LP, &ALICE, 1000));
+        assert_ok!(RewardsModule::deposit_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+        assert_ok!(RewardsModule::set_auto_compound(Origin::signed(ALICE), WUSD_NCAT_LP, true));
+        assert_ok!(RewardsModule::set_auto_compound(Origin::signed(BOB), WUSD_NCAT_LP, true));
+        assert_ok!(RewardsModule::withdraw_dex_share(Origin::signed(ALICE), WUSD_NCAT_LP, 1000));
+
+        run_to_block(10);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::AutoCompoundSet(ALICE, WUSD_NCAT_LP, false))));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::AutoCompoundSet(BOB, WUSD_NCAT_LP, false))));
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, ALICE), None);
+        assert_eq!(RewardsModule::auto_compounds(WUSD_NCAT_LP, BOB), None);
+        assert!(RewardsModule::auto_compound_queue(20).is_empty());
+    });
+}
+
+#[test]
+fn set_emission_pool_weight_works() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        assert_noop!(
+            RewardsModule::set_emission_pool_weight(Origin::signed(ALICE), BOLT_WUSD_POOL, 1),
+            BadOrigin
+        );
+        assert_noop!(
+            RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::DexYieldFarming(WUSD), 1),
+            Error::<Test>::InvalidPoolId
+        );
+
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), BOLT_WUSD_POOL, 1));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::EmissionPoolWeightSet(BOLT_WUSD_POOL, 1))));
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(BOLT), 3));
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(WUSD), 1));
+        // MaxEmissionPools is 3
+        assert_noop!(
+            RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(NCAT), 1),
+            Error::<Test>::TooManyEmissionPools
+        );
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(WUSD), 2));
+        assert_eq!(RewardsModule::emission_pool_weights(PoolId::Staking(WUSD)), 2);
+
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(WUSD), 0));
+        assert_eq!(RewardsModule::emission_pool_weights(PoolId::Staking(WUSD)), 0);
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), PoolId::Staking(NCAT), 1));
+    });
+}
+
+#[test]
+fn minted_emission_halves_until_the_cap() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let staking_pool = PoolId::Staking(BOLT);
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), BOLT_WUSD_POOL, 1));
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), staking_pool, 3));
+
+        let schedule = EmissionSchedule {
+            source: EmissionSource::Mint,
+            initial_emission: 1000,
+            curve: EmissionCurve::Halving(10),
+            start_blk: 10,
+            cap: 1600,
+        };
+        assert_noop!(
+            RewardsModule::set_emission_schedule(Origin::signed(ALICE), Some(schedule.clone())),
+            BadOrigin
+        );
+        assert_noop!(
+            RewardsModule::set_emission_schedule(
+                Origin::root(),
+                Some(EmissionSchedule { curve: EmissionCurve::Halving(0), ..schedule.clone() })
+            ),
+            Error::<Test>::InvalidEmissionSchedule
+        );
+        assert_ok!(RewardsModule::set_emission_schedule(Origin::root(), Some(schedule.clone())));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::EmissionScheduleSet(Some(schedule.clone())))));
+
+        run_to_block(10);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::Emitted(10, 1000, 1000))));
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::YieldFarmingReward(BOLT_WUSD_POOL, BOLT, 250))));
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 1000);
+
+        // 500 after the first halving, then capped at 100
+        run_to_block(20);
+        assert_eq!(RewardsModule::total_emitted(), 1500);
+        run_to_block(30);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::Emitted(30, 100, 1600))));
+        run_to_block(40);
+        assert_eq!(RewardsModule::total_emitted(), 1600);
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 1600);
+        assert_eq!(RewardsModule::pools(BOLT_WUSD_POOL).rewards.get(&BOLT), Some(&(400, 0)));
+        assert_eq!(RewardsModule::pools(staking_pool).rewards.get(&BOLT), Some(&(1200, 0)));
+
+        // the emissions stop with the schedule
+        assert_ok!(RewardsModule::set_emission_schedule(Origin::root(), None));
+        assert_eq!(RewardsModule::total_emitted(), 1600);
+        run_to_block(50);
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 1600);
+
+        // the cap of a new schedule counts the emissions of the previous ones
+        let schedule = EmissionSchedule {
+            curve: EmissionCurve::Constant,
+            start_blk: 60,
+            cap: 1600,
+            ..schedule
+        };
+        assert_noop!(
+            RewardsModule::set_emission_schedule(Origin::root(), Some(schedule.clone())),
+            Error::<Test>::InvalidEmissionSchedule
+        );
+        assert_ok!(RewardsModule::set_emission_schedule(
+            Origin::root(),
+            Some(EmissionSchedule { cap: 2000, ..schedule })
+        ));
+        run_to_block(70);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::Emitted(60, 400, 2000))));
+        assert_eq!(RewardsModule::total_emitted(), 2000);
+        assert_eq!(Currencies::free_balance(BOLT, &RewardsModule::account_id()), 2000);
+    });
+}
+
+#[test]
+fn treasury_emission_decays_while_funded() {
+    ExtBuilder::default().build().execute_with(|| {
+        run_to_block(1);
+        let staking_pool = PoolId::Staking(BOLT);
+        assert_ok!(Currencies::deposit(BOLT, &TREASURY, 1900));
+        assert_ok!(RewardsModule::set_emission_pool_weight(Origin::root(), staking_pool, 1));
+        assert_ok!(RewardsModule::set_emission_schedule(Origin::root(), Some(EmissionSchedule {
+            source: EmissionSource::Treasury,
+            initial_emission: 1000,
+            curve: EmissionCurve::Decay(10, Permill::from_percent(10)),
+            start_blk: 5,
+            cap: Balance::MAX,
+        })));
+
+        // emitted at the AccumulatePeriod boundaries from the start
+        run_to_block(10);
+        assert_eq!(RewardsModule::total_emitted(), 1000);
+        run_to_block(20);
+        assert_eq!(RewardsModule::total_emitted(), 1900);
+        assert_eq!(Currencies::free_balance(BOLT, &TREASURY), 0);
+
+        // 810 left to emit, the treasury is empty
+        run_to_block(30);
+        assert!(System::events().iter().any(|record| record.event ==
+            Event::pallet_rewards(crate::Event::EmissionFailed(30, 810))));
+        assert_eq!(RewardsModule::total_emitted(), 1900);
+        assert_eq!(RewardsModule::pools(staking_pool).rewards.get(&BOLT), Some(&(1900, 0)));
     });
 }
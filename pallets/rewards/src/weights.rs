@@ -0,0 +1,147 @@
+//! Estimated weights for pallet_rewards
+//!
+//! NOT GENERATED BY THE BENCHMARK CLI. The values are estimated by hand from the storage reads
+//! and writes of each call and from the benchmarked weights of similar calls of
+//! pallet_bullet_train. Regenerate this file with the command below before relying on them.
+
+// Regenerate with:
+// ./target/release/substrate
+// benchmark
+// --chain=spanner-dev
+// --steps=50
+// --repeat=20
+// --pallet=pallet_rewards
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --heap-pages=4096
+// --output=./pallets/rewards/src/weights.rs
+// --template=./template.hbs
+
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_rewards.
+pub trait WeightInfo {
+	fn deposit_dex_share() -> Weight;
+	fn withdraw_dex_share() -> Weight;
+	fn claim_reward() -> Weight;
+	fn schedule_yield_farming_rewards(r: u32, ) -> Weight;
+	fn add_yield_farming_reward() -> Weight;
+	fn stake() -> Weight;
+	fn unstake() -> Weight;
+	fn release_yield_farming_reward() -> Weight;
+	fn cancel_yield_farming_schedule() -> Weight;
+	fn lock_dex_share() -> Weight;
+	fn unlock_dex_share() -> Weight;
+	fn decay_dex_share_locks(n: u32, ) -> Weight;
+	fn set_auto_compound() -> Weight;
+	fn auto_compound(n: u32, ) -> Weight;
+	fn set_emission_schedule() -> Weight;
+	fn set_emission_pool_weight() -> Weight;
+	fn emit(n: u32, ) -> Weight;
+}
+
+/// Weight functions for pallet_rewards.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn deposit_dex_share() -> Weight {
+		(86_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn withdraw_dex_share() -> Weight {
+		(132_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn claim_reward() -> Weight {
+		(78_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn schedule_yield_farming_rewards(r: u32, ) -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add((78_000_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((4 as Weight).saturating_mul(r as Weight)))
+			.saturating_add(T::DbWeight::get().writes((7 as Weight).saturating_mul(r as Weight)))
+	}
+	fn add_yield_farming_reward() -> Weight {
+		(21_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn stake() -> Weight {
+		(84_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn unstake() -> Weight {
+		(129_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn release_yield_farming_reward() -> Weight {
+		(29_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn cancel_yield_farming_schedule() -> Weight {
+		(83_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn lock_dex_share() -> Weight {
+		(58_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	fn unlock_dex_share() -> Weight {
+		(124_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	fn decay_dex_share_locks(n: u32, ) -> Weight {
+		(9_000_000 as Weight)
+			.saturating_add((96_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(n as Weight)))
+	}
+	fn set_auto_compound() -> Weight {
+		(31_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn auto_compound(n: u32, ) -> Weight {
+		(11_000_000 as Weight)
+			.saturating_add((243_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((14 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((13 as Weight).saturating_mul(n as Weight)))
+	}
+	fn set_emission_schedule() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn set_emission_pool_weight() -> Weight {
+		(41_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(21 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn emit(n: u32, ) -> Weight {
+		(38_000_000 as Weight)
+			.saturating_add((27_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+}
@@ -27,6 +27,8 @@ use sp_runtime::{
 
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use node_type_registry::TypeInfo;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -103,7 +105,7 @@ pub mod report {
 
 
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, PartialOrd, Ord)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
 pub enum TokenSymbol {
 	BOLT,
 	WUSD,
@@ -113,7 +115,7 @@ pub enum TokenSymbol {
 }
 
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, RuntimeDebug, PartialOrd, Ord)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, TypeInfo))]
 pub enum CurrencyId {
 	Token(TokenSymbol),
 	DexShare(TokenSymbol, TokenSymbol),
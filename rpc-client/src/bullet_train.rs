@@ -0,0 +1,180 @@
+//! Bullet-train calls and storage.
+
+use node_primitives::{AccountId, Balance, BlockNumber, Hash};
+use pallet_bullet_train::{
+	Buyer, DpoAccessPolicy, DpoInfo, DpoMemberInfo, Target, TravelCabinBuyerInfo, TravelCabinInfo,
+};
+use pallet_bullet_train_primitives::{DpoIndex, TravelCabinIndex, TravelCabinInventoryIndex};
+use spanner_runtime::{BulletTrainCall, Call, Runtime};
+use crate::{Client, Result, Signer};
+
+/// The bullet-train pallet, reached through [`Client::bullet_train`].
+///
+/// Every call is submitted with [`Client::submit_and_wait`].
+pub struct BulletTrain<'a> {
+	client: &'a Client,
+}
+
+impl<'a> BulletTrain<'a> {
+	pub(crate) fn new(client: &'a Client) -> Self {
+		BulletTrain { client }
+	}
+
+	/// Buy one of the travel cabins left of `travel_cabin_idx`.
+	pub fn passenger_buy_travel_cabin(&self, signer: &Signer, travel_cabin_idx: TravelCabinIndex) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::passenger_buy_travel_cabin(travel_cabin_idx))
+	}
+
+	/// Create a dpo crowdfunding `target`, the manager buying `manager_purchase_amount` of it.
+	#[allow(clippy::too_many_arguments)]
+	pub fn create_dpo(
+		&self,
+		signer: &Signer,
+		name: Vec<u8>,
+		target: Target<Balance>,
+		manager_purchase_amount: Balance,
+		base_fee: u32,
+		direct_referral_rate: u32,
+		end: BlockNumber,
+		referrer: Option<AccountId>,
+		access_policy: DpoAccessPolicy,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::create_dpo(
+			name,
+			target,
+			manager_purchase_amount,
+			base_fee,
+			direct_referral_rate,
+			end,
+			referrer,
+			access_policy,
+		))
+	}
+
+	/// Buy `amount` of the shares of `dpo_idx` as a passenger.
+	pub fn passenger_buy_dpo_share(
+		&self,
+		signer: &Signer,
+		dpo_idx: DpoIndex,
+		amount: Balance,
+		referrer: Option<AccountId>,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::passenger_buy_dpo_share(dpo_idx, amount, referrer))
+	}
+
+	/// Have the filled dpo `buyer_dpo_idx` buy its travel cabin target.
+	pub fn dpo_buy_travel_cabin(
+		&self,
+		signer: &Signer,
+		buyer_dpo_idx: DpoIndex,
+		travel_cabin_idx: TravelCabinIndex,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::dpo_buy_travel_cabin(buyer_dpo_idx, travel_cabin_idx))
+	}
+
+	/// Have the filled dpo `buyer_dpo_idx` buy `amount` of the shares of `target_dpo_idx`.
+	pub fn dpo_buy_dpo_share(
+		&self,
+		signer: &Signer,
+		buyer_dpo_idx: DpoIndex,
+		target_dpo_idx: DpoIndex,
+		amount: Balance,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::dpo_buy_dpo_share(buyer_dpo_idx, target_dpo_idx, amount))
+	}
+
+	/// Withdraw the fare of a matured travel cabin to its buyer.
+	pub fn withdraw_fare_from_travel_cabin(
+		&self,
+		signer: &Signer,
+		travel_cabin_idx: TravelCabinIndex,
+		travel_cabin_number: TravelCabinInventoryIndex,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::withdraw_fare_from_travel_cabin(travel_cabin_idx, travel_cabin_number))
+	}
+
+	/// Withdraw the yield accrued by a travel cabin to its buyer.
+	pub fn withdraw_yield_from_travel_cabin(
+		&self,
+		signer: &Signer,
+		travel_cabin_idx: TravelCabinIndex,
+		travel_cabin_number: TravelCabinInventoryIndex,
+	) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::withdraw_yield_from_travel_cabin(travel_cabin_idx, travel_cabin_number))
+	}
+
+	/// Release the fare received by `dpo_idx` to its members.
+	pub fn release_fare_from_dpo(&self, signer: &Signer, dpo_idx: DpoIndex) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::release_fare_from_dpo(dpo_idx))
+	}
+
+	/// Release the yield received by `dpo_idx` to its members.
+	pub fn release_yield_from_dpo(&self, signer: &Signer, dpo_idx: DpoIndex) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::release_yield_from_dpo(dpo_idx))
+	}
+
+	/// Release the bonus received by `dpo_idx` to its members.
+	pub fn release_bonus_from_dpo(&self, signer: &Signer, dpo_idx: DpoIndex) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::release_bonus_from_dpo(dpo_idx))
+	}
+
+	/// Pay `member` its share of the rewards released by `dpo_idx`.
+	pub fn claim_dpo_rewards(&self, signer: &Signer, dpo_idx: DpoIndex, member: Buyer<AccountId>) -> Result<Hash> {
+		self.submit(signer, BulletTrainCall::claim_dpo_rewards(dpo_idx, member))
+	}
+
+	/// Number of travel cabins ever created, the next travel cabin index.
+	pub fn travel_cabin_count(&self) -> Result<TravelCabinIndex> {
+		self.client.fetch_or_default(pallet_bullet_train::TravelCabinCount::<Runtime>::hashed_key().to_vec())
+	}
+
+	/// The travel cabin `travel_cabin_idx`, if created.
+	pub fn travel_cabin(
+		&self,
+		travel_cabin_idx: TravelCabinIndex,
+	) -> Result<Option<TravelCabinInfo<Balance, AccountId, BlockNumber>>> {
+		self.client.fetch(pallet_bullet_train::TravelCabins::<Runtime>::hashed_key_for(travel_cabin_idx))
+	}
+
+	/// Sold and total inventory of the travel cabin `travel_cabin_idx`.
+	pub fn travel_cabin_inventory(
+		&self,
+		travel_cabin_idx: TravelCabinIndex,
+	) -> Result<Option<(TravelCabinInventoryIndex, TravelCabinInventoryIndex)>> {
+		self.client.fetch(pallet_bullet_train::TravelCabinInventory::<Runtime>::hashed_key_for(travel_cabin_idx))
+	}
+
+	/// The buyer of the `travel_cabin_number`th sold travel cabin `travel_cabin_idx`.
+	pub fn travel_cabin_buyer(
+		&self,
+		travel_cabin_idx: TravelCabinIndex,
+		travel_cabin_number: TravelCabinInventoryIndex,
+	) -> Result<Option<TravelCabinBuyerInfo<Balance, AccountId, BlockNumber>>> {
+		self.client.fetch(
+			pallet_bullet_train::TravelCabinBuyer::<Runtime>::hashed_key_for(travel_cabin_idx, travel_cabin_number)
+		)
+	}
+
+	/// Number of dpos ever created, the next dpo index.
+	pub fn dpo_count(&self) -> Result<DpoIndex> {
+		self.client.fetch_or_default(pallet_bullet_train::DpoCount::<Runtime>::hashed_key().to_vec())
+	}
+
+	/// The dpo `dpo_idx`, if created.
+	pub fn dpo(&self, dpo_idx: DpoIndex) -> Result<Option<DpoInfo<Balance, BlockNumber, AccountId>>> {
+		self.client.fetch(pallet_bullet_train::Dpos::<Runtime>::hashed_key_for(dpo_idx))
+	}
+
+	/// The share of `buyer` in the dpo `dpo_idx`, if a member.
+	pub fn dpo_member(
+		&self,
+		dpo_idx: DpoIndex,
+		buyer: Buyer<AccountId>,
+	) -> Result<Option<DpoMemberInfo<AccountId, Balance>>> {
+		self.client.fetch(pallet_bullet_train::DpoMembers::<Runtime>::hashed_key_for(dpo_idx, buyer))
+	}
+
+	fn submit(&self, signer: &Signer, call: BulletTrainCall<Runtime>) -> Result<Hash> {
+		self.client.submit_and_wait(signer, Call::BulletTrain(call))
+	}
+}
@@ -0,0 +1,86 @@
+//! Dex calls and storage.
+
+use node_primitives::{AccountId, Balance, BlockNumber, CurrencyId, Hash};
+use pallet_dex::{TradingPair, TradingPairStatus};
+use spanner_runtime::{Call, DexCall, Runtime};
+use crate::{Client, Result, Signer};
+
+/// The dex pallet, reached through [`Client::dex`].
+///
+/// Every call is submitted with [`Client::submit_and_wait`].
+pub struct Dex<'a> {
+	client: &'a Client,
+}
+
+impl<'a> Dex<'a> {
+	pub(crate) fn new(client: &'a Client) -> Self {
+		Dex { client }
+	}
+
+	/// Swap `supply_amount` of the first token of `path` for at least `min_target_amount` of
+	/// its last token, through the pools of every pair of consecutive tokens.
+	pub fn swap_with_exact_supply(
+		&self,
+		signer: &Signer,
+		path: Vec<CurrencyId>,
+		supply_amount: Balance,
+		min_target_amount: Balance,
+	) -> Result<Hash> {
+		self.submit(signer, DexCall::swap_with_exact_supply(path, supply_amount, min_target_amount))
+	}
+
+	/// Swap at most `max_supply_amount` of the first token of `path` for `target_amount` of
+	/// its last token, through the pools of every pair of consecutive tokens.
+	pub fn swap_with_exact_target(
+		&self,
+		signer: &Signer,
+		path: Vec<CurrencyId>,
+		target_amount: Balance,
+		max_supply_amount: Balance,
+	) -> Result<Hash> {
+		self.submit(signer, DexCall::swap_with_exact_target(path, target_amount, max_supply_amount))
+	}
+
+	/// Add at most `max_amount_a` and `max_amount_b` to the pool of an enabled pair, or
+	/// provision a listing pair.
+	pub fn add_liquidity(
+		&self,
+		signer: &Signer,
+		currency_id_a: CurrencyId,
+		currency_id_b: CurrencyId,
+		max_amount_a: Balance,
+		max_amount_b: Balance,
+	) -> Result<Hash> {
+		self.submit(signer, DexCall::add_liquidity(currency_id_a, currency_id_b, max_amount_a, max_amount_b))
+	}
+
+	/// Burn `remove_share` of the dex share of a pair for its part of the pool.
+	pub fn remove_liquidity(
+		&self,
+		signer: &Signer,
+		currency_id_a: CurrencyId,
+		currency_id_b: CurrencyId,
+		remove_share: Balance,
+	) -> Result<Hash> {
+		self.submit(signer, DexCall::remove_liquidity(currency_id_a, currency_id_b, remove_share))
+	}
+
+	/// Pool of `trading_pair`, in the order of its tokens.
+	pub fn liquidity_pool(&self, trading_pair: TradingPair) -> Result<(Balance, Balance)> {
+		self.client.fetch_or_default(pallet_dex::LiquidityPool::<Runtime>::hashed_key_for(trading_pair))
+	}
+
+	/// Status of `trading_pair`.
+	pub fn trading_pair_status(&self, trading_pair: TradingPair) -> Result<TradingPairStatus<Balance, BlockNumber>> {
+		self.client.fetch_or_default(pallet_dex::TradingPairStatuses::<Runtime>::hashed_key_for(trading_pair))
+	}
+
+	/// Provision of `who` to the listing `trading_pair`, in the order of its tokens.
+	pub fn provision(&self, trading_pair: TradingPair, who: &AccountId) -> Result<(Balance, Balance)> {
+		self.client.fetch_or_default(pallet_dex::ProvisioningPool::<Runtime>::hashed_key_for(trading_pair, who))
+	}
+
+	fn submit(&self, signer: &Signer, call: DexCall<Runtime>) -> Result<Hash> {
+		self.client.submit_and_wait(signer, Call::Dex(call))
+	}
+}
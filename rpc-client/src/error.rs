@@ -0,0 +1,48 @@
+//! Errors of the client.
+
+use jsonrpc_core_client::RpcError;
+use node_primitives::Hash;
+use sp_runtime::DispatchError;
+
+/// Result of the client operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Aggregated error for `Client` operations.
+#[derive(Debug, derive_more::From, derive_more::Display)]
+pub enum Error {
+	/// Could not start the runtime driving the connection.
+	Io(std::io::Error),
+	/// The node could not be reached, or answered with an error.
+	Rpc(RpcError),
+	/// Could not decode a storage value.
+	Codec(codec::Error),
+	/// The node does not know the requested block.
+	#[from(ignore)]
+	NotFound(String),
+	/// The secret uri of a signer is invalid.
+	#[from(ignore)]
+	#[display(fmt = "Invalid secret uri: {}", _0)]
+	InvalidSuri(String),
+	/// The extrinsic was not included in a block before the inclusion timeout.
+	#[from(ignore)]
+	#[display(fmt = "Extrinsic {:?} was not included in time", _0)]
+	Timeout(Hash),
+	/// The extrinsic was included in a block, but failed to dispatch.
+	#[from(ignore)]
+	#[display(fmt = "Extrinsic {:?} failed: {:?}", _0, _1)]
+	ExtrinsicFailed(Hash, DispatchError),
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match *self {
+			Self::Io(ref e) => Some(e),
+			Self::Codec(ref e) => Some(e),
+			Self::Rpc(_)
+			| Self::NotFound(_)
+			| Self::InvalidSuri(_)
+			| Self::Timeout(_)
+			| Self::ExtrinsicFailed(_, _) => None,
+		}
+	}
+}
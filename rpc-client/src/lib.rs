@@ -0,0 +1,280 @@
+//! Typed rpc client for the spanner node.
+//!
+//! [`Client`] connects to the http rpc endpoint of a node, signs and submits extrinsics with
+//! the types of `node_primitives` and the spanner runtime, and decodes the storage of the
+//! bullet-train, dex and rewards pallets. The pallets are reached through [`Client::bullet_train`],
+//! [`Client::dex`] and [`Client::rewards`].
+//!
+//! The client blocks the calling thread on every request, the connection itself is driven by
+//! a runtime owned by the client.
+
+#![warn(missing_docs)]
+
+mod bullet_train;
+mod dex;
+mod error;
+mod rewards;
+mod signer;
+
+pub use bullet_train::BulletTrain;
+pub use dex::Dex;
+pub use error::{Error, Result};
+pub use rewards::Rewards;
+pub use signer::Signer;
+
+use std::{thread, time::{Duration, Instant}};
+use codec::{Decode, Encode};
+use futures::Future;
+use jsonrpc_core_client::{transports::http, RpcChannel};
+use node_primitives::{AccountId, Balance, BlockNumber, CurrencyId, Hash, Header, Index};
+use sc_rpc::{
+	author::AuthorClient,
+	chain::ChainClient,
+	state::StateClient,
+};
+use sp_core::{hashing::twox_128, storage::StorageKey, Bytes};
+use sp_rpc::{list::ListOrValue, number::NumberOrHex};
+use sp_runtime::{
+	generic::{Era, SignedBlock},
+	traits::{BlakeTwo256, Hash as HashT},
+};
+use spanner_runtime::{Call, Event, Runtime, SignedExtra, SignedPayload, UncheckedExtrinsic};
+use substrate_frame_rpc_system::SystemClient;
+
+/// The http rpc endpoint of a node started with the default options.
+pub const DEFAULT_URL: &str = "http://localhost:9933";
+
+// interval between two checks of the nonce of a sender waiting for its extrinsic
+const INCLUSION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Account data of the native token.
+pub type AccountInfo = frame_system::AccountInfo<Index, pallet_balances::AccountData<Balance>>;
+
+/// Event of the spanner runtime, with the phase of the block it was deposited in.
+pub type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+/// Where to reach the node, and how long to wait for it.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+	/// Http endpoint of the node rpc.
+	pub url: String,
+	/// How long [`Client::submit_and_wait`] waits for an extrinsic to be included in a block.
+	pub inclusion_timeout: Duration,
+}
+
+impl Default for ClientConfig {
+	fn default() -> Self {
+		ClientConfig {
+			url: DEFAULT_URL.into(),
+			inclusion_timeout: Duration::from_secs(60),
+		}
+	}
+}
+
+/// A connection to the rpc of a spanner node.
+pub struct Client {
+	config: ClientConfig,
+	genesis_hash: Hash,
+	author: AuthorClient<Hash, Hash>,
+	chain: ChainClient<BlockNumber, Hash, Header, SignedBlock<node_primitives::Block>>,
+	state: StateClient<Hash>,
+	system: SystemClient<Hash, AccountId, Index>,
+	// drives the http transport, kept alive as long as the client
+	_runtime: tokio::runtime::Runtime,
+}
+
+impl Client {
+	/// Connect to the node at `config.url`.
+	pub fn connect(config: ClientConfig) -> Result<Self> {
+		let mut runtime = tokio::runtime::Runtime::new()?;
+		let channel: RpcChannel = runtime.block_on(http::connect(&config.url))?;
+
+		let chain: ChainClient<_, _, _, _> = channel.clone().into();
+		let genesis_hash = match chain
+			.block_hash(Some(ListOrValue::Value(NumberOrHex::Number(0))))
+			.wait()?
+		{
+			ListOrValue::Value(Some(hash)) => hash,
+			_ => return Err(Error::NotFound("genesis block hash".into())),
+		};
+
+		Ok(Client {
+			config,
+			genesis_hash,
+			author: channel.clone().into(),
+			chain,
+			state: channel.clone().into(),
+			system: channel.into(),
+			_runtime: runtime,
+		})
+	}
+
+	/// Hash of the genesis block of the chain.
+	pub fn genesis_hash(&self) -> Hash {
+		self.genesis_hash
+	}
+
+	/// Header of the best block.
+	pub fn best_header(&self) -> Result<Header> {
+		self.chain.header(None).wait()?
+			.ok_or_else(|| Error::NotFound("best block header".into()))
+	}
+
+	/// Hash of the last finalized block.
+	pub fn finalized_hash(&self) -> Result<Hash> {
+		Ok(self.chain.finalized_head().wait()?)
+	}
+
+	/// Native token account of `who`, with its nonce.
+	pub fn account(&self, who: &AccountId) -> Result<AccountInfo> {
+		self.fetch_or_default(frame_system::Account::<Runtime>::hashed_key_for(who))
+	}
+
+	/// Balance of `who` in the orml token `currency_id`.
+	pub fn token_balance(&self, who: &AccountId, currency_id: CurrencyId) -> Result<orml_tokens::AccountData<Balance>> {
+		use frame_support::storage::StorageDoubleMap;
+
+		self.fetch_or_default(orml_tokens::Accounts::<Runtime>::hashed_key_for(who, currency_id))
+	}
+
+	/// Next nonce of `who`, counting its extrinsics waiting in the transaction pool.
+	pub fn next_nonce(&self, who: &AccountId) -> Result<Index> {
+		Ok(self.system.nonce(who.clone()).wait()?)
+	}
+
+	/// Sign `call` with the next nonce of `signer` and submit it, without waiting for it to be
+	/// included in a block. Returns the hash of the extrinsic.
+	pub fn submit(&self, signer: &Signer, call: Call) -> Result<Hash> {
+		let nonce = self.next_nonce(signer.account_id())?;
+		self.submit_with_nonce(signer, call, nonce)
+	}
+
+	/// Sign `call` with the next nonce of `signer`, submit it and wait until it is included in
+	/// the best block. Returns the hash of the extrinsic.
+	///
+	/// Fails with [`Error::ExtrinsicFailed`] if the included extrinsic failed to dispatch.
+	pub fn submit_and_wait(&self, signer: &Signer, call: Call) -> Result<Hash> {
+		let nonce = self.next_nonce(signer.account_id())?;
+		let submitted_at = self.best_header()?.number;
+		let hash = self.submit_with_nonce(signer, call, nonce)?;
+
+		let start = Instant::now();
+		while self.account(signer.account_id())?.nonce <= nonce {
+			if start.elapsed() > self.config.inclusion_timeout {
+				return Err(Error::Timeout(hash));
+			}
+			thread::sleep(INCLUSION_POLL_INTERVAL);
+		}
+
+		let (block_hash, index) = self.find_extrinsic(hash, submitted_at)?;
+		let failure = self.events(block_hash)?
+			.into_iter()
+			.filter(|record| record.phase == frame_system::Phase::ApplyExtrinsic(index))
+			.find_map(|record| match record.event {
+				Event::frame_system(frame_system::Event::ExtrinsicFailed(error, _)) => Some(error),
+				_ => None,
+			});
+		match failure {
+			Some(error) => Err(Error::ExtrinsicFailed(hash, error)),
+			None => Ok(hash),
+		}
+	}
+
+	/// Events deposited in the block `block_hash`.
+	pub fn events(&self, block_hash: Hash) -> Result<Vec<EventRecord>> {
+		// `frame_system::Events` is private to the pallet, its key is built from the prefixes
+		let key = [twox_128(b"System"), twox_128(b"Events")].concat();
+		self.fetch_at(key, Some(block_hash)).map(Option::unwrap_or_default)
+	}
+
+	/// The bullet-train calls and storage.
+	pub fn bullet_train(&self) -> BulletTrain {
+		BulletTrain::new(self)
+	}
+
+	/// The dex calls and storage.
+	pub fn dex(&self) -> Dex {
+		Dex::new(self)
+	}
+
+	/// The rewards calls and storage.
+	pub fn rewards(&self) -> Rewards {
+		Rewards::new(self)
+	}
+
+	// Block of the best chain after `from` including the extrinsic `hash`, and the index of the
+	// extrinsic in the block.
+	fn find_extrinsic(&self, hash: Hash, from: BlockNumber) -> Result<(Hash, u32)> {
+		let best = self.best_header()?.number;
+		for number in from.saturating_add(1)..=best {
+			let block_hash = match self.chain
+				.block_hash(Some(ListOrValue::Value(NumberOrHex::Number(number.into()))))
+				.wait()?
+			{
+				ListOrValue::Value(Some(block_hash)) => block_hash,
+				_ => return Err(Error::NotFound(format!("hash of block {}", number))),
+			};
+			let block = self.chain.block(Some(block_hash)).wait()?
+				.ok_or_else(|| Error::NotFound(format!("block {}", number)))?;
+			if let Some(index) = block.block.extrinsics
+				.iter()
+				.position(|extrinsic| BlakeTwo256::hash_of(extrinsic) == hash)
+			{
+				return Ok((block_hash, index as u32));
+			}
+		}
+		Err(Error::NotFound(format!("block including extrinsic {:?}", hash)))
+	}
+
+	fn submit_with_nonce(&self, signer: &Signer, call: Call, nonce: Index) -> Result<Hash> {
+		let extrinsic = self.sign(signer, call, nonce)?;
+		log::debug!(target: "rpc-client", "Submitting {:?}", extrinsic);
+		Ok(self.author.submit_extrinsic(Bytes(extrinsic.encode())).wait()?)
+	}
+
+	// Immortal extrinsic, checked against the runtime version of the best block.
+	fn sign(&self, signer: &Signer, call: Call, nonce: Index) -> Result<UncheckedExtrinsic> {
+		let version = self.state.runtime_version(None).wait()?;
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::new(),
+			frame_system::CheckTxVersion::new(),
+			frame_system::CheckGenesis::new(),
+			frame_system::CheckEra::from(Era::Immortal),
+			frame_system::CheckNonce::from(nonce),
+			frame_system::CheckWeight::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		);
+		let raw_payload = SignedPayload::from_raw(
+			call,
+			extra,
+			(
+				version.spec_version,
+				version.transaction_version,
+				self.genesis_hash,
+				self.genesis_hash,
+				(),
+				(),
+				(),
+			),
+		);
+		let signature = raw_payload.using_encoded(|payload| signer.sign(payload));
+		let (call, extra, _) = raw_payload.deconstruct();
+
+		Ok(UncheckedExtrinsic::new_signed(call, signer.account_id().clone().into(), signature, extra))
+	}
+
+	fn fetch<V: Decode>(&self, key: Vec<u8>) -> Result<Option<V>> {
+		self.fetch_at(key, None)
+	}
+
+	fn fetch_at<V: Decode>(&self, key: Vec<u8>, block_hash: Option<Hash>) -> Result<Option<V>> {
+		self.state.storage(StorageKey(key), block_hash).wait()?
+			.map(|data| V::decode(&mut &data.0[..]))
+			.transpose()
+			.map_err(Into::into)
+	}
+
+	fn fetch_or_default<V: Decode + Default>(&self, key: Vec<u8>) -> Result<V> {
+		self.fetch(key).map(Option::unwrap_or_default)
+	}
+}
@@ -1,71 +1,217 @@
-// This file is part of Substrate.
-
-// Copyright (C) 2019-2021 Parity Technologies (UK) Ltd.
-// SPDX-License-Identifier: Apache-2.0
-
-// Licensed under the Apache License, Version 2.0 (the "License");
-// you may not use this file except in compliance with the License.
-// You may obtain a copy of the License at
-//
-// 	http://www.apache.org/licenses/LICENSE-2.0
-//
-// Unless required by applicable law or agreed to in writing, software
-// distributed under the License is distributed on an "AS IS" BASIS,
-// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
-// See the License for the specific language governing permissions and
-// limitations under the License.
+//! Command line interface of the spanner rpc client.
+//!
+//! Queries the storage of a node and submits bullet-train, dex and rewards extrinsics to it,
+//! waiting for them to be included in a block.
 
 #![warn(missing_docs)]
 
-//! Example substrate RPC client code.
-//!
-//! This module shows how you can write a Rust RPC client that connects to a running
-//! substrate node and use statically typed RPC wrappers.
-
-use futures::Future;
-use hyper::rt;
-use node_primitives::Hash;
-use sc_rpc::author::{
-	AuthorClient,
-	hash::ExtrinsicOrHash,
-};
-use jsonrpc_core_client::{
-	transports::http,
-	RpcError,
-};
+use node_primitives::{AccountId, Balance, CurrencyId, TokenSymbol};
+use node_rpc_client::{Client, ClientConfig, Signer, DEFAULT_URL};
+use pallet_bullet_train_primitives::{DpoIndex, TravelCabinIndex};
+use pallet_dex::TradingPair;
+use pallet_rewards::PoolId;
+use sp_core::crypto::Ss58Codec;
+use sp_runtime::traits::Header as _;
+use structopt::StructOpt;
 
-fn main() {
-	sp_tracing::try_init_simple();
+#[derive(Debug, StructOpt)]
+#[structopt(name = "spanner-client", about = "Typed rpc client of the spanner node")]
+struct Opt {
+	/// Http rpc endpoint of the node.
+	#[structopt(long, default_value = DEFAULT_URL)]
+	url: String,
+
+	/// Seconds to wait for a submitted extrinsic to be included in a block.
+	#[structopt(long, default_value = "60")]
+	inclusion_timeout: u64,
+
+	/// Secret uri of the sr25519 key signing the extrinsics, such as `//Alice`.
+	#[structopt(long, default_value = "//Alice")]
+	suri: String,
+
+	#[structopt(subcommand)]
+	command: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+	/// Print the best block header.
+	Head,
+	/// Print the native and token balances of an account.
+	Balance {
+		/// Ss58 address of the account.
+		#[structopt(parse(try_from_str = parse_account))]
+		account: AccountId,
+	},
+	/// Print a travel cabin and its inventory.
+	TravelCabin {
+		/// Index of the travel cabin.
+		travel_cabin_idx: TravelCabinIndex,
+	},
+	/// Print a dpo.
+	Dpo {
+		/// Index of the dpo.
+		dpo_idx: DpoIndex,
+	},
+	/// Print the pool of a dex trading pair.
+	Pool {
+		/// Symbol of a token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_a: CurrencyId,
+		/// Symbol of the other token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_b: CurrencyId,
+	},
+	/// Buy a travel cabin as a passenger.
+	BuyTravelCabin {
+		/// Index of the travel cabin.
+		travel_cabin_idx: TravelCabinIndex,
+	},
+	/// Buy shares of a dpo as a passenger.
+	BuyDpoShare {
+		/// Index of the dpo.
+		dpo_idx: DpoIndex,
+		/// Amount of the dpo token to pay.
+		amount: Balance,
+	},
+	/// Swap an exact supply along a path of tokens.
+	Swap {
+		/// Comma separated token symbols, from the supplied token to the target token.
+		#[structopt(use_delimiter = true, parse(try_from_str = parse_token))]
+		path: Vec<CurrencyId>,
+		/// Amount of the first token of the path to supply.
+		supply_amount: Balance,
+		/// Least amount of the last token of the path to receive.
+		#[structopt(long, default_value = "0")]
+		min_target_amount: Balance,
+	},
+	/// Add liquidity to the pool of a dex trading pair.
+	AddLiquidity {
+		/// Symbol of a token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_a: CurrencyId,
+		/// Symbol of the other token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_b: CurrencyId,
+		/// Most of the first token to add.
+		max_amount_a: Balance,
+		/// Most of the other token to add.
+		max_amount_b: Balance,
+	},
+	/// Deposit dex shares of a trading pair into its yield farming pool.
+	DepositDexShare {
+		/// Symbol of a token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_a: CurrencyId,
+		/// Symbol of the other token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_b: CurrencyId,
+		/// Amount of the dex share to deposit.
+		amount: Balance,
+	},
+	/// Claim the rewards of the yield farming pool of a trading pair.
+	ClaimDexShareRewards {
+		/// Symbol of a token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_a: CurrencyId,
+		/// Symbol of the other token of the pair.
+		#[structopt(parse(try_from_str = parse_token))]
+		token_b: CurrencyId,
+	},
+}
+
+const TOKENS: [TokenSymbol; 5] = [
+	TokenSymbol::BOLT,
+	TokenSymbol::WUSD,
+	TokenSymbol::BBOT,
+	TokenSymbol::NCAT,
+	TokenSymbol::PLKT,
+];
 
-	rt::run(rt::lazy(|| {
-		let uri = "http://localhost:9933";
-
-		http::connect(uri)
-			.and_then(|client: AuthorClient<Hash, Hash>| {
-				remove_all_extrinsics(client)
-			})
-			.map_err(|e| {
-				println!("Error: {:?}", e);
-			})
-	}))
+fn parse_token(symbol: &str) -> Result<CurrencyId, String> {
+	TOKENS.iter()
+		.find(|token| format!("{:?}", token).eq_ignore_ascii_case(symbol))
+		.map(|token| CurrencyId::Token(*token))
+		.ok_or_else(|| format!("Unknown token {}", symbol))
 }
 
-/// Remove all pending extrinsics from the node.
-///
-/// The example code takes `AuthorClient` and first:
-/// 1. Calls the `pending_extrinsics` method to get all extrinsics in the pool.
-/// 2. Then calls `remove_extrinsic` passing the obtained raw extrinsics.
-///
-/// As the result of running the code the entire content of the transaction pool is going
-/// to be removed and the extrinsics are going to be temporarily banned.
-fn remove_all_extrinsics(client: AuthorClient<Hash, Hash>) -> impl Future<Item=(), Error=RpcError> {
-	client.pending_extrinsics()
-		.and_then(move |pending| {
-			client.remove_extrinsic(
-				pending.into_iter().map(|tx| ExtrinsicOrHash::Extrinsic(tx.into())).collect()
-			)
-		})
-		.map(|removed| {
-			println!("Removed extrinsics: {:?}", removed);
-		})
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_ss58check(address).map_err(|e| format!("Invalid address {}: {:?}", address, e))
+}
+
+fn dex_share(token_a: CurrencyId, token_b: CurrencyId) -> Result<CurrencyId, String> {
+	TradingPair::from_token_currency_ids(token_a, token_b)
+		.and_then(|pair| pair.get_dex_share_currency_id())
+		.ok_or_else(|| format!("No dex share for {:?} and {:?}", token_a, token_b))
+}
+
+fn run(opt: Opt) -> Result<(), Box<dyn std::error::Error>> {
+	let client = Client::connect(ClientConfig {
+		url: opt.url,
+		inclusion_timeout: std::time::Duration::from_secs(opt.inclusion_timeout),
+	})?;
+	let signer = Signer::from_suri(&opt.suri)?;
+
+	match opt.command {
+		Command::Head => {
+			let header = client.best_header()?;
+			println!("#{} {:?}", header.number, header.hash());
+		},
+		Command::Balance { account } => {
+			println!("{:?}: {:?}", TokenSymbol::BOLT, client.account(&account)?.data);
+			for token in TOKENS.iter().skip(1) {
+				println!("{:?}: {:?}", token, client.token_balance(&account, CurrencyId::Token(*token))?);
+			}
+		},
+		Command::TravelCabin { travel_cabin_idx } => {
+			let bullet_train = client.bullet_train();
+			println!("{:?}", bullet_train.travel_cabin(travel_cabin_idx)?);
+			println!("Inventory (sold, total): {:?}", bullet_train.travel_cabin_inventory(travel_cabin_idx)?);
+		},
+		Command::Dpo { dpo_idx } => {
+			println!("{:?}", client.bullet_train().dpo(dpo_idx)?);
+		},
+		Command::Pool { token_a, token_b } => {
+			let pair = TradingPair::from_token_currency_ids(token_a, token_b)
+				.ok_or_else(|| format!("No trading pair for {:?} and {:?}", token_a, token_b))?;
+			println!("{:?}: {:?}", pair, client.dex().liquidity_pool(pair)?);
+			println!("Status: {:?}", client.dex().trading_pair_status(pair)?);
+		},
+		Command::BuyTravelCabin { travel_cabin_idx } => {
+			let hash = client.bullet_train().passenger_buy_travel_cabin(&signer, travel_cabin_idx)?;
+			println!("Included {:?}", hash);
+		},
+		Command::BuyDpoShare { dpo_idx, amount } => {
+			let hash = client.bullet_train().passenger_buy_dpo_share(&signer, dpo_idx, amount, None)?;
+			println!("Included {:?}", hash);
+		},
+		Command::Swap { path, supply_amount, min_target_amount } => {
+			let hash = client.dex().swap_with_exact_supply(&signer, path, supply_amount, min_target_amount)?;
+			println!("Included {:?}", hash);
+		},
+		Command::AddLiquidity { token_a, token_b, max_amount_a, max_amount_b } => {
+			let hash = client.dex().add_liquidity(&signer, token_a, token_b, max_amount_a, max_amount_b)?;
+			println!("Included {:?}", hash);
+		},
+		Command::DepositDexShare { token_a, token_b, amount } => {
+			let hash = client.rewards().deposit_dex_share(&signer, dex_share(token_a, token_b)?, amount)?;
+			println!("Included {:?}", hash);
+		},
+		Command::ClaimDexShareRewards { token_a, token_b } => {
+			let pool = PoolId::DexYieldFarming(dex_share(token_a, token_b)?);
+			let hash = client.rewards().claim_reward(&signer, pool)?;
+			println!("Included {:?}", hash);
+		},
+	}
+
+	Ok(())
+}
+
+fn main() {
+	sp_tracing::try_init_simple();
+
+	if let Err(e) = run(Opt::from_args()) {
+		eprintln!("Error: {}", e);
+		std::process::exit(1);
+	}
 }
@@ -0,0 +1,91 @@
+//! Rewards calls and storage.
+
+use std::collections::BTreeMap;
+use node_primitives::{AccountId, Balance, BlockNumber, CurrencyId, Hash};
+use pallet_rewards::{DexShareLock, PoolId, PoolInfo};
+use spanner_runtime::{Call, Runtime};
+use crate::{Client, Result, Signer};
+
+type RewardsCall = pallet_rewards::Call<Runtime>;
+
+/// The rewards pallet, reached through [`Client::rewards`].
+///
+/// Every call is submitted with [`Client::submit_and_wait`].
+pub struct Rewards<'a> {
+	client: &'a Client,
+}
+
+impl<'a> Rewards<'a> {
+	pub(crate) fn new(client: &'a Client) -> Self {
+		Rewards { client }
+	}
+
+	/// Deposit `amount` of the dex share `lp_token` into its yield farming pool.
+	pub fn deposit_dex_share(&self, signer: &Signer, lp_token: CurrencyId, amount: Balance) -> Result<Hash> {
+		self.submit(signer, RewardsCall::deposit_dex_share(lp_token, amount))
+	}
+
+	/// Withdraw `amount` of the dex share `lp_token` from its yield farming pool.
+	pub fn withdraw_dex_share(&self, signer: &Signer, lp_token: CurrencyId, amount: Balance) -> Result<Hash> {
+		self.submit(signer, RewardsCall::withdraw_dex_share(lp_token, amount))
+	}
+
+	/// Lock `amount` of the deposited dex share `lp_token` for `lock_period` blocks.
+	pub fn lock_dex_share(
+		&self,
+		signer: &Signer,
+		lp_token: CurrencyId,
+		amount: Balance,
+		lock_period: BlockNumber,
+	) -> Result<Hash> {
+		self.submit(signer, RewardsCall::lock_dex_share(lp_token, amount, lock_period))
+	}
+
+	/// Unlock the dex share `lp_token`, forfeiting the pending rewards if the lock is not over.
+	pub fn unlock_dex_share(&self, signer: &Signer, lp_token: CurrencyId) -> Result<Hash> {
+		self.submit(signer, RewardsCall::unlock_dex_share(lp_token))
+	}
+
+	/// Stake `amount` of `currency_id` into its staking pool.
+	pub fn stake(&self, signer: &Signer, currency_id: CurrencyId, amount: Balance) -> Result<Hash> {
+		self.submit(signer, RewardsCall::stake(currency_id, amount))
+	}
+
+	/// Unstake `amount` of `currency_id` from its staking pool.
+	pub fn unstake(&self, signer: &Signer, currency_id: CurrencyId, amount: Balance) -> Result<Hash> {
+		self.submit(signer, RewardsCall::unstake(currency_id, amount))
+	}
+
+	/// Claim the rewards of `pool_id` in every reward currency.
+	pub fn claim_reward(&self, signer: &Signer, pool_id: PoolId) -> Result<Hash> {
+		self.submit(signer, RewardsCall::claim_reward(pool_id))
+	}
+
+	/// Compound the rewards of the dex share `lp_token` back into its pool, or stop doing so.
+	pub fn set_auto_compound(&self, signer: &Signer, lp_token: CurrencyId, enabled: bool) -> Result<Hash> {
+		self.submit(signer, RewardsCall::set_auto_compound(lp_token, enabled))
+	}
+
+	/// Total shares and rewards of `pool_id`.
+	pub fn pool(&self, pool_id: PoolId) -> Result<PoolInfo<Balance>> {
+		self.client.fetch_or_default(pallet_rewards::Pools::<Runtime>::hashed_key_for(pool_id))
+	}
+
+	/// Share of `who` in `pool_id`, and its withdrawn rewards by reward currency.
+	pub fn share_and_withdrawn_reward(
+		&self,
+		pool_id: PoolId,
+		who: &AccountId,
+	) -> Result<(Balance, BTreeMap<CurrencyId, Balance>)> {
+		self.client.fetch_or_default(pallet_rewards::ShareAndWithdrawnReward::<Runtime>::hashed_key_for(pool_id, who))
+	}
+
+	/// Lock of the dex share `lp_token` of `who`, if locked.
+	pub fn dex_share_lock(&self, lp_token: CurrencyId, who: &AccountId) -> Result<Option<DexShareLock<BlockNumber>>> {
+		self.client.fetch(pallet_rewards::DexShareLocks::<Runtime>::hashed_key_for(lp_token, who))
+	}
+
+	fn submit(&self, signer: &Signer, call: RewardsCall) -> Result<Hash> {
+		self.client.submit_and_wait(signer, Call::Rewards(call))
+	}
+}
@@ -0,0 +1,44 @@
+//! Keys signing the extrinsics of the client.
+
+use node_primitives::{AccountId, Signature};
+use sp_core::{sr25519, Pair};
+use sp_runtime::{traits::IdentifyAccount, MultiSigner};
+use crate::{Error, Result};
+
+/// An account signing extrinsics, from any key pair supported by `node_primitives::Signature`.
+pub struct Signer {
+	account_id: AccountId,
+	sign: Box<dyn Fn(&[u8]) -> Signature + Send + Sync>,
+}
+
+impl Signer {
+	/// Sign with `pair`.
+	pub fn new<P>(pair: P) -> Self
+	where
+		P: Pair + Send + Sync + 'static,
+		P::Public: Into<MultiSigner>,
+		P::Signature: Into<Signature>,
+	{
+		Signer {
+			account_id: pair.public().into().into_account(),
+			sign: Box::new(move |payload| pair.sign(payload).into()),
+		}
+	}
+
+	/// Sign with the sr25519 key of a secret uri, such as `//Alice` or a mnemonic phrase.
+	pub fn from_suri(suri: &str) -> Result<Self> {
+		sr25519::Pair::from_string(suri, None)
+			.map(Self::new)
+			.map_err(|e| Error::InvalidSuri(format!("{:?}", e)))
+	}
+
+	/// Account of the signer.
+	pub fn account_id(&self) -> &AccountId {
+		&self.account_id
+	}
+
+	/// Sign `payload`, the encoding of a signed extrinsic payload.
+	pub fn sign(&self, payload: &[u8]) -> Signature {
+		(self.sign)(payload)
+	}
+}
@@ -0,0 +1,77 @@
+use node_primitives::{CurrencyId, TokenSymbol};
+use node_rpc_client::{Client, ClientConfig, Error, Signer};
+use node_testing::node::DevNode;
+use pallet_dex::TradingPair;
+use pallet_rewards::PoolId;
+use sp_keyring::AccountKeyring;
+use spanner_runtime::constants::currency::DOLLARS;
+
+const WUSD: CurrencyId = CurrencyId::Token(TokenSymbol::WUSD);
+const NCAT: CurrencyId = CurrencyId::Token(TokenSymbol::NCAT);
+
+fn connect(node: &DevNode) -> Client {
+	Client::connect(ClientConfig {
+		url: node.rpc_http_url(),
+		..Default::default()
+	}).expect("Failed to connect to the dev node")
+}
+
+#[test]
+fn submits_and_queries_against_a_dev_node() {
+	let node = DevNode::start();
+	let client = connect(&node);
+	let alice = Signer::new(AccountKeyring::Alice.pair());
+	let bob = Signer::new(AccountKeyring::Bob.pair());
+
+	// the dev chain seeds the travel cabins and enables the WUSD-NCAT pair at genesis
+	let bullet_train = client.bullet_train();
+	assert!(bullet_train.travel_cabin_count().unwrap() > 0);
+	assert!(bullet_train.travel_cabin(0).unwrap().is_some());
+	let pair = TradingPair::new(WUSD, NCAT);
+	assert_eq!(client.dex().liquidity_pool(pair).unwrap(), (0, 0));
+
+	// swapping through the empty pool fails to dispatch
+	assert!(matches!(
+		client.dex().swap_with_exact_supply(&bob, vec![WUSD, NCAT], DOLLARS, 0),
+		Err(Error::ExtrinsicFailed(_, _))
+	));
+
+	client.dex().add_liquidity(&alice, WUSD, NCAT, 1_000 * DOLLARS, 1_000 * DOLLARS).unwrap();
+	assert_eq!(client.dex().liquidity_pool(pair).unwrap(), (1_000 * DOLLARS, 1_000 * DOLLARS));
+
+	let ncat_before = client.token_balance(bob.account_id(), NCAT).unwrap().free;
+	client.dex().swap_with_exact_supply(&bob, vec![WUSD, NCAT], DOLLARS, 0).unwrap();
+	assert!(client.token_balance(bob.account_id(), NCAT).unwrap().free > ncat_before);
+
+	let (sold_before, _) = bullet_train.travel_cabin_inventory(0).unwrap().unwrap();
+	bullet_train.passenger_buy_travel_cabin(&bob, 0).unwrap();
+	assert_eq!(bullet_train.travel_cabin_inventory(0).unwrap().unwrap().0, sold_before + 1);
+
+	let lp_token = pair.get_dex_share_currency_id().unwrap();
+	let lp_amount = client.token_balance(alice.account_id(), lp_token).unwrap().free;
+	client.rewards().deposit_dex_share(&alice, lp_token, lp_amount / 2).unwrap();
+	let pool = PoolId::DexYieldFarming(lp_token);
+	assert_eq!(client.rewards().pool(pool).unwrap().total_shares, lp_amount / 2);
+	assert_eq!(client.rewards().share_and_withdrawn_reward(pool, alice.account_id()).unwrap().0, lp_amount / 2);
+}
+
+#[test]
+fn signs_with_the_next_nonce() {
+	let node = DevNode::start();
+	let client = connect(&node);
+	let alice = Signer::new(AccountKeyring::Alice.pair());
+	let charlie = Signer::from_suri("//Charlie").unwrap();
+	client.dex().add_liquidity(&alice, WUSD, NCAT, 1_000 * DOLLARS, 1_000 * DOLLARS).unwrap();
+
+	assert_eq!(client.next_nonce(charlie.account_id()).unwrap(), 0);
+	let wusd_before = client.token_balance(charlie.account_id(), WUSD).unwrap().free;
+	let ncat_before = client.token_balance(charlie.account_id(), NCAT).unwrap().free;
+	client.dex().swap_with_exact_supply(&charlie, vec![WUSD, NCAT], DOLLARS, 0).unwrap();
+	assert_eq!(client.token_balance(charlie.account_id(), WUSD).unwrap().free, wusd_before - DOLLARS);
+	let ncat_received = client.token_balance(charlie.account_id(), NCAT).unwrap().free - ncat_before;
+	assert!(ncat_received > 0);
+	client.dex().swap_with_exact_supply(&charlie, vec![NCAT, WUSD], ncat_received, 0).unwrap();
+	assert_eq!(client.token_balance(charlie.account_id(), NCAT).unwrap().free, ncat_before);
+	assert!(client.token_balance(charlie.account_id(), WUSD).unwrap().free > wusd_before - DOLLARS);
+	assert_eq!(client.account(charlie.account_id()).unwrap().nonce, 2);
+}
@@ -0,0 +1,4 @@
+// orml benchmarking
+mod utils;
+pub mod currencies;
+pub mod tokens;
@@ -82,6 +82,10 @@ pub use frame_system::Call as SystemCall;
 #[cfg(any(feature = "std", test))]
 pub use pallet_balances::Call as BalancesCall;
 #[cfg(any(feature = "std", test))]
+pub use pallet_bullet_train::Call as BulletTrainCall;
+#[cfg(any(feature = "std", test))]
+pub use pallet_dex::Call as DexCall;
+#[cfg(any(feature = "std", test))]
 pub use pallet_staking::StakerStatus;
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
@@ -99,6 +103,10 @@ use sp_runtime::generic::Era;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+/// The custom types of the runtime, for the type definitions of the clients.
+#[cfg(feature = "std")]
+pub mod type_registry;
+
 // Make the WASM binary available.
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
@@ -1085,6 +1093,11 @@ parameter_types! {
     pub const ReleaseYieldGracePeriod: BlockNumber = 7 * DAYS;
     pub const DpoMakePurchaseGracePeriod: BlockNumber = 7 * DAYS;
     pub const MilestoneRewardMinimum: Balance = 10_000_000_000;
+    pub const MilestoneRewardReleaseIncentive: Permill = Permill::from_percent(1);
+    pub const MilestoneRewardPayoutsPerCall: u32 = 50;
+    pub const DpoPayoutMembersPerCall: u32 = 50;
+    pub const DpoExpiriesPerBlock: u32 = 10;
+    pub const RefundExpiredDpos: bool = true;
     pub const CabinYieldRewardMinimum: Balance = 10_000_000_000;
     pub const CabinBonusRewardMinimum: Balance = 10_000_000_000;
     pub const DpoSharePercentCap: (u8, u8) = (1, 2); // 50%
@@ -1095,14 +1108,29 @@ parameter_types! {
     pub const ManagerSlashPerThousand: u32 = 500;
     pub const ManagementFeeCap: u32 = 200; // per thousand
     pub const ManagementBaseFeeCap: u32 = 50; // per thousand
+    pub const ExternalReferralRate: u32 = 300; // per thousand
+    pub const ReferralChainDepth: u32 = 2;
+    pub const ExternalReferralRateCap: u32 = 500; // per thousand
+    pub const ReferralChainDepthCap: u32 = 5;
+    pub const MaxPortfolioLegs: u32 = 10;
+    pub const MaxAllowListLength: u32 = 100;
+    pub const DpoCreationDeposit: Balance = 10 * DOLLARS;
+    pub const MaxNameLength: u32 = 64;
+    pub const DpoBaseFeeChangeDelay: BlockNumber = 2 * DAYS;
 }
 impl pallet_bullet_train::Config for Runtime {
     type Event = Event;
     type Currency = Currencies;
+    type GetNativeCurrencyId = GetNativeCurrencyId;
     type ModuleId = BulletTrainId;
     type ReleaseYieldGracePeriod = ReleaseYieldGracePeriod;
     type DpoMakePurchaseGracePeriod = DpoMakePurchaseGracePeriod;
     type MilestoneRewardMinimum = MilestoneRewardMinimum;
+    type MilestoneRewardReleaseIncentive = MilestoneRewardReleaseIncentive;
+    type MilestoneRewardPayoutsPerCall = MilestoneRewardPayoutsPerCall;
+    type DpoPayoutMembersPerCall = DpoPayoutMembersPerCall;
+    type DpoExpiriesPerBlock = DpoExpiriesPerBlock;
+    type RefundExpiredDpos = RefundExpiredDpos;
     type CabinYieldRewardMinimum = CabinYieldRewardMinimum;
     type CabinBonusRewardMinimum = CabinBonusRewardMinimum;
     type DpoSharePercentCap = DpoSharePercentCap;
@@ -1113,6 +1141,15 @@ impl pallet_bullet_train::Config for Runtime {
     type ManagerSlashPerThousand = ManagerSlashPerThousand;
     type ManagementFeeCap = ManagementFeeCap;
     type ManagementBaseFeeCap = ManagementBaseFeeCap;
+    type ExternalReferralRate = ExternalReferralRate;
+    type ReferralChainDepth = ReferralChainDepth;
+    type ExternalReferralRateCap = ExternalReferralRateCap;
+    type ReferralChainDepthCap = ReferralChainDepthCap;
+    type MaxPortfolioLegs = MaxPortfolioLegs;
+    type MaxAllowListLength = MaxAllowListLength;
+    type DpoCreationDeposit = DpoCreationDeposit;
+    type MaxNameLength = MaxNameLength;
+    type DpoBaseFeeChangeDelay = DpoBaseFeeChangeDelay;
     type EngineerOrigin = pallet_collective::EnsureMember<AccountId, BulletTrainEngineerCollective>;
     type WeightInfo = pallet_bullet_train::weights::SubstrateWeight<Runtime>;
 }
@@ -1121,6 +1158,16 @@ parameter_types! {
     pub const MinimumYieldFarmingReward: Balance = 10_000_000_000;
     pub const AccumulatePeriod: BlockNumber = HOURS;
     pub const StartDelay: BlockNumber = 2;
+    pub const MaxLockPeriod: BlockNumber = 180 * DAYS;
+    pub const LockDecayPeriod: BlockNumber = DAYS;
+    pub const MaxLockDecaysPerBlock: u32 = 50;
+    pub const MaxLockBoost: Permill = Permill::from_percent(100);
+    pub const EarlyUnlockFee: Permill = Permill::from_percent(5);
+    pub const BlocksPerYear: BlockNumber = 365 * DAYS;
+    pub const AutoCompoundFee: Permill = Permill::from_percent(2);
+    pub const MaxAutoCompoundsPerBlock: u32 = 50;
+    pub const AutoCompoundMaxSlippage: Permill = Permill::from_percent(3);
+    pub const MaxEmissionPools: u32 = 20;
 }
 impl pallet_rewards::Config for Runtime {
     type Event = Event;
@@ -1133,6 +1180,20 @@ impl pallet_rewards::Config for Runtime {
     type MinimumYieldFarmingReward = MinimumYieldFarmingReward;
     type Scheduler = Scheduler;
     type PalletsOrigin = OriginCaller;
+    type MaxLockPeriod = MaxLockPeriod;
+    type LockDecayPeriod = LockDecayPeriod;
+    type MaxLockDecaysPerBlock = MaxLockDecaysPerBlock;
+    type MaxLockBoost = MaxLockBoost;
+    type EarlyUnlockFee = EarlyUnlockFee;
+    type BlocksPerYear = BlocksPerYear;
+    type AutoCompoundFee = AutoCompoundFee;
+    type AutoCompoundFeeReceiver = TreasuryModuleAccount;
+    type MaxAutoCompoundsPerBlock = MaxAutoCompoundsPerBlock;
+    type AutoCompoundMaxSlippage = AutoCompoundMaxSlippage;
+    type EmissionCurrencyId = GetNativeCurrencyId;
+    type EmissionTreasury = TreasuryModuleAccount;
+    type MaxEmissionPools = MaxEmissionPools;
+    type WeightInfo = pallet_rewards::weights::SubstrateWeight<Runtime>;
 }
 
 construct_runtime!(
@@ -1177,7 +1238,7 @@ construct_runtime!(
         Mmr: pallet_mmr::{Module, Storage},
         Lottery: pallet_lottery::{Module, Call, Storage, Event<T>},
         // spanner added pallets
-        BulletTrain: pallet_bullet_train::{Module, Call, Storage, Event<T>},
+        BulletTrain: pallet_bullet_train::{Module, Call, Storage, Event<T>, Config<T>},
         Dex: pallet_dex::{Module, Call, Storage, Event<T>, Config<T>},
         Tokens: orml_tokens::{Module, Storage, Event<T>, Config<T>},
         Currencies: orml_currencies::{Module, Call, Event<T>},
@@ -1225,6 +1286,19 @@ pub type Executive = frame_executive::Executive<
     AllModules,
 >;
 
+/// Runs the runtime upgrade between the pre and post upgrade checks of the migrations,
+/// returning the weight it used.
+#[cfg(feature = "try-runtime")]
+pub fn try_runtime_upgrade() -> Result<Weight, &'static str> {
+    BulletTrain::pre_upgrade()?;
+    Rewards::pre_upgrade()?;
+    let weight = Executive::execute_on_runtime_upgrade();
+    frame_support::debug::info!("runtime upgrade used weight {}", weight);
+    BulletTrain::post_upgrade()?;
+    Rewards::post_upgrade()?;
+    Ok(weight)
+}
+
 /// MMR helper types.
 mod mmr {
     use super::Runtime;
@@ -1390,20 +1464,93 @@ impl_runtime_apis! {
         }
     }
 
-    // Disable RPC
-    // impl pallet_bullet_train_rpc_runtime_api::BulletTrainApi<Block, AccountId> for Runtime {
-    //     fn get_travel_cabins_of_account(
-    //         origin: AccountId,
-    //     ) -> Vec<(pallet_bullet_train_primitives::TravelCabinIndex, pallet_bullet_train_primitives::TravelCabinInventoryIndex)> {
-    //         BulletTrain::get_travel_cabins_of_account(&origin)
-    //     }
-    //
-    //     fn get_dpos_of_account(
-    //         origin: AccountId,
-    //     ) -> Vec<pallet_bullet_train_primitives::DpoIndex> {
-    //         BulletTrain::get_dpos_of_account(origin)
-    //     }
-    // }
+    impl pallet_rewards_rpc_runtime_api::RewardsApi<Block, AccountId, BlockNumber> for Runtime {
+        fn get_yield_farming_schedules_of_account(
+            origin: AccountId,
+        ) -> Vec<(
+            pallet_rewards_primitives::YieldFarmingScheduleIndex,
+            pallet_rewards_primitives::YieldFarmingSchedule<AccountId, BlockNumber>,
+        )> {
+            Rewards::get_yield_farming_schedules_of_account(&origin)
+        }
+
+        fn get_yield_farming_schedules_of_pool(
+            pool_id: pallet_rewards_primitives::PoolId,
+        ) -> Vec<(
+            pallet_rewards_primitives::YieldFarmingScheduleIndex,
+            pallet_rewards_primitives::YieldFarmingSchedule<AccountId, BlockNumber>,
+        )> {
+            Rewards::get_yield_farming_schedules_of_pool(pool_id)
+        }
+
+        fn get_pending_rewards(
+            origin: AccountId,
+            pool_id: pallet_rewards_primitives::PoolId,
+        ) -> Vec<(CurrencyId, Balance)> {
+            Rewards::get_pending_rewards(&origin, pool_id)
+        }
+
+        fn get_pool_info(
+            pool_id: pallet_rewards_primitives::PoolId,
+        ) -> pallet_rewards_primitives::PoolInfo<Balance> {
+            Rewards::pools(pool_id)
+        }
+
+        fn get_yield_farming_apr(
+            pool_id: pallet_rewards_primitives::PoolId,
+        ) -> Vec<(CurrencyId, sp_runtime::FixedU128)> {
+            Rewards::get_yield_farming_apr(pool_id)
+        }
+    }
+
+    impl pallet_bullet_train_rpc_runtime_api::BulletTrainApi<Block, AccountId> for Runtime {
+        fn get_travel_cabins_of_account(
+            origin: AccountId,
+        ) -> Vec<(pallet_bullet_train_primitives::TravelCabinIndex, pallet_bullet_train_primitives::TravelCabinInventoryIndex)> {
+            BulletTrain::get_travel_cabins_of_account(&origin)
+        }
+
+        fn get_dpos_of_account(
+            origin: AccountId,
+        ) -> Vec<pallet_bullet_train_primitives::DpoIndex> {
+            BulletTrain::get_dpos_of_account(origin)
+        }
+
+        fn get_travel_cabin_withdrawable_yield(
+            travel_cabin_idx: pallet_bullet_train_primitives::TravelCabinIndex,
+            travel_cabin_number: pallet_bullet_train_primitives::TravelCabinInventoryIndex,
+        ) -> Balance {
+            BulletTrain::get_travel_cabin_withdrawable_yield(travel_cabin_idx, travel_cabin_number)
+        }
+
+        fn get_milestone_reward_releases(
+            token_id: CurrencyId,
+        ) -> Vec<(pallet_bullet_train_primitives::MilestoneRewardReleaseIndex, BlockNumber, Balance, Balance, Balance)> {
+            BulletTrain::get_milestone_reward_releases(token_id)
+        }
+
+        fn get_milestone_reward_payouts_of_account(
+            origin: AccountId,
+        ) -> Vec<(
+            CurrencyId,
+            pallet_bullet_train_primitives::MilestoneRewardReleaseIndex,
+            pallet_bullet_train_primitives::TravelCabinIndex,
+            pallet_bullet_train_primitives::TravelCabinInventoryIndex,
+            Balance,
+        )> {
+            BulletTrain::get_milestone_reward_payouts_of_account(&origin)
+        }
+
+        fn get_referral_earnings_of_account(
+            origin: AccountId,
+        ) -> Vec<(CurrencyId, Balance)> {
+            BulletTrain::get_referral_earnings_of_account(&origin)
+        }
+
+        fn check_invariants() -> pallet_bullet_train_primitives::InvariantReport<Balance> {
+            BulletTrain::check_invariants()
+        }
+    }
 
     impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<
         Block,
@@ -1517,6 +1664,7 @@ impl_runtime_apis! {
 
             add_benchmark!(params, batches, pallet_bullet_train, BulletTrain);
             add_benchmark!(params, batches, pallet_dex, Dex);
+            add_benchmark!(params, batches, pallet_rewards, Rewards);
             orml_add_benchmark!(params, batches, orml_currencies, benchmarking::currencies);
             orml_add_benchmark!(params, batches, orml_tokens, benchmarking::tokens);
 
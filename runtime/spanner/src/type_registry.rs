@@ -0,0 +1,78 @@
+//! The registry written to `types.json` and `types_mapping.json` by the `export-types`
+//! subcommand of the node.
+//!
+//! The structs and enums are described by their `TypeInfo` derive. The aliases are checked
+//! against the type they alias, so that a change of either fails to compile until the
+//! registry follows.
+
+use frame_support::weights::Weight;
+use node_primitives::{AccountId, Amount, Balance, BlockNumber, CurrencyId, TokenSymbol};
+use node_type_registry::Registry;
+use orml_traits::{MultiCurrency, MultiCurrencyExtended};
+use pallet_bullet_train_primitives::{
+    DpoIndex, MilestoneRewardReleaseIndex, TravelCabinIndex, TravelCabinInventoryIndex,
+};
+use pallet_rewards_primitives::YieldFarmingScheduleIndex;
+use sp_runtime::FixedU128;
+
+use crate::Runtime;
+
+type CurrencyOf = <Runtime as orml_currencies::Config>::MultiCurrency;
+
+/// The custom types of the spanner runtime, grouped by the crate defining them.
+pub fn type_registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .alias::<<Runtime as pallet_assets::Config>::AssetId, u32>("AssetId", "u32")
+        .alias::<Weight, u64>("Weight", "u64")
+        .alias::<Amount, i128>("Amount", "i128")
+        .alias::<<CurrencyOf as MultiCurrencyExtended<AccountId>>::Amount, Amount>("AmountOf", "Amount")
+        .register::<TokenSymbol>()
+        .register::<CurrencyId>()
+        .alias::<<CurrencyOf as MultiCurrency<AccountId>>::CurrencyId, CurrencyId>("CurrencyIdOf", "CurrencyId")
+        // bullet train
+        .alias::<TravelCabinIndex, u32>("TravelCabinIndex", "u32")
+        .alias::<TravelCabinInventoryIndex, u16>("TravelCabinInventoryIndex", "u16")
+        .alias::<DpoIndex, u32>("DpoIndex", "u32")
+        .alias::<MilestoneRewardReleaseIndex, u32>("MilestoneRewardReleaseIndex", "u32")
+        .register::<pallet_bullet_train::TravelCabinInfo<Balance, AccountId, BlockNumber>>()
+        .register::<pallet_bullet_train::YieldSchedule<BlockNumber>>()
+        .register::<pallet_bullet_train::TravelCabinBuyerInfo<Balance, AccountId, BlockNumber>>()
+        .register::<pallet_bullet_train::MilestoneRewardInfo<Balance>>()
+        .register::<pallet_bullet_train::MilestoneRewardReleaseInfo<Balance, AccountId, BlockNumber>>()
+        .register::<pallet_bullet_train::MilestoneRewardReleaseProgress<Balance, BlockNumber>>()
+        .register::<pallet_bullet_train::MilestoneRewardPayoutInfo<Balance, AccountId>>()
+        .register::<pallet_bullet_train::DpoState>()
+        .register::<pallet_bullet_train::Target<Balance>>()
+        .register::<pallet_bullet_train::Buyer<AccountId>>()
+        .register::<pallet_bullet_train::DpoInfo<Balance, BlockNumber, AccountId>>()
+        .register::<pallet_bullet_train::DpoAccessPolicy>()
+        .register::<pallet_bullet_train::PortfolioLeg<Balance>>()
+        .register::<pallet_bullet_train::PortfolioLegState>()
+        .register::<pallet_bullet_train::DpoMemberInfo<AccountId, Balance>>()
+        .register::<pallet_bullet_train::Referrer<AccountId>>()
+        .register::<pallet_bullet_train::PaymentType>()
+        .register::<pallet_bullet_train::DpoPayoutProgress<Balance>>()
+        .register::<pallet_bullet_train::DpoRewardPoolInfo<Balance>>()
+        .register::<pallet_bullet_train::DpoReferralPolicy>()
+        .register::<pallet_bullet_train::DpoBaseFeeChangeInfo<BlockNumber>>()
+        // rewards
+        .register::<pallet_rewards::PoolId>()
+        .register::<pallet_rewards::PoolInfo<Balance>>()
+        .alias::<YieldFarmingScheduleIndex, u32>("YieldFarmingScheduleIndex", "u32")
+        .register::<pallet_rewards::YieldFarmingSchedule<AccountId, BlockNumber>>()
+        .register::<pallet_rewards::DexShareLock<BlockNumber>>()
+        .register::<pallet_rewards::EmissionSource>()
+        .register::<pallet_rewards::EmissionCurve<BlockNumber>>()
+        .register::<pallet_rewards::EmissionSchedule<BlockNumber>>()
+        // common
+        .alias::<pallet_common::Price, FixedU128>("Price", "FixedU128")
+        .alias::<pallet_common::ExchangeRate, FixedU128>("ExchangeRate", "FixedU128")
+        .alias::<pallet_common::Ratio, FixedU128>("Ratio", "FixedU128")
+        .alias::<pallet_common::Rate, FixedU128>("Rate", "FixedU128")
+        // dex
+        .register::<pallet_dex::TradingPair>()
+        .register::<pallet_dex::TradingPairStatus<Balance, BlockNumber>>()
+        .register::<pallet_dex::TradingPairProvisionParameters<Balance, BlockNumber>>();
+    registry
+}
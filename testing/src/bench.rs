@@ -24,7 +24,7 @@
 
 use std::{sync::Arc, path::{Path, PathBuf}, collections::BTreeMap};
 
-use node_primitives::Block;
+use node_primitives::{Block, Balance, CurrencyId, Index, TokenSymbol};
 use crate::client::{Client, Backend};
 use crate::keyring::*;
 use sc_client_db::PruningMode;
@@ -47,9 +47,16 @@ use spanner_runtime::{
 	MinimumPeriod,
 	SystemCall,
 	BalancesCall,
+	BulletTrainCall,
+	DexCall,
+	BulletTrainConfig,
+	DexConfig,
+	TokensConfig,
 	AccountId,
 	Signature,
 };
+use pallet_bullet_train::{Buyer, YieldSchedule};
+use pallet_dex::TradingPair;
 use sp_core::{ExecutionContext, blake2_256, traits::SpawnNamed, Pair, Public, sr25519, ed25519};
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -61,6 +68,35 @@ use sc_client_api::{
 use sc_block_builder::BlockBuilderProvider;
 use futures::executor;
 
+const BOLT: CurrencyId = CurrencyId::Token(TokenSymbol::BOLT);
+const WUSD: CurrencyId = CurrencyId::Token(TokenSymbol::WUSD);
+const NCAT: CurrencyId = CurrencyId::Token(TokenSymbol::NCAT);
+const PLKT: CurrencyId = CurrencyId::Token(TokenSymbol::PLKT);
+
+/// Large dpos of the seeded state, filled by their members and holding a travel cabin each.
+const SEEDED_LARGE_DPOS: usize = 50;
+/// Members of a large dpo besides its manager, each holding 1% of it.
+const LARGE_DPO_MEMBERS: usize = 99;
+/// Open dpos of the seeded state, with all but the 1% of their manager on sale.
+const SEEDED_OPEN_DPOS: usize = 100;
+/// Passengers filling an open dpo with 1% purchases.
+const OPEN_DPO_BUYERS: usize = 99;
+/// Accounts at the end of the keyring reserved for the seeded state: the dex liquidity
+/// provider, the managers of the large dpos, their members and the managers of the open dpos.
+const SEEDED_ACCOUNTS: usize = 1 + SEEDED_LARGE_DPOS * (1 + LARGE_DPO_MEMBERS) + SEEDED_OPEN_DPOS;
+
+/// Travel cabin bought by every large dpo, one with a maturity of a single block so that its
+/// whole yield can be withdrawn in the benchmarked block.
+const LARGE_DPO_CABIN: u32 = 0;
+const LARGE_DPO_TARGET: Balance = 5_000 * DOLLARS;
+/// Travel cabin targeted by every open dpo.
+const OPEN_DPO_CABIN: u32 = 1;
+const OPEN_DPO_TARGET: Balance = 1_000 * DOLLARS;
+/// Block at which the seeded dpos expire, far past the benchmarked block.
+const SEEDED_DPO_END: u32 = 10_000;
+/// Amount of each token in the seeded `WUSD-NCAT` and `NCAT-PLKT` dex pools.
+const SEEDED_POOL_LIQUIDITY: Balance = 100_000 * DOLLARS;
+
 /// Keyring full of accounts for benching.
 ///
 /// Accounts are ordered:
@@ -199,6 +235,15 @@ pub enum BlockType {
 	RandomTransfersReaping,
 	/// Bunch of "no-op" calls.
 	Noop,
+	/// Bunch of `WUSD` to `NCAT` swaps on the seeded dex pool.
+	DexSwaps,
+	/// Bunch of `WUSD` to `PLKT` swaps routed through both seeded dex pools.
+	DexMultiHopSwaps,
+	/// Bunch of 1% share purchases of the seeded open dpos.
+	DpoSharePurchases,
+	/// Yield withdrawn from the travel cabin of each seeded large dpo, booked in its reward pool
+	/// and then claimed by each of its 99 passenger members.
+	LargeDpoYieldReleases,
 }
 
 impl BlockType {
@@ -209,6 +254,11 @@ impl BlockType {
 			size,
 		}
 	}
+
+	/// Whether blocks of this type need the state built by [`BenchDb::seed_state`].
+	pub fn requires_seeded_state(self) -> bool {
+		matches!(self, Self::DexSwaps | Self::DexMultiHopSwaps | Self::LargeDpoYieldReleases)
+	}
 }
 
 /// Content of the generated block.
@@ -304,6 +354,12 @@ impl<'a> Iterator for BlockContentIterator<'a> {
 		if self.content.size.map(|size| size <= self.iteration).unwrap_or(false) {
 			return None;
 		}
+		// a withdrawal, a release and a claim per member for each large dpo
+		if self.content.block_type == BlockType::LargeDpoYieldReleases
+			&& self.iteration >= SEEDED_LARGE_DPOS * (2 + LARGE_DPO_MEMBERS)
+		{
+			return None;
+		}
 
 		let sender = self.keyring.at(self.iteration);
 		let receiver = get_account_id_from_seed::<sr25519::Public>(
@@ -337,6 +393,47 @@ impl<'a> Iterator for BlockContentIterator<'a> {
 							SystemCall::remark(Vec::new())
 						)
 					},
+					BlockType::DexSwaps => {
+						Call::Dex(
+							DexCall::swap_with_exact_supply(vec![WUSD, NCAT], DOLLARS, 0)
+						)
+					},
+					BlockType::DexMultiHopSwaps => {
+						Call::Dex(
+							DexCall::swap_with_exact_supply(vec![WUSD, NCAT, PLKT], DOLLARS, 0)
+						)
+					},
+					BlockType::DpoSharePurchases => {
+						let open_dpo = self.iteration / OPEN_DPO_BUYERS % SEEDED_OPEN_DPOS;
+						Call::BulletTrain(
+							BulletTrainCall::passenger_buy_dpo_share(
+								(SEEDED_LARGE_DPOS + open_dpo) as u32,
+								OPEN_DPO_TARGET / 100,
+								None,
+							)
+						)
+					},
+					BlockType::LargeDpoYieldReleases => {
+						// the large dpos bought the cabins of their own index while seeding
+						let large_dpo = self.iteration / (2 + LARGE_DPO_MEMBERS);
+						match self.iteration % (2 + LARGE_DPO_MEMBERS) {
+							0 => Call::BulletTrain(
+								BulletTrainCall::withdraw_yield_from_travel_cabin(
+									LARGE_DPO_CABIN,
+									large_dpo as u16,
+								)
+							),
+							1 => Call::BulletTrain(
+								BulletTrainCall::release_yield_from_dpo(large_dpo as u32)
+							),
+							step => Call::BulletTrain(
+								BulletTrainCall::claim_dpo_rewards(
+									large_dpo as u32,
+									Buyer::Passenger(self.keyring.large_dpo_member(large_dpo, step - 2)),
+								)
+							),
+						}
+					},
 				},
 			},
 			self.runtime_version.spec_version,
@@ -436,8 +533,13 @@ impl BenchDb {
 	///
 	/// Uses already instantiated Client.
 	pub fn generate_inherents(&mut self, client: &Client) -> Vec<OpaqueExtrinsic> {
+		Self::inherents_at_best(client, 1 * MinimumPeriod::get())
+	}
+
+	// The seeding blocks all keep the timestamp at zero, so that the generated block
+	// still lands in the first babe slot.
+	fn inherents_at_best(client: &Client, timestamp: node_primitives::Moment) -> Vec<OpaqueExtrinsic> {
 		let mut inherent_data = InherentData::new();
-		let timestamp = 1 * MinimumPeriod::get();
 
 		inherent_data
 			.put_data(sp_timestamp::INHERENT_IDENTIFIER, &timestamp)
@@ -445,12 +547,85 @@ impl BenchDb {
 
 		client.runtime_api()
 			.inherent_extrinsics_with_context(
-				&BlockId::number(0),
+				&BlockId::Hash(client.chain_info().best_hash),
 				ExecutionContext::BlockConstruction,
 				inherent_data,
 			).expect("Get inherents failed")
 	}
 
+	/// Import the blocks building the seeded state on top of genesis: the liquidity of
+	/// the dex pools, and the large dpos filled by their members and buying their cabin.
+	///
+	/// The travel cabins, the dpos and the dex tokens come with the genesis of the keyring,
+	/// see [`BenchKeyring::generate_genesis`].
+	pub fn seed_state(&mut self) {
+		let mut client = Arc::new(self.client());
+
+		let runtime_version = client.runtime_version_at(&BlockId::number(0))
+			.expect("There should be runtime version at 0");
+		let genesis_hash: node_primitives::Hash = client.block_hash(Zero::zero())
+			.expect("Database error?")
+			.expect("Genesis block always exists; qed")
+			.into();
+
+		let mut pending = self.keyring.seed_calls().into_iter().map(|(sender, nonce, function)| {
+			let signed = self.keyring.sign(
+				CheckedExtrinsic {
+					signed: Some((sender, signed_extra(nonce, 0))),
+					function,
+				},
+				runtime_version.spec_version,
+				runtime_version.transaction_version,
+				genesis_hash.into(),
+			);
+			OpaqueExtrinsic::decode(&mut &signed.encode()[..]).expect("Failed to decode opaque")
+		}).peekable();
+
+		while pending.peek().is_some() {
+			let mut block = client
+				.new_block(Default::default())
+				.expect("Block creation failed");
+
+			for extrinsic in Self::inherents_at_best(&client, 0) {
+				block.push(extrinsic).expect("Push inherent failed");
+			}
+
+			let mut pushed = 0;
+			while let Some(opaque) = pending.peek() {
+				match block.push(opaque.clone()) {
+					Err(sp_blockchain::Error::ApplyExtrinsicFailed(
+							sp_blockchain::ApplyExtrinsicFailed::Validity(e)
+					)) if e.exhausted_resources() && pushed > 0 => {
+						break;
+					},
+					Err(err) => panic!("Error pushing seeding transaction: {:?}", err),
+					Ok(_) => {
+						pending.next();
+						pushed += 1;
+					},
+				}
+			}
+
+			let block = block.build().expect("Block build failed").block;
+
+			let mut import_params = BlockImportParams::new(BlockOrigin::Own, block.header.clone());
+			import_params.body = Some(block.extrinsics().to_vec());
+			import_params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+			match client.import_block(import_params, Default::default())
+				.expect("Failed to import seeding block")
+			{
+				ImportResult::Imported(_) => {},
+				result => panic!("Seeding block not imported: {:?}", result),
+			}
+
+			log::info!(
+				target: "bench-logistics",
+				"Seeded block #{} ({} tx)",
+				block.header.number, pushed,
+			);
+		}
+	}
+
 	/// Iterate over some block content with transaction signed using this database keyring.
 	pub fn block_content(&self, content: BlockContent, client: &Client) -> BlockContentIterator {
 		BlockContentIterator::new(content, &self.keyring, client)
@@ -605,12 +780,140 @@ impl BenchKeyring {
 	}
 
 	/// Generate genesis with accounts from this keyring endowed with some balance.
+	///
+	/// Keyrings large enough to hold the accounts of the seeded state also get `WUSD`,
+	/// the enabled dex pairs, and the travel cabins and dpos of the seeded state.
 	pub fn generate_genesis(&self) -> spanner_runtime::GenesisConfig {
-		crate::genesis::config_endowed(
+		let mut genesis = crate::genesis::config_endowed(
 			false,
 			Some(spanner_runtime::wasm_binary_unwrap()),
 			self.collect_account_ids(),
-		)
+		);
+
+		if self.accounts.len() > SEEDED_ACCOUNTS {
+			let provider = self.liquidity_provider();
+			let mut endowed_accounts: Vec<_> = self.accounts.keys()
+				.filter(|account| **account != provider)
+				.map(|account| (account.clone(), WUSD, 100 * DOLLARS))
+				.collect();
+			endowed_accounts.extend(
+				[WUSD, NCAT, PLKT].iter().map(|token| (provider.clone(), *token, 10 * SEEDED_POOL_LIQUIDITY))
+			);
+			genesis.orml_tokens = Some(TokensConfig { endowed_accounts });
+
+			genesis.pallet_dex = Some(DexConfig {
+				initial_listing_trading_pairs: vec![],
+				initial_enabled_trading_pairs: vec![TradingPair::new(WUSD, NCAT), TradingPair::new(NCAT, PLKT)],
+				initial_added_liquidity_pools: vec![],
+			});
+
+			let large_dpos = (0..SEEDED_LARGE_DPOS).map(|large_dpo| (
+				self.large_dpo_manager(large_dpo),
+				b"bench large dpo".to_vec(),
+				LARGE_DPO_CABIN,
+				LARGE_DPO_TARGET / 100,
+				0,
+				SEEDED_DPO_END,
+			));
+			let open_dpos = (0..SEEDED_OPEN_DPOS).map(|open_dpo| (
+				self.open_dpo_manager(open_dpo),
+				b"bench open dpo".to_vec(),
+				OPEN_DPO_CABIN,
+				OPEN_DPO_TARGET / 100,
+				0,
+				SEEDED_DPO_END,
+			));
+			genesis.pallet_bullet_train = Some(BulletTrainConfig {
+				engineer_endowment: vec![(BOLT, 10_000 * DOLLARS)],
+				initial_travel_cabins: vec![
+					(
+						BOLT,
+						b"bench large dpo cabin".to_vec(),
+						LARGE_DPO_TARGET,
+						10 * DOLLARS,
+						100 * DOLLARS,
+						1,
+						SEEDED_LARGE_DPOS as u16,
+						YieldSchedule::Linear,
+					),
+					(
+						BOLT,
+						b"bench open dpo cabin".to_vec(),
+						OPEN_DPO_TARGET,
+						10 * DOLLARS,
+						100 * DOLLARS,
+						SEEDED_DPO_END,
+						1,
+						YieldSchedule::Linear,
+					),
+				],
+				initial_milestone_rewards: vec![],
+				initial_dpos: large_dpos.chain(open_dpos).collect(),
+			});
+		}
+
+		genesis
+	}
+
+	/// Calls of the seeding blocks, with their sender and nonce: the liquidity of both dex
+	/// pools, then for each large dpo the purchases of its members and of its cabin.
+	fn seed_calls(&self) -> Vec<(AccountId, Index, Call)> {
+		assert!(self.accounts.len() > SEEDED_ACCOUNTS, "Keyring too small for the seeded state");
+
+		let provider = self.liquidity_provider();
+		let mut calls = vec![
+			(
+				provider.clone(),
+				0,
+				Call::Dex(DexCall::add_liquidity(WUSD, NCAT, SEEDED_POOL_LIQUIDITY, SEEDED_POOL_LIQUIDITY)),
+			),
+			(
+				provider,
+				1,
+				Call::Dex(DexCall::add_liquidity(NCAT, PLKT, SEEDED_POOL_LIQUIDITY, SEEDED_POOL_LIQUIDITY)),
+			),
+		];
+
+		for large_dpo in 0..SEEDED_LARGE_DPOS {
+			calls.extend((0..LARGE_DPO_MEMBERS).map(|member| (
+				self.large_dpo_member(large_dpo, member),
+				0,
+				Call::BulletTrain(BulletTrainCall::passenger_buy_dpo_share(
+					large_dpo as u32,
+					LARGE_DPO_TARGET / 100,
+					None,
+				)),
+			)));
+			calls.push((
+				self.large_dpo_manager(large_dpo),
+				0,
+				Call::BulletTrain(BulletTrainCall::dpo_buy_travel_cabin(large_dpo as u32, LARGE_DPO_CABIN)),
+			));
+		}
+
+		calls
+	}
+
+	// The accounts of the seeded state, at the end of the keyring so that the block
+	// content keeps sending from fresh accounts.
+	fn seeded_account(&self, offset: usize) -> AccountId {
+		self.at(self.accounts.len() - SEEDED_ACCOUNTS + offset)
+	}
+
+	fn liquidity_provider(&self) -> AccountId {
+		self.seeded_account(0)
+	}
+
+	fn large_dpo_manager(&self, large_dpo: usize) -> AccountId {
+		self.seeded_account(1 + large_dpo)
+	}
+
+	fn large_dpo_member(&self, large_dpo: usize, member: usize) -> AccountId {
+		self.seeded_account(1 + SEEDED_LARGE_DPOS + large_dpo * LARGE_DPO_MEMBERS + member)
+	}
+
+	fn open_dpo_manager(&self, open_dpo: usize) -> AccountId {
+		self.seeded_account(1 + SEEDED_LARGE_DPOS * (1 + LARGE_DPO_MEMBERS) + open_dpo)
 	}
 }
 
@@ -686,7 +989,7 @@ impl BenchContext {
 		import_params.body = Some(block.extrinsics().to_vec());
 		import_params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
 
-		assert_eq!(self.client.chain_info().best_number, 0);
+		let best_number = self.client.chain_info().best_number;
 
 		assert_eq!(
 			self.client.import_block(import_params, Default::default())
@@ -702,7 +1005,7 @@ impl BenchContext {
 			)
 		);
 
-		assert_eq!(self.client.chain_info().best_number, 1);
+		assert_eq!(self.client.chain_info().best_number, best_number + 1);
 	}
 
 	/// Database path for the current context.
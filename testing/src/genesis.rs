@@ -125,6 +125,7 @@ pub fn config_endowed(
         orml_tokens: Some(TokensConfig {
             endowed_accounts: vec![],
         }),
-        pallet_dex: Some(Default::default())
+        pallet_dex: Some(Default::default()),
+        pallet_bullet_train: Some(Default::default()),
     }
 }
@@ -24,3 +24,5 @@ pub mod client;
 pub mod genesis;
 pub mod keyring;
 pub mod bench;
+#[cfg(feature = "dev-node")]
+pub mod node;
@@ -0,0 +1,93 @@
+//! An in-process development node, for testing clients that talk to the node over rpc.
+
+use std::net::TcpListener;
+use futures::FutureExt;
+use sc_cli::SubstrateCli;
+use sc_service::{TaskManager, TaskType};
+use structopt::StructOpt;
+
+/// A `--dev` node authoring blocks as Alice, on a temporary database.
+///
+/// The node runs on its own tokio runtime until dropped. Its rpc and p2p ports are picked
+/// among the free local ports, so that several nodes can run side by side.
+pub struct DevNode {
+	rpc_port: u16,
+	// dropped before the runtime, its tasks have to stop within it
+	task_manager: Option<TaskManager>,
+	runtime: tokio::runtime::Runtime,
+	_base_path: tempfile::TempDir,
+}
+
+impl DevNode {
+	/// Start a development node of the spanner chain.
+	pub fn start() -> Self {
+		let base_path = tempfile::tempdir().expect("Failed to create the node base path");
+		let rpc_port = free_port();
+
+		let cli = node_cli::Cli::from_iter(&[
+			"substrate".to_string(),
+			"--dev".to_string(),
+			"--base-path".to_string(),
+			base_path.path().display().to_string(),
+			"--rpc-port".to_string(),
+			rpc_port.to_string(),
+			"--ws-port".to_string(),
+			free_port().to_string(),
+			"--port".to_string(),
+			free_port().to_string(),
+			"--no-mdns".to_string(),
+			"--no-prometheus".to_string(),
+			"--no-telemetry".to_string(),
+		]);
+
+		let runtime = tokio::runtime::Builder::new()
+			.threaded_scheduler()
+			.enable_all()
+			.build()
+			.expect("Failed to create the node runtime");
+
+		let runtime_handle = runtime.handle().clone();
+		let task_executor = move |fut, task_type| {
+			match task_type {
+				TaskType::Async => runtime_handle.spawn(fut).map(drop),
+				TaskType::Blocking => runtime_handle
+					.spawn_blocking(move || futures::executor::block_on(fut))
+					.map(drop),
+			}
+		};
+
+		let config = cli.create_configuration(&cli.run, task_executor.into())
+			.expect("Failed to create the dev node configuration");
+		let task_manager = runtime.enter(|| node_cli::build_full(config))
+			.expect("Failed to start the dev node");
+
+		log::info!(target: "dev-node", "Dev node serving rpc on port {}", rpc_port);
+
+		DevNode {
+			rpc_port,
+			task_manager: Some(task_manager),
+			runtime,
+			_base_path: base_path,
+		}
+	}
+
+	/// Url of the http rpc endpoint of the node.
+	pub fn rpc_http_url(&self) -> String {
+		format!("http://127.0.0.1:{}", self.rpc_port)
+	}
+}
+
+impl Drop for DevNode {
+	fn drop(&mut self) {
+		let task_manager = self.task_manager.take();
+		self.runtime.enter(move || drop(task_manager));
+	}
+}
+
+// The port is released before the node binds it, another process could take it in between.
+fn free_port() -> u16 {
+	TcpListener::bind("127.0.0.1:0")
+		.and_then(|listener| listener.local_addr())
+		.map(|addr| addr.port())
+		.expect("Failed to find a free local port")
+}
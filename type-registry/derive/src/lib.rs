@@ -0,0 +1,144 @@
+//! Derive of `node_type_registry::TypeInfo`.
+//!
+//! The fields are described with their types as written, generic parameters and associated
+//! types included, so that the derived impl does not need any bound on the type parameters.
+//! The `#[codec(compact)]` and `#[codec(skip)]` attributes are followed, so that the
+//! description matches the encoding.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+	ext::IdentExt, parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument, Meta,
+	NestedMeta, PathArguments, Type,
+};
+
+/// Implements `TypeInfo` for a struct or an enum.
+#[proc_macro_derive(TypeInfo)]
+pub fn derive_type_info(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	expand(input).unwrap_or_else(|e| e.to_compile_error()).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+	let ident = &input.ident;
+	let name = ident.unraw().to_string();
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+	let type_def = match &input.data {
+		Data::Struct(data) => {
+			let fields = fields(&data.fields)?;
+			quote!(::node_type_registry::TypeDef::Composite(#fields))
+		},
+		Data::Enum(data) => {
+			let mut variants = Vec::new();
+			for variant in data.variants.iter() {
+				if has_codec_attr(&variant.attrs, "skip")? {
+					continue;
+				}
+				// the registry lists the variants by position
+				if has_codec_attr(&variant.attrs, "index")? || variant.discriminant.is_some() {
+					return Err(syn::Error::new_spanned(
+						variant,
+						"explicit variant indices are not supported by the type registry",
+					));
+				}
+				let name = variant.ident.unraw().to_string();
+				let fields = fields(&variant.fields)?;
+				variants.push(quote!(::node_type_registry::Variant { name: #name, fields: #fields }));
+			}
+			quote!(::node_type_registry::TypeDef::Variant(::std::vec![#(#variants),*]))
+		},
+		Data::Union(_) => return Err(syn::Error::new_spanned(
+			ident,
+			"unions are not supported by the type registry",
+		)),
+	};
+
+	Ok(quote! {
+		impl #impl_generics ::node_type_registry::TypeInfo for #ident #ty_generics #where_clause {
+			const NAME: &'static str = #name;
+
+			fn type_def() -> ::node_type_registry::TypeDef {
+				#type_def
+			}
+		}
+	})
+}
+
+fn fields(fields: &Fields) -> syn::Result<TokenStream2> {
+	let mut described = Vec::new();
+	for field in fields.iter() {
+		if has_codec_attr(&field.attrs, "skip")? {
+			continue;
+		}
+		let name = match &field.ident {
+			Some(ident) => {
+				let name = ident.unraw().to_string();
+				quote!(::std::option::Option::Some(#name))
+			},
+			None => quote!(::std::option::Option::None),
+		};
+		let mut ty = type_name(&field.ty)?;
+		if has_codec_attr(&field.attrs, "compact")? {
+			ty = quote!(::node_type_registry::TypeName::Compact(::std::boxed::Box::new(#ty)));
+		}
+		described.push(quote!(::node_type_registry::Field { name: #name, ty: #ty }));
+	}
+	Ok(quote!(::std::vec![#(#described),*]))
+}
+
+// Path types are named by their last segment, dropping `T::` and module paths.
+fn type_name(ty: &Type) -> syn::Result<TokenStream2> {
+	match ty {
+		Type::Path(type_path) => {
+			let segment = type_path.path.segments.last()
+				.ok_or_else(|| syn::Error::new_spanned(ty, "empty type path"))?;
+			let ident = segment.ident.unraw().to_string();
+			let mut args = Vec::new();
+			match &segment.arguments {
+				PathArguments::None => {},
+				PathArguments::AngleBracketed(generic_args) => {
+					for arg in generic_args.args.iter() {
+						if let GenericArgument::Type(arg) = arg {
+							args.push(type_name(arg)?);
+						}
+					}
+				},
+				PathArguments::Parenthesized(_) => return Err(unsupported(ty)),
+			}
+			Ok(quote!(::node_type_registry::TypeName::Path(#ident, ::std::vec![#(#args),*])))
+		},
+		Type::Tuple(tuple) => {
+			let elems = tuple.elems.iter().map(type_name).collect::<syn::Result<Vec<_>>>()?;
+			Ok(quote!(::node_type_registry::TypeName::Tuple(::std::vec![#(#elems),*])))
+		},
+		Type::Array(array) => {
+			let elem = type_name(&array.elem)?;
+			let len = array.len.to_token_stream().to_string();
+			Ok(quote!(::node_type_registry::TypeName::Array(::std::boxed::Box::new(#elem), #len)))
+		},
+		Type::Paren(paren) => type_name(&paren.elem),
+		Type::Group(group) => type_name(&group.elem),
+		_ => Err(unsupported(ty)),
+	}
+}
+
+fn unsupported(ty: &Type) -> syn::Error {
+	syn::Error::new_spanned(ty, "type not supported by the type registry")
+}
+
+fn has_codec_attr(attrs: &[Attribute], name: &str) -> syn::Result<bool> {
+	for attr in attrs.iter().filter(|attr| attr.path.is_ident("codec")) {
+		if let Meta::List(list) = attr.parse_meta()? {
+			let found = list.nested.iter().any(|nested| match nested {
+				NestedMeta::Meta(meta) => meta.path().is_ident(name),
+				NestedMeta::Lit(_) => false,
+			});
+			if found {
+				return Ok(true);
+			}
+		}
+	}
+	Ok(false)
+}
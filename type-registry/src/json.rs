@@ -0,0 +1,76 @@
+//! Minimal json values, keeping the order of the object keys.
+
+use std::fmt::Write;
+
+/// A json value of the type registries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Json {
+	Null,
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(String, Json)>),
+}
+
+impl Json {
+	/// Pretty printed with an indentation of two spaces, as `JSON.stringify(value, null, 2)`.
+	pub fn pretty(&self) -> String {
+		let mut out = String::new();
+		self.write(&mut out, 0);
+		out
+	}
+
+	fn write(&self, out: &mut String, indent: usize) {
+		match self {
+			Json::Null => out.push_str("null"),
+			Json::String(s) => write_string(out, s),
+			Json::Array(items) if items.is_empty() => out.push_str("[]"),
+			Json::Array(items) => {
+				out.push('[');
+				for (i, item) in items.iter().enumerate() {
+					out.push_str(if i == 0 { "\n" } else { ",\n" });
+					push_indent(out, indent + 1);
+					item.write(out, indent + 1);
+				}
+				out.push('\n');
+				push_indent(out, indent);
+				out.push(']');
+			},
+			Json::Object(entries) if entries.is_empty() => out.push_str("{}"),
+			Json::Object(entries) => {
+				out.push('{');
+				for (i, (key, value)) in entries.iter().enumerate() {
+					out.push_str(if i == 0 { "\n" } else { ",\n" });
+					push_indent(out, indent + 1);
+					write_string(out, key);
+					out.push_str(": ");
+					value.write(out, indent + 1);
+				}
+				out.push('\n');
+				push_indent(out, indent);
+				out.push('}');
+			},
+		}
+	}
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+	for _ in 0..indent {
+		out.push_str("  ");
+	}
+}
+
+fn write_string(out: &mut String, s: &str) {
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			c if (c as u32) < 0x20 => {
+				let _ = write!(out, "\\u{:04x}", c as u32);
+			},
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
@@ -0,0 +1,383 @@
+//! Type definitions of the runtime for polkadot.js and py-substrate-interface.
+//!
+//! The custom types of the pallets derive [`TypeInfo`], describing their fields and variants
+//! with the types as written in Rust. A [`Registry`] of these types renders `types.json`, the
+//! type definitions of polkadot.js, and its `types_mapping.json` counterpart for
+//! py-substrate-interface, so that both follow the runtime types instead of being maintained
+//! by hand.
+//!
+//! Unlike scale-info, the description does not require the type parameters and the field types
+//! to be described themselves: like the polkadot.js definitions, the registry refers to them by
+//! name (`Balance`, `AccountId`, `FixedU128`, ...). Generic arguments are dropped from the names,
+//! except for the containers known to polkadot.js (`Vec<Buyer>`, `Option<BlockNumber>`, ...).
+
+#![warn(missing_docs)]
+
+mod json;
+
+use std::fmt;
+use json::Json;
+
+pub use node_type_registry_derive::TypeInfo;
+
+// lets the derive name this crate from within its own tests
+extern crate self as node_type_registry;
+
+// generic types keeping their arguments in the registry
+const CONTAINERS: [&str; 5] = ["Vec", "Option", "BTreeMap", "BTreeSet", "Result"];
+
+/// A type described for the registry. Derived with `#[derive(TypeInfo)]`.
+pub trait TypeInfo {
+	/// Name of the type in the registry, without its generic parameters.
+	const NAME: &'static str;
+
+	/// Fields or variants of the type.
+	fn type_def() -> TypeDef;
+}
+
+/// The fields of a struct or the variants of an enum, in their encoding order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDef {
+	/// A struct, with named fields or as a tuple struct.
+	Composite(Vec<Field>),
+	/// An enum.
+	Variant(Vec<Variant>),
+}
+
+/// A field of a struct or an enum variant, unnamed for tuples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+	/// Name of the field.
+	pub name: Option<&'static str>,
+	/// Type of the field, as written.
+	pub ty: TypeName,
+}
+
+/// A variant of an enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+	/// Name of the variant.
+	pub name: &'static str,
+	/// Fields of the variant, none for a unit variant.
+	pub fields: Vec<Field>,
+}
+
+/// A type as written in a field. Displayed with its name in the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeName {
+	/// A type named by the last segment of its path, with its generic arguments.
+	Path(&'static str, Vec<TypeName>),
+	/// A tuple, the unit type when empty.
+	Tuple(Vec<TypeName>),
+	/// An array, with its length as written.
+	Array(Box<TypeName>, &'static str),
+	/// A field encoded with `#[codec(compact)]`.
+	Compact(Box<TypeName>),
+}
+
+impl fmt::Display for TypeName {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			// the byte strings of the pallets are utf-8 names
+			TypeName::Path("Vec", args) if args == &[TypeName::Path("u8", Vec::new())] => f.write_str("Text"),
+			TypeName::Path(name, args) if CONTAINERS.contains(name) && !args.is_empty() => {
+				write!(f, "{}<{}>", name, join(args))
+			},
+			TypeName::Path(name, _) => f.write_str(name),
+			TypeName::Tuple(elems) if elems.is_empty() => f.write_str("Null"),
+			TypeName::Tuple(elems) => write!(f, "({})", join(elems)),
+			TypeName::Array(elem, len) => write!(f, "[{}; {}]", elem, len),
+			TypeName::Compact(ty) => write!(f, "Compact<{}>", ty),
+		}
+	}
+}
+
+fn join(types: &[TypeName]) -> String {
+	types.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Implemented by a type for itself only, to check an alias against the type it aliases.
+pub trait Is<T> {}
+
+impl<T> Is<T> for T {}
+
+#[derive(Debug, Clone)]
+enum Definition {
+	Alias(&'static str),
+	Type(TypeDef),
+}
+
+/// The custom types of a runtime, in their registration order.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+	types: Vec<(&'static str, Definition)>,
+}
+
+impl Registry {
+	/// An empty registry.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers `T` under its name.
+	pub fn register<T: TypeInfo>(&mut self) -> &mut Self {
+		self.insert(T::NAME, Definition::Type(T::type_def()))
+	}
+
+	/// Registers `name` as an alias of `target`. `A`, the aliased type, has to be `T`, the type
+	/// registered as `target`.
+	pub fn alias<A: Is<T>, T>(&mut self, name: &'static str, target: &'static str) -> &mut Self {
+		self.insert(name, Definition::Alias(target))
+	}
+
+	fn insert(&mut self, name: &'static str, definition: Definition) -> &mut Self {
+		assert!(
+			self.types.iter().all(|(registered, _)| *registered != name),
+			"{} is registered twice",
+			name
+		);
+		self.types.push((name, definition));
+		self
+	}
+
+	/// The polkadot.js type definitions, the content of `types.json`.
+	pub fn types_json(&self) -> String {
+		self.definitions().pretty() + "\n"
+	}
+
+	/// The py-substrate-interface type mappings, the content of `types_mapping.json`.
+	pub fn types_mapping_json(&self) -> String {
+		let mappings = match self.definitions() {
+			Json::Object(definitions) => definitions.into_iter()
+				.map(|(name, definition)| (name, type_mapping(definition)))
+				.collect(),
+			_ => unreachable!("the definitions are an object; qed"),
+		};
+		Json::Object(mappings).pretty() + "\n"
+	}
+
+	fn definitions(&self) -> Json {
+		Json::Object(self.types.iter()
+			.map(|(name, definition)| {
+				let json = match definition {
+					Definition::Alias(target) => Json::String(target.to_string()),
+					Definition::Type(TypeDef::Composite(fields)) => composite(fields),
+					Definition::Type(TypeDef::Variant(variants)) => enumeration(variants),
+				};
+				(name.to_string(), json)
+			})
+			.collect())
+	}
+}
+
+// named fields as an object, unnamed fields as a tuple
+fn composite(fields: &[Field]) -> Json {
+	let named = fields.iter()
+		.map(|field| field.name.map(|name| (name.to_string(), Json::String(field.ty.to_string()))))
+		.collect::<Option<Vec<_>>>();
+	match named {
+		Some(named) if !named.is_empty() => Json::Object(named),
+		_ => match fields {
+			[field] => Json::String(field.ty.to_string()),
+			_ => Json::String(TypeName::Tuple(fields.iter().map(|field| field.ty.clone()).collect()).to_string()),
+		},
+	}
+}
+
+// a list of names for unit variants only, an object of the variant fields otherwise
+fn enumeration(variants: &[Variant]) -> Json {
+	let variants = if variants.iter().all(|variant| variant.fields.is_empty()) {
+		Json::Array(variants.iter().map(|variant| Json::String(variant.name.to_string())).collect())
+	} else {
+		Json::Object(variants.iter()
+			.map(|variant| {
+				let fields = if variant.fields.is_empty() {
+					Json::Null
+				} else {
+					composite(&variant.fields)
+				};
+				(variant.name.to_string(), fields)
+			})
+			.collect())
+	};
+	Json::Object(vec![("_enum".to_string(), variants)])
+}
+
+// the conversion of a polkadot.js definition, as done by py-substrate-interface
+fn type_mapping(definition: Json) -> Json {
+	let string = |s: &str| Json::String(s.to_string());
+	let pairs = |entries: Vec<(String, Json)>| Json::Array(entries.into_iter()
+		.map(|(name, json)| {
+			let json = if json == Json::Null { string("null") } else { json };
+			Json::Array(vec![Json::String(name), json])
+		})
+		.collect());
+
+	match definition {
+		Json::Object(mut entries) if entries.len() == 1 && entries[0].0 == "_enum" => {
+			match entries.remove(0).1 {
+				Json::Array(names) => Json::Object(vec![
+					("type".to_string(), string("enum")),
+					("value_list".to_string(), Json::Array(names)),
+				]),
+				Json::Object(variants) => Json::Object(vec![
+					("type".to_string(), string("enum")),
+					("type_mapping".to_string(), pairs(variants)),
+				]),
+				_ => unreachable!("enums are a list or an object; qed"),
+			}
+		},
+		Json::Object(fields) => Json::Object(vec![
+			("type".to_string(), string("struct")),
+			("type_mapping".to_string(), pairs(fields)),
+		]),
+		definition => definition,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[allow(dead_code)]
+	#[derive(TypeInfo)]
+	struct Info<Balance, AccountId, BlockNumber> {
+		name: Vec<u8>,
+		owner: AccountId,
+		#[codec(compact)]
+		amount: Balance,
+		shares: (Balance, Balance),
+		members: Vec<Buyer<AccountId>>,
+		expiry: Option<BlockNumber>,
+		#[codec(skip)]
+		cache: Vec<Balance>,
+	}
+
+	#[allow(dead_code)]
+	#[derive(TypeInfo)]
+	enum Buyer<AccountId> {
+		Passenger(AccountId),
+		Both(u32, AccountId),
+		Invalid,
+	}
+
+	#[allow(dead_code)]
+	#[derive(TypeInfo)]
+	enum State {
+		Created,
+		Active,
+	}
+
+	#[allow(dead_code)]
+	#[derive(TypeInfo)]
+	struct Pair(u8, u8);
+
+	fn registry() -> Registry {
+		let mut registry = Registry::new();
+		registry
+			.alias::<u32, u32>("Index", "u32")
+			.register::<Info<u128, [u8; 32], u32>>()
+			.register::<Buyer<()>>()
+			.register::<State>()
+			.register::<Pair>();
+		registry
+	}
+
+	#[test]
+	fn renders_the_polkadot_js_definitions() {
+		assert_eq!(registry().types_json(), r#"{
+  "Index": "u32",
+  "Info": {
+    "name": "Text",
+    "owner": "AccountId",
+    "amount": "Compact<Balance>",
+    "shares": "(Balance, Balance)",
+    "members": "Vec<Buyer>",
+    "expiry": "Option<BlockNumber>"
+  },
+  "Buyer": {
+    "_enum": {
+      "Passenger": "AccountId",
+      "Both": "(u32, AccountId)",
+      "Invalid": null
+    }
+  },
+  "State": {
+    "_enum": [
+      "Created",
+      "Active"
+    ]
+  },
+  "Pair": "(u8, u8)"
+}
+"#);
+	}
+
+	#[test]
+	fn renders_the_type_mappings() {
+		assert_eq!(registry().types_mapping_json(), r#"{
+  "Index": "u32",
+  "Info": {
+    "type": "struct",
+    "type_mapping": [
+      [
+        "name",
+        "Text"
+      ],
+      [
+        "owner",
+        "AccountId"
+      ],
+      [
+        "amount",
+        "Compact<Balance>"
+      ],
+      [
+        "shares",
+        "(Balance, Balance)"
+      ],
+      [
+        "members",
+        "Vec<Buyer>"
+      ],
+      [
+        "expiry",
+        "Option<BlockNumber>"
+      ]
+    ]
+  },
+  "Buyer": {
+    "type": "enum",
+    "type_mapping": [
+      [
+        "Passenger",
+        "AccountId"
+      ],
+      [
+        "Both",
+        "(u32, AccountId)"
+      ],
+      [
+        "Invalid",
+        "null"
+      ]
+    ]
+  },
+  "State": {
+    "type": "enum",
+    "value_list": [
+      "Created",
+      "Active"
+    ]
+  },
+  "Pair": "(u8, u8)"
+}
+"#);
+	}
+
+	#[test]
+	#[should_panic(expected = "State is registered twice")]
+	fn rejects_a_type_registered_twice() {
+		registry().register::<State>();
+	}
+}